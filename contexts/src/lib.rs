@@ -54,3 +54,4 @@ pub const TZJCSVM_V1: &str = include_str!("../tzjcsvm-2021-v1.jsonld");
 pub const EIP712VM: &str = include_str!("../eip712vm.jsonld");
 pub const EPSIG_V0_1: &str = include_str!("../epsig-v0.1.jsonld");
 pub const SOLVM: &str = include_str!("../solvm.jsonld");
+pub const BTCMSG_V0_1: &str = include_str!("../btcmsg-v0.1.jsonld");
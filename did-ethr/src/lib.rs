@@ -5,14 +5,16 @@ use std::collections::BTreeMap;
 use ssi::caip10::BlockchainAccountId;
 use ssi::caip2::ChainId;
 use ssi::did::{
-    Context, Contexts, DIDMethod, Document, Source, VerificationMethod, VerificationMethodMap,
-    DEFAULT_CONTEXT, DIDURL,
+    Context, Contexts, DIDMethod, Document, GenerateError, Source, VerificationMethod,
+    VerificationMethodMap, DEFAULT_CONTEXT, DIDURL,
 };
 use ssi::did_resolve::{
     DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata, ERROR_INVALID_DID,
     TYPE_DID_LD_JSON,
 };
 
+pub mod registry;
+
 /// did:ethr DID Method
 ///
 /// [Specification](https://github.com/decentralized-identity/ethr-did-resolver/)
@@ -173,6 +175,92 @@ fn resolve_pk(
     (res_meta, Some(doc), Some(doc_meta))
 }
 
+/// Resolve an Ethr DID controlled by an account address
+fn resolve_address(
+    did: &str,
+    chain_id: i64,
+    account_address: String,
+) -> (
+    ResolutionMetadata,
+    Option<Document>,
+    Option<DocumentMetadata>,
+) {
+    let mut context = BTreeMap::new();
+    context.insert(
+        "blockchainAccountId".to_string(),
+        Value::String("https://w3id.org/security#blockchainAccountId".to_string()),
+    );
+    context.insert(
+        "EcdsaSecp256k1RecoveryMethod2020".to_string(),
+        Value::String("https://identity.foundation/EcdsaSecp256k1RecoverySignature2020#EcdsaSecp256k1RecoveryMethod2020".to_string()),
+    );
+    context.insert(
+        "Eip712Method2021".to_string(),
+        Value::String("https://w3id.org/security#Eip712Method2021".to_string()),
+    );
+
+    let blockchain_account_id = BlockchainAccountId {
+        account_address,
+        chain_id: ChainId {
+            namespace: "eip155".to_string(),
+            reference: chain_id.to_string(),
+        },
+    };
+    let vm_didurl = DIDURL {
+        did: did.to_string(),
+        fragment: Some("controller".to_string()),
+        ..Default::default()
+    };
+    let eip712vm_didurl = DIDURL {
+        did: did.to_string(),
+        fragment: Some("Eip712Method2021".to_string()),
+        ..Default::default()
+    };
+    let vm = VerificationMethod::Map(VerificationMethodMap {
+        id: vm_didurl.to_string(),
+        type_: "EcdsaSecp256k1RecoveryMethod2020".to_string(),
+        controller: did.to_string(),
+        blockchain_account_id: Some(blockchain_account_id.to_string()),
+        ..Default::default()
+    });
+    let eip712vm = VerificationMethod::Map(VerificationMethodMap {
+        id: eip712vm_didurl.to_string(),
+        type_: "Eip712Method2021".to_string(),
+        controller: did.to_string(),
+        blockchain_account_id: Some(blockchain_account_id.to_string()),
+        ..Default::default()
+    });
+
+    let doc = Document {
+        context: Contexts::Many(vec![
+            Context::URI(DEFAULT_CONTEXT.to_string()),
+            Context::Object(context),
+        ]),
+        id: did.to_string(),
+        authentication: Some(vec![
+            VerificationMethod::DIDURL(vm_didurl.clone()),
+            VerificationMethod::DIDURL(eip712vm_didurl.clone()),
+        ]),
+        assertion_method: Some(vec![
+            VerificationMethod::DIDURL(vm_didurl),
+            VerificationMethod::DIDURL(eip712vm_didurl),
+        ]),
+        verification_method: Some(vec![vm, eip712vm]),
+        ..Default::default()
+    };
+
+    let res_meta = ResolutionMetadata {
+        content_type: Some(TYPE_DID_LD_JSON.to_string()),
+        ..Default::default()
+    };
+
+    let doc_meta = DocumentMetadata {
+        ..Default::default()
+    };
+
+    (res_meta, Some(doc), Some(doc_meta))
+}
+
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl DIDResolver for DIDEthr {
@@ -207,80 +295,7 @@ impl DIDResolver for DIDEthr {
             }
         };
 
-        let mut context = BTreeMap::new();
-        context.insert(
-            "blockchainAccountId".to_string(),
-            Value::String("https://w3id.org/security#blockchainAccountId".to_string()),
-        );
-        context.insert(
-            "EcdsaSecp256k1RecoveryMethod2020".to_string(),
-            Value::String("https://identity.foundation/EcdsaSecp256k1RecoverySignature2020#EcdsaSecp256k1RecoveryMethod2020".to_string()),
-        );
-        context.insert(
-            "Eip712Method2021".to_string(),
-            Value::String("https://w3id.org/security#Eip712Method2021".to_string()),
-        );
-
-        let blockchain_account_id = BlockchainAccountId {
-            account_address,
-            chain_id: ChainId {
-                namespace: "eip155".to_string(),
-                reference: chain_id.to_string(),
-            },
-        };
-        let vm_didurl = DIDURL {
-            did: did.to_string(),
-            fragment: Some("controller".to_string()),
-            ..Default::default()
-        };
-        let eip712vm_didurl = DIDURL {
-            did: did.to_string(),
-            fragment: Some("Eip712Method2021".to_string()),
-            ..Default::default()
-        };
-        let vm = VerificationMethod::Map(VerificationMethodMap {
-            id: vm_didurl.to_string(),
-            type_: "EcdsaSecp256k1RecoveryMethod2020".to_string(),
-            controller: did.to_string(),
-            blockchain_account_id: Some(blockchain_account_id.to_string()),
-            ..Default::default()
-        });
-        let eip712vm = VerificationMethod::Map(VerificationMethodMap {
-            id: eip712vm_didurl.to_string(),
-            type_: "Eip712Method2021".to_string(),
-            controller: did.to_string(),
-            blockchain_account_id: Some(blockchain_account_id.to_string()),
-            ..Default::default()
-        });
-
-        let doc = Document {
-            context: Contexts::Many(vec![
-                Context::URI(DEFAULT_CONTEXT.to_string()),
-                Context::Object(context),
-            ]),
-            id: did.to_string(),
-            authentication: Some(vec![
-                VerificationMethod::DIDURL(vm_didurl.clone()),
-                VerificationMethod::DIDURL(eip712vm_didurl.clone()),
-            ]),
-            assertion_method: Some(vec![
-                VerificationMethod::DIDURL(vm_didurl),
-                VerificationMethod::DIDURL(eip712vm_didurl),
-            ]),
-            verification_method: Some(vec![vm, eip712vm]),
-            ..Default::default()
-        };
-
-        let res_meta = ResolutionMetadata {
-            content_type: Some(TYPE_DID_LD_JSON.to_string()),
-            ..Default::default()
-        };
-
-        let doc_meta = DocumentMetadata {
-            ..Default::default()
-        };
-
-        (res_meta, Some(doc), Some(doc_meta))
+        resolve_address(did, chain_id, account_address)
     }
 
     fn to_did_method(&self) -> Option<&dyn DIDMethod> {
@@ -293,24 +308,21 @@ impl DIDMethod for DIDEthr {
         "ethr"
     }
 
-    fn generate(&self, source: &Source) -> Option<String> {
+    fn generate(&self, source: &Source) -> Result<String, GenerateError> {
         let jwk = match source {
             Source::Key(jwk) => jwk,
             Source::KeyAndPattern(jwk, pattern) => {
                 if !pattern.is_empty() {
                     // TODO: support pattern
-                    return None;
+                    return Err(GenerateError::UnsupportedPattern(pattern.to_string()));
                 }
                 jwk
             }
-            _ => return None,
-        };
-        let hash = match ssi::keccak_hash::hash_public_key(jwk) {
-            Ok(hash) => hash,
-            _ => return None,
+            _ => return Err(GenerateError::UnsupportedSource),
         };
+        let hash = ssi::keccak_hash::hash_public_key(jwk)?;
         let did = format!("did:ethr:{}", hash);
-        Some(did)
+        Ok(did)
     }
 
     fn to_resolver(&self) -> &dyn DIDResolver {
@@ -0,0 +1,1094 @@
+//! [ERC-1056](https://github.com/ethereum/EIPs/issues/1056) registry resolution.
+//!
+//! The registry is read through the [`EthereumRPC`] trait, so that any JSON-RPC client (or a
+//! mock node) can be used.
+
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::str::FromStr;
+use thiserror::Error;
+
+use ssi::caip10::BlockchainAccountId;
+use ssi::caip2::ChainId;
+use ssi::did::{
+    Context, Contexts, DIDMethod, Document, GenerateError, Service, ServiceEndpoint, Source,
+    VerificationMethod, VerificationMethodMap, DIDURL,
+};
+use ssi::did_resolve::{
+    DIDResolver, DocumentMetadata, Metadata, ResolutionInputMetadata, ResolutionMetadata,
+    ERROR_INVALID_DID, ERROR_NOT_FOUND,
+};
+use ssi::jwk::{Base64urlUInt, OctetParams, Params, JWK};
+use ssi::one_or_many::OneOrMany;
+use ssi::USER_AGENT;
+
+use crate::{parse_did, resolve_address, resolve_pk, DIDEthr};
+
+/// Address of the ERC-1056 registry deployed on mainnet and most test networks.
+pub const DEFAULT_REGISTRY_ADDRESS: &str = "0xdca7ef03e98e0dc2b855be647c39abe984fcf21b";
+
+/// Function selector of `changed(address)`
+const CHANGED_SELECTOR: &str = "f96d0f9f";
+/// Topic of `DIDOwnerChanged(address,address,uint256)`
+const TOPIC_OWNER_CHANGED: &str =
+    "0x38a5a6e68f30ed1ab45860a4afb34bcb2fc00f22ca462d249b8a8d40cda6f7a3";
+/// Topic of `DIDDelegateChanged(address,bytes32,address,uint256,uint256)`
+const TOPIC_DELEGATE_CHANGED: &str =
+    "0x5a5084339536bcab65f20799fcc58724588145ca054bd2be626174b27ba156f7";
+/// Topic of `DIDAttributeChanged(address,bytes32,bytes,uint256,uint256)`
+const TOPIC_ATTRIBUTE_CHANGED: &str =
+    "0x18ab6b2ae3d64306c00ce663125f2bd680e441a098de1635bd7ad8b0d44965e4";
+
+const NULL_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+#[derive(Error, Debug)]
+pub enum RegistryError {
+    #[error("JSON-RPC error: {0}")]
+    RPC(String),
+    #[error("Invalid JSON-RPC response: {0}")]
+    InvalidResponse(String),
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+}
+
+/// Ethereum JSON-RPC client
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait EthereumRPC {
+    /// Make a JSON-RPC request, returning its result.
+    async fn request(&self, method: &str, params: Value) -> Result<Value, RegistryError>;
+}
+
+/// JSON-RPC client for an Ethereum node HTTP endpoint.
+pub struct HttpRPC {
+    pub url: String,
+    client: reqwest::Client,
+}
+
+impl HttpRPC {
+    pub fn new(url: &str) -> Result<Self, RegistryError> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            "User-Agent",
+            reqwest::header::HeaderValue::from_static(USER_AGENT),
+        );
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()?;
+        Ok(Self {
+            url: url.to_string(),
+            client,
+        })
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl EthereumRPC for HttpRPC {
+    async fn request(&self, method: &str, params: Value) -> Result<Value, RegistryError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let mut response: Value = self
+            .client
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(error) = response.get("error") {
+            return Err(RegistryError::RPC(error.to_string()));
+        }
+        response
+            .get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| RegistryError::InvalidResponse("missing result".to_string()))
+    }
+}
+
+/// ERC-1056 registry event
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistryEvent {
+    OwnerChanged {
+        owner: String,
+    },
+    DelegateChanged {
+        delegate_type: String,
+        delegate: String,
+        valid_to: u64,
+    },
+    AttributeChanged {
+        name: String,
+        value: Vec<u8>,
+        valid_to: u64,
+    },
+}
+
+/// Registry event with its position in the identity's change history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistryLog {
+    pub block_number: u64,
+    pub previous_change: u64,
+    pub event: RegistryEvent,
+}
+
+fn invalid_response(msg: &str) -> RegistryError {
+    RegistryError::InvalidResponse(msg.to_string())
+}
+
+fn decode_hex(value: &Value) -> Result<Vec<u8>, RegistryError> {
+    let hex_str = value
+        .as_str()
+        .ok_or_else(|| invalid_response("expected hex string"))?;
+    hex::decode(hex_str.strip_prefix("0x").unwrap_or(hex_str))
+        .map_err(|e| RegistryError::InvalidResponse(e.to_string()))
+}
+
+fn parse_quantity(value: &Value) -> Result<u64, RegistryError> {
+    let hex_str = value
+        .as_str()
+        .and_then(|s| s.strip_prefix("0x"))
+        .ok_or_else(|| invalid_response("expected quantity"))?;
+    u64::from_str_radix(hex_str, 16).map_err(|e| RegistryError::InvalidResponse(e.to_string()))
+}
+
+fn word(data: &[u8], i: usize) -> Result<&[u8], RegistryError> {
+    data.get(i * 32..(i + 1) * 32)
+        .ok_or_else(|| invalid_response("truncated event data"))
+}
+
+/// Decode a uint256, saturating at `u64::MAX`.
+fn word_to_u64(word: &[u8]) -> u64 {
+    if word[..24].iter().any(|b| *b != 0) {
+        return u64::MAX;
+    }
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&word[24..]);
+    u64::from_be_bytes(bytes)
+}
+
+fn word_to_address(word: &[u8]) -> String {
+    format!("0x{}", hex::encode(&word[12..]))
+}
+
+/// Decode a bytes32 holding a zero-padded string.
+fn word_to_string(word: &[u8]) -> String {
+    let end = word.iter().position(|b| *b == 0).unwrap_or(word.len());
+    String::from_utf8_lossy(&word[..end]).into_owned()
+}
+
+fn address_topic(address: &str) -> String {
+    format!("0x{:0>64}", &address[2..])
+}
+
+/// Decode a registry event from a log returned by `eth_getLogs`.
+pub fn decode_log(log: &Value) -> Result<Option<RegistryLog>, RegistryError> {
+    let topic = log
+        .get("topics")
+        .and_then(|topics| topics.get(0))
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid_response("missing log topic"))?;
+    let block_number = parse_quantity(
+        log.get("blockNumber")
+            .ok_or_else(|| invalid_response("missing log block number"))?,
+    )?;
+    let data = decode_hex(
+        log.get("data")
+            .ok_or_else(|| invalid_response("missing log data"))?,
+    )?;
+    let (event, previous_change) = match topic {
+        TOPIC_OWNER_CHANGED => (
+            RegistryEvent::OwnerChanged {
+                owner: word_to_address(word(&data, 0)?),
+            },
+            word_to_u64(word(&data, 1)?),
+        ),
+        TOPIC_DELEGATE_CHANGED => (
+            RegistryEvent::DelegateChanged {
+                delegate_type: word_to_string(word(&data, 0)?),
+                delegate: word_to_address(word(&data, 1)?),
+                valid_to: word_to_u64(word(&data, 2)?),
+            },
+            word_to_u64(word(&data, 3)?),
+        ),
+        TOPIC_ATTRIBUTE_CHANGED => {
+            // name, value offset, validTo, previousChange, then the value bytes
+            let offset = word_to_u64(word(&data, 1)?) as usize;
+            let value_start = offset
+                .checked_add(32)
+                .ok_or_else(|| invalid_response("invalid attribute value offset"))?;
+            let len_word = data
+                .get(offset..value_start)
+                .ok_or_else(|| invalid_response("truncated attribute value"))?;
+            let len = word_to_u64(len_word) as usize;
+            let value_end = value_start
+                .checked_add(len)
+                .ok_or_else(|| invalid_response("invalid attribute value length"))?;
+            let value = data
+                .get(value_start..value_end)
+                .ok_or_else(|| invalid_response("truncated attribute value"))?;
+            (
+                RegistryEvent::AttributeChanged {
+                    name: word_to_string(word(&data, 0)?),
+                    value: value.to_vec(),
+                    valid_to: word_to_u64(word(&data, 2)?),
+                },
+                word_to_u64(word(&data, 3)?),
+            )
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(RegistryLog {
+        block_number,
+        previous_change,
+        event,
+    }))
+}
+
+/// ERC-1056 registry on a network
+pub struct Network {
+    pub registry_address: String,
+    pub rpc: Box<dyn EthereumRPC + Send + Sync>,
+}
+
+impl Network {
+    /// Get the block number of the last change for an identity.
+    async fn changed(&self, identity: &str) -> Result<u64, RegistryError> {
+        let call = json!({
+            "to": self.registry_address,
+            "data": format!("0x{}{:0>64}", CHANGED_SELECTOR, &identity[2..]),
+        });
+        let result = self
+            .rpc
+            .request("eth_call", json!([call, "latest"]))
+            .await?;
+        Ok(word_to_u64(word(&decode_hex(&result)?, 0)?))
+    }
+
+    /// Get the change history of an identity, oldest first, by following the `previousChange`
+    /// links from the last change.
+    pub async fn history(&self, identity: &str) -> Result<Vec<RegistryLog>, RegistryError> {
+        let mut history = Vec::new();
+        let mut block = self.changed(identity).await?;
+        while block != 0 {
+            let filter = json!({
+                "address": self.registry_address,
+                "fromBlock": format!("0x{:x}", block),
+                "toBlock": format!("0x{:x}", block),
+                "topics": [Value::Null, address_topic(identity)],
+            });
+            let logs = self.rpc.request("eth_getLogs", json!([filter])).await?;
+            let logs = logs
+                .as_array()
+                .ok_or_else(|| invalid_response("expected logs array"))?;
+            let mut block_history = Vec::new();
+            for log in logs {
+                if let Some(log) = decode_log(log)? {
+                    block_history.push(log);
+                }
+            }
+            // Follow the earliest link, guarding against loops.
+            let previous = block_history
+                .iter()
+                .map(|log| log.previous_change)
+                .filter(|previous| *previous < block)
+                .min()
+                .unwrap_or(0);
+            block_history.append(&mut history);
+            history = block_history;
+            block = previous;
+        }
+        Ok(history)
+    }
+
+    /// Get the timestamp of a block.
+    async fn block_timestamp(&self, block: u64) -> Result<i64, RegistryError> {
+        let result = self
+            .rpc
+            .request(
+                "eth_getBlockByNumber",
+                json!([format!("0x{:x}", block), false]),
+            )
+            .await?;
+        let timestamp = parse_quantity(
+            result
+                .get("timestamp")
+                .ok_or_else(|| invalid_response("missing block timestamp"))?,
+        )?;
+        i64::try_from(timestamp).map_err(|_| invalid_response("block timestamp out of range"))
+    }
+}
+
+/// did:ethr DID Method, with ERC-1056 registry resolution
+///
+/// Documents are built from the registry's change history: owner changes replace the
+/// controller, and unexpired delegates and `did/pub/...`/`did/svc/...` attributes add
+/// verification methods and services. DIDs on networks without a configured registry are
+/// resolved as by [`DIDEthr`].
+#[derive(Default)]
+pub struct DIDEthrRegistry {
+    networks: HashMap<i64, Network>,
+}
+
+impl DIDEthrRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configure the registry and JSON-RPC client for a network, given by chain id.
+    pub fn insert_network(
+        &mut self,
+        chain_id: i64,
+        registry_address: &str,
+        rpc: Box<dyn EthereumRPC + Send + Sync>,
+    ) -> Option<Network> {
+        self.networks.insert(
+            chain_id,
+            Network {
+                registry_address: registry_address.to_lowercase(),
+                rpc,
+            },
+        )
+    }
+}
+
+/// Accumulated document properties, keyed by the event that added them so that later events
+/// can revoke them.
+#[derive(Default)]
+struct DocumentBuilder {
+    verification_methods: Vec<(String, VerificationMethodMap)>,
+    authentication: Vec<(String, String)>,
+    assertion_method: Vec<(String, String)>,
+    key_agreement: Vec<(String, String)>,
+    services: Vec<(String, Service)>,
+    delegate_count: usize,
+    service_count: usize,
+}
+
+impl DocumentBuilder {
+    fn revoke(&mut self, key: &str) {
+        self.verification_methods.retain(|(k, _)| k != key);
+        self.authentication.retain(|(k, _)| k != key);
+        self.assertion_method.retain(|(k, _)| k != key);
+        self.key_agreement.retain(|(k, _)| k != key);
+        self.services.retain(|(k, _)| k != key);
+    }
+
+    fn add_delegate(
+        &mut self,
+        key: String,
+        did: &str,
+        chain_id: i64,
+        delegate_type: &str,
+        delegate: &str,
+    ) {
+        // Re-adding a delegate replaces the earlier entry.
+        self.revoke(&key);
+        self.delegate_count += 1;
+        let id = format!("{}#delegate-{}", did, self.delegate_count);
+        let blockchain_account_id = BlockchainAccountId {
+            account_address: delegate.to_string(),
+            chain_id: ChainId {
+                namespace: "eip155".to_string(),
+                reference: chain_id.to_string(),
+            },
+        };
+        self.verification_methods.push((
+            key.clone(),
+            VerificationMethodMap {
+                id: id.clone(),
+                type_: "EcdsaSecp256k1RecoveryMethod2020".to_string(),
+                controller: did.to_string(),
+                blockchain_account_id: Some(blockchain_account_id.to_string()),
+                ..Default::default()
+            },
+        ));
+        match delegate_type {
+            "veriKey" => self.assertion_method.push((key, id)),
+            "sigAuth" => {
+                self.assertion_method.push((key.clone(), id.clone()));
+                self.authentication.push((key, id));
+            }
+            _ => {}
+        }
+    }
+
+    /// Add a `did/pub/<algorithm>[/<purpose>[/<encoding>]]` attribute.
+    fn add_public_key(&mut self, key: String, did: &str, parts: &[&str], value: &[u8]) {
+        self.revoke(&key);
+        self.delegate_count += 1;
+        let id = format!("{}#delegate-{}", did, self.delegate_count);
+        let algorithm = parts.get(2).copied().unwrap_or_default();
+        let purpose = parts.get(3).copied();
+        let encoding = parts.get(4).copied().unwrap_or("hex");
+        let type_ = match algorithm {
+            "Secp256k1" => "EcdsaSecp256k1VerificationKey2019",
+            "Ed25519" => "Ed25519VerificationKey2018",
+            "X25519" => "X25519KeyAgreementKey2019",
+            "RSA" => "RSAVerificationKey2018",
+            algorithm => algorithm,
+        };
+        let mut vm = VerificationMethodMap {
+            id: id.clone(),
+            type_: type_.to_string(),
+            controller: did.to_string(),
+            ..Default::default()
+        };
+        let okp = |curve: &str| {
+            JWK::from(Params::OKP(OctetParams {
+                curve: curve.to_string(),
+                public_key: Base64urlUInt(value.to_vec()),
+                private_key: None,
+            }))
+        };
+        // Use JWKs for key types that can be verified; otherwise keep the encoded value.
+        vm.public_key_jwk = match (algorithm, encoding) {
+            (_, "pem") => None,
+            ("Secp256k1", _) => ssi::jwk::secp256k1_parse(value).ok(),
+            ("Ed25519", _) => Some(okp("Ed25519")),
+            ("X25519", _) => Some(okp("X25519")),
+            _ => None,
+        };
+        if vm.public_key_jwk.is_none() {
+            let (property, encoded) = match encoding {
+                "pem" => ("publicKeyPem", String::from_utf8_lossy(value).into_owned()),
+                "base64" => ("publicKeyBase64", base64::encode(value)),
+                "base58" => ("publicKeyBase58", bs58::encode(value).into_string()),
+                _ => ("publicKeyHex", hex::encode(value)),
+            };
+            if property == "publicKeyBase58" {
+                vm.public_key_base58 = Some(encoded);
+            } else {
+                let mut property_set = BTreeMap::new();
+                property_set.insert(property.to_string(), Value::String(encoded));
+                vm.property_set = Some(property_set);
+            }
+        }
+        self.verification_methods.push((key.clone(), vm));
+        match purpose {
+            Some("veriKey") => self.assertion_method.push((key, id)),
+            Some("sigAuth") => {
+                self.assertion_method.push((key.clone(), id.clone()));
+                self.authentication.push((key, id));
+            }
+            Some("enc") => self.key_agreement.push((key, id)),
+            _ => {}
+        }
+    }
+
+    /// Add a `did/svc/<type>` attribute.
+    fn add_service(&mut self, key: String, did: &str, type_: &str, value: &[u8]) {
+        self.revoke(&key);
+        self.service_count += 1;
+        let endpoint = String::from_utf8_lossy(value).into_owned();
+        let service_endpoint = match serde_json::from_str(&endpoint) {
+            Ok(value @ Value::Object(_)) | Ok(value @ Value::Array(_)) => {
+                ServiceEndpoint::Map(value)
+            }
+            _ => ServiceEndpoint::URI(endpoint),
+        };
+        self.services.push((
+            key,
+            Service {
+                id: format!("{}#service-{}", did, self.service_count),
+                type_: OneOrMany::One(type_.to_string()),
+                service_endpoint: Some(OneOrMany::One(service_endpoint)),
+                property_set: None,
+            },
+        ));
+    }
+
+    /// Add the accumulated properties to a base document.
+    fn build(self, doc: &mut Document) {
+        let has_keys = self
+            .verification_methods
+            .iter()
+            .any(|(_, vm)| vm.type_ != "EcdsaSecp256k1RecoveryMethod2020");
+        let references = |entries: Vec<(String, String)>| {
+            entries
+                .into_iter()
+                .filter_map(|(_, id)| DIDURL::from_str(&id).ok())
+                .map(VerificationMethod::DIDURL)
+                .collect::<Vec<_>>()
+        };
+        let extend = |list: &mut Option<Vec<VerificationMethod>>,
+                      items: Vec<VerificationMethod>| {
+            if !items.is_empty() {
+                list.get_or_insert_with(Vec::new).extend(items);
+            }
+        };
+        extend(
+            &mut doc.verification_method,
+            self.verification_methods
+                .into_iter()
+                .map(|(_, vm)| VerificationMethod::Map(vm))
+                .collect(),
+        );
+        extend(&mut doc.authentication, references(self.authentication));
+        extend(&mut doc.assertion_method, references(self.assertion_method));
+        extend(&mut doc.key_agreement, references(self.key_agreement));
+        if !self.services.is_empty() {
+            doc.service = Some(self.services.into_iter().map(|(_, s)| s).collect());
+        }
+        if !has_keys {
+            return;
+        }
+        if let Contexts::Many(ref mut contexts) = doc.context {
+            if let Some(Context::Object(ref mut context)) = contexts.get_mut(1) {
+                for (term, iri) in &[
+                    (
+                        "EcdsaSecp256k1VerificationKey2019",
+                        "https://w3id.org/security#EcdsaSecp256k1VerificationKey2019",
+                    ),
+                    (
+                        "Ed25519VerificationKey2018",
+                        "https://w3id.org/security#Ed25519VerificationKey2018",
+                    ),
+                    (
+                        "X25519KeyAgreementKey2019",
+                        "https://w3id.org/security#X25519KeyAgreementKey2019",
+                    ),
+                ] {
+                    context
+                        .entry(term.to_string())
+                        .or_insert_with(|| Value::String(iri.to_string()));
+                }
+                context
+                    .entry("publicKeyJwk".to_string())
+                    .or_insert_with(|| {
+                        json!({
+                            "@id": "https://w3id.org/security#publicKeyJwk",
+                            "@type": "@json"
+                        })
+                    });
+            }
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl DIDResolver for DIDEthrRegistry {
+    async fn resolve(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        let (chain_id, addr_or_pk) = match parse_did(did) {
+            Some(parsed) => parsed,
+            None => {
+                return (
+                    ResolutionMetadata::from_error(ERROR_INVALID_DID),
+                    None,
+                    None,
+                )
+            }
+        };
+        let network = match self.networks.get(&chain_id) {
+            Some(network) => network,
+            None => return DIDEthr.resolve(did, input_metadata).await,
+        };
+        let identity = match addr_or_pk.len() {
+            42 => addr_or_pk.to_lowercase(),
+            68 => {
+                let pk = hex::decode(&addr_or_pk[2..])
+                    .ok()
+                    .and_then(|pk| ssi::jwk::secp256k1_parse(&pk).ok())
+                    .and_then(|jwk| ssi::keccak_hash::hash_public_key(&jwk).ok());
+                match pk {
+                    Some(address) => address,
+                    None => {
+                        return (
+                            ResolutionMetadata::from_error(ERROR_INVALID_DID),
+                            None,
+                            None,
+                        )
+                    }
+                }
+            }
+            _ => {
+                return (
+                    ResolutionMetadata::from_error(ERROR_INVALID_DID),
+                    None,
+                    None,
+                )
+            }
+        };
+        let version = match input_metadata.version_id {
+            Some(ref version_id) => match version_id.parse::<u64>() {
+                Ok(version) => Some(version),
+                Err(_) => return (ResolutionMetadata::from_error(ERROR_NOT_FOUND), None, None),
+            },
+            None => None,
+        };
+        let history = match network.history(&identity).await {
+            Ok(history) => history,
+            Err(err) => return (ResolutionMetadata::from_error(&err.to_string()), None, None),
+        };
+        let (history, later): (Vec<RegistryLog>, Vec<RegistryLog>) =
+            history.into_iter().partition(|log| match version {
+                Some(version) => log.block_number <= version,
+                None => true,
+            });
+
+        // Delegates and attributes are checked for expiry as of the resolved version.
+        let now = match version {
+            Some(version) => match network.block_timestamp(version).await {
+                Ok(timestamp) => timestamp as u64,
+                Err(err) => return (ResolutionMetadata::from_error(&err.to_string()), None, None),
+            },
+            None => Utc::now().timestamp() as u64,
+        };
+        let mut owner = identity.clone();
+        let mut builder = DocumentBuilder::default();
+        for log in &history {
+            match log.event {
+                RegistryEvent::OwnerChanged {
+                    owner: ref new_owner,
+                } => {
+                    owner = new_owner.clone();
+                }
+                RegistryEvent::DelegateChanged {
+                    ref delegate_type,
+                    ref delegate,
+                    valid_to,
+                } => {
+                    let key = format!("delegate-{}-{}", delegate_type, delegate);
+                    if valid_to >= now {
+                        builder.add_delegate(key, did, chain_id, delegate_type, delegate);
+                    } else {
+                        builder.delegate_count += 1;
+                        builder.revoke(&key);
+                    }
+                }
+                RegistryEvent::AttributeChanged {
+                    ref name,
+                    ref value,
+                    valid_to,
+                } => {
+                    let key = format!("attribute-{}-{}", name, hex::encode(value));
+                    let parts: Vec<&str> = name.split('/').collect();
+                    let section = match parts.as_slice() {
+                        ["did", section, _, ..] => *section,
+                        _ => continue,
+                    };
+                    match (section, valid_to >= now) {
+                        ("pub", true) => builder.add_public_key(key, did, &parts, value),
+                        ("svc", true) => builder.add_service(key, did, parts[2], value),
+                        ("pub", false) => {
+                            builder.delegate_count += 1;
+                            builder.revoke(&key);
+                        }
+                        ("svc", false) => {
+                            builder.service_count += 1;
+                            builder.revoke(&key);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let mut doc_meta = DocumentMetadata::default();
+        if let Some(last) = history.last() {
+            doc_meta.version_id = Some(last.block_number.to_string());
+            let updated = network
+                .block_timestamp(last.block_number)
+                .await
+                .and_then(|timestamp| {
+                    Utc.timestamp_opt(timestamp, 0)
+                        .single()
+                        .ok_or_else(|| invalid_response("block timestamp out of range"))
+                });
+            match updated {
+                Ok(updated) => doc_meta.updated = Some(updated),
+                Err(err) => return (ResolutionMetadata::from_error(&err.to_string()), None, None),
+            }
+        }
+        if let Some(next) = later.first() {
+            let mut property_set = HashMap::new();
+            property_set.insert(
+                "nextVersionId".to_string(),
+                Metadata::String(next.block_number.to_string()),
+            );
+            doc_meta.property_set = Some(property_set);
+        }
+
+        if owner == NULL_ADDRESS {
+            doc_meta.deactivated = Some(true);
+            let doc = Document::new(did);
+            return (ResolutionMetadata::default(), Some(doc), Some(doc_meta));
+        }
+        let (res_meta, doc, _) = if owner == identity && addr_or_pk.len() == 68 {
+            resolve_pk(did, chain_id, &addr_or_pk)
+        } else {
+            resolve_address(did, chain_id, owner)
+        };
+        let mut doc = match doc {
+            Some(doc) => doc,
+            None => return (res_meta, None, None),
+        };
+        builder.build(&mut doc);
+        (res_meta, Some(doc), Some(doc_meta))
+    }
+
+    fn to_did_method(&self) -> Option<&dyn DIDMethod> {
+        Some(self)
+    }
+}
+
+impl DIDMethod for DIDEthrRegistry {
+    fn name(&self) -> &'static str {
+        "ethr"
+    }
+
+    fn generate(&self, source: &Source) -> Result<String, GenerateError> {
+        DIDEthr.generate(source)
+    }
+
+    fn to_resolver(&self) -> &dyn DIDResolver {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDENTITY: &str = "0xb9c5714089478a327f09197987f16f9e5d936e8a";
+    const OWNER: &str = "0x2fbf1be19d90a29aea9363f4ef0b6bf1c4ff0758";
+    const DELEGATE: &str = "0x1111111111111111111111111111111111111111";
+    const DELEGATE_2: &str = "0x2222222222222222222222222222222222222222";
+
+    fn u256(value: u64) -> String {
+        format!("{:064x}", value)
+    }
+
+    fn address_word(address: &str) -> String {
+        format!("{:0>64}", &address[2..])
+    }
+
+    fn bytes32(s: &str) -> String {
+        format!("{:0<64}", hex::encode(s))
+    }
+
+    /// Mock Ethereum node holding the registry logs for one identity.
+    struct MockNode {
+        logs: Vec<Value>,
+    }
+
+    impl MockNode {
+        fn log(block: u64, topic: &str, data: String) -> Value {
+            json!({
+                "address": DEFAULT_REGISTRY_ADDRESS,
+                "blockNumber": format!("0x{:x}", block),
+                "topics": [topic, address_topic(IDENTITY)],
+                "data": format!("0x{}", data),
+            })
+        }
+
+        fn attribute(block: u64, previous: u64, name: &str, value: &[u8], valid_to: u64) -> Value {
+            let padded_len = (value.len() + 31) / 32 * 32;
+            let data = bytes32(name)
+                + &u256(128)
+                + &u256(valid_to)
+                + &u256(previous)
+                + &u256(value.len() as u64)
+                + &format!("{:0<width$}", hex::encode(value), width = padded_len * 2);
+            Self::log(block, TOPIC_ATTRIBUTE_CHANGED, data)
+        }
+
+        fn last_block(&self) -> u64 {
+            self.logs
+                .iter()
+                .map(|log| parse_quantity(&log["blockNumber"]).unwrap())
+                .max()
+                .unwrap_or(0)
+        }
+    }
+
+    #[async_trait]
+    impl EthereumRPC for MockNode {
+        async fn request(&self, method: &str, params: Value) -> Result<Value, RegistryError> {
+            match method {
+                "eth_call" => {
+                    assert_eq!(params[0]["to"], DEFAULT_REGISTRY_ADDRESS);
+                    let data = params[0]["data"].as_str().unwrap();
+                    assert_eq!(
+                        data,
+                        format!("0x{}{}", CHANGED_SELECTOR, address_word(IDENTITY))
+                    );
+                    Ok(json!(format!("0x{}", u256(self.last_block()))))
+                }
+                "eth_getLogs" => {
+                    let filter = &params[0];
+                    assert_eq!(filter["topics"][1], address_topic(IDENTITY));
+                    let logs: Vec<Value> = self
+                        .logs
+                        .iter()
+                        .filter(|log| log["blockNumber"] == filter["fromBlock"])
+                        .cloned()
+                        .collect();
+                    Ok(Value::Array(logs))
+                }
+                "eth_getBlockByNumber" => {
+                    let block = parse_quantity(&params[0])?;
+                    // 2021-01-01T00:00:00Z plus one minute per block
+                    Ok(json!({ "timestamp": format!("0x{:x}", 1609459200 + block * 60) }))
+                }
+                _ => Err(RegistryError::RPC(format!("unexpected method {}", method))),
+            }
+        }
+    }
+
+    fn registry(logs: Vec<Value>) -> DIDEthrRegistry {
+        let mut registry = DIDEthrRegistry::new();
+        registry.insert_network(1, DEFAULT_REGISTRY_ADDRESS, Box::new(MockNode { logs }));
+        registry
+    }
+
+    #[tokio::test]
+    async fn resolve_unchanged() {
+        let did = format!("did:ethr:{}", IDENTITY);
+        let (res_meta, doc, doc_meta) = registry(vec![])
+            .resolve(&did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, None);
+        let (_, doc_expected, _) = DIDEthr
+            .resolve(&did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(
+            serde_json::to_value(doc.unwrap()).unwrap(),
+            serde_json::to_value(doc_expected.unwrap()).unwrap()
+        );
+        assert_eq!(doc_meta.unwrap().version_id, None);
+    }
+
+    #[tokio::test]
+    async fn resolve_registry_events() {
+        let far_future = u64::MAX;
+        let logs = vec![
+            MockNode::log(10, TOPIC_OWNER_CHANGED, address_word(OWNER) + &u256(0)),
+            MockNode::log(
+                20,
+                TOPIC_DELEGATE_CHANGED,
+                bytes32("sigAuth") + &address_word(DELEGATE) + &u256(far_future) + &u256(10),
+            ),
+            MockNode::attribute(
+                30,
+                20,
+                "did/pub/Ed25519/veriKey/base58",
+                &[7u8; 32],
+                far_future,
+            ),
+            MockNode::attribute(
+                30,
+                20,
+                "did/svc/HubService",
+                b"https://hubs.example.com",
+                far_future,
+            ),
+            // Expires in 2021, after block 30 but before now.
+            MockNode::log(
+                30,
+                TOPIC_DELEGATE_CHANGED,
+                bytes32("veriKey")
+                    + &address_word(DELEGATE_2)
+                    + &u256(1609459200 + 35 * 60)
+                    + &u256(20),
+            ),
+            MockNode::attribute(40, 30, "did/svc/HubService", b"https://hubs.example.com", 0),
+        ];
+        let registry = registry(logs);
+        let did = format!("did:ethr:{}", IDENTITY);
+        let (res_meta, doc, doc_meta) = registry
+            .resolve(&did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, None);
+        let doc = doc.unwrap();
+        let doc_meta = doc_meta.unwrap();
+        assert_eq!(doc_meta.version_id, Some("40".to_string()));
+        assert_eq!(
+            doc_meta.updated,
+            Utc.timestamp_opt(1609459200 + 40 * 60, 0).single()
+        );
+
+        let vms = doc.verification_method.as_ref().unwrap();
+        let controller = match &vms[0] {
+            VerificationMethod::Map(vm) => vm,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            controller.blockchain_account_id,
+            Some(format!("eip155:1:{}", OWNER))
+        );
+        let delegate = vms
+            .iter()
+            .find_map(|vm| match vm {
+                VerificationMethod::Map(vm) if vm.id == format!("{}#delegate-1", did) => Some(vm),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            delegate.blockchain_account_id,
+            Some(format!("eip155:1:{}", DELEGATE))
+        );
+        let key = vms
+            .iter()
+            .find_map(|vm| match vm {
+                VerificationMethod::Map(vm) if vm.id == format!("{}#delegate-2", did) => Some(vm),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(key.type_, "Ed25519VerificationKey2018");
+        assert!(key.public_key_jwk.is_some());
+        let authentication: Vec<String> = doc
+            .authentication
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|vm| vm.get_id(&did))
+            .collect();
+        assert!(authentication.contains(&format!("{}#delegate-1", did)));
+        assert!(!authentication.contains(&format!("{}#delegate-2", did)));
+        // The service was revoked at block 40.
+        assert!(doc.service.is_none());
+        // The second delegate has expired.
+        assert!(!vms
+            .iter()
+            .any(|vm| vm.get_id(&did) == format!("{}#delegate-3", did)));
+
+        // Resolve the version before the revocation.
+        let input_metadata = ResolutionInputMetadata {
+            version_id: Some("30".to_string()),
+            ..Default::default()
+        };
+        let (res_meta, doc, doc_meta) = registry.resolve(&did, &input_metadata).await;
+        assert_eq!(res_meta.error, None);
+        let doc_meta = doc_meta.unwrap();
+        assert_eq!(doc_meta.version_id, Some("30".to_string()));
+        match doc_meta.property_set.unwrap().get("nextVersionId") {
+            Some(Metadata::String(next)) => assert_eq!(next, "40"),
+            _ => panic!("missing nextVersionId"),
+        }
+        let doc = doc.unwrap();
+        // The second delegate had not expired at block 30.
+        assert!(doc
+            .verification_method
+            .unwrap()
+            .iter()
+            .any(|vm| vm.get_id(&did) == format!("{}#delegate-3", did)));
+        let services = doc.service.unwrap();
+        assert_eq!(services[0].id, format!("{}#service-1", did));
+        assert_eq!(
+            services[0].service_endpoint,
+            Some(OneOrMany::One(ServiceEndpoint::URI(
+                "https://hubs.example.com".to_string()
+            )))
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_readded_delegate() {
+        let far_future = u64::MAX;
+        let delegate = |block: u64, previous: u64| {
+            MockNode::log(
+                block,
+                TOPIC_DELEGATE_CHANGED,
+                bytes32("sigAuth") + &address_word(DELEGATE) + &u256(far_future) + &u256(previous),
+            )
+        };
+        let logs = vec![
+            delegate(10, 0),
+            delegate(20, 10),
+            MockNode::attribute(
+                30,
+                20,
+                "did/svc/HubService",
+                b"https://hubs.example.com",
+                far_future,
+            ),
+            MockNode::attribute(
+                40,
+                30,
+                "did/svc/HubService",
+                b"https://hubs.example.com",
+                far_future,
+            ),
+        ];
+        let did = format!("did:ethr:{}", IDENTITY);
+        let (res_meta, doc, _doc_meta) = registry(logs)
+            .resolve(&did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, None);
+        let doc = doc.unwrap();
+        let delegates: Vec<String> = doc
+            .verification_method
+            .as_ref()
+            .unwrap()
+            .iter()
+            .filter_map(|vm| match vm {
+                VerificationMethod::Map(vm)
+                    if vm.blockchain_account_id == Some(format!("eip155:1:{}", DELEGATE)) =>
+                {
+                    Some(vm.id.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(delegates, vec![format!("{}#delegate-2", did)]);
+        let authentication: Vec<String> = doc
+            .authentication
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|vm| vm.get_id(&did))
+            .filter(|id| id.contains("#delegate-"))
+            .collect();
+        assert_eq!(authentication, delegates);
+        let services = doc.service.unwrap();
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].id, format!("{}#service-2", did));
+    }
+
+    #[test]
+    fn decode_log_overflow() {
+        // Value offset at the end of the address space
+        let data = bytes32("did/svc/HubService") + &u256(u64::MAX) + &u256(0) + &u256(0);
+        let log = MockNode::log(1, TOPIC_ATTRIBUTE_CHANGED, data);
+        assert!(decode_log(&log).is_err());
+    }
+
+    #[tokio::test]
+    async fn resolve_deactivated() {
+        let logs = vec![MockNode::log(
+            5,
+            TOPIC_OWNER_CHANGED,
+            address_word(NULL_ADDRESS) + &u256(0),
+        )];
+        let did = format!("did:ethr:{}", IDENTITY);
+        let (res_meta, doc, doc_meta) = registry(logs)
+            .resolve(&did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, None);
+        assert_eq!(doc_meta.unwrap().deactivated, Some(true));
+        assert!(doc.unwrap().verification_method.is_none());
+
+        let (res_meta, _doc, _meta) = registry(vec![])
+            .resolve("did:ethr:0x1234", &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, Some(ERROR_INVALID_DID.to_string()));
+    }
+}
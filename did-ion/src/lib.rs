@@ -0,0 +1,515 @@
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::Value;
+use ssi::did::{
+    DIDCreate, DIDMethod, DIDMethodError, DIDMethodTransaction, Document, GenerateError, Source,
+};
+use ssi::did_resolve::{
+    DIDResolver, DocumentMetadata, Metadata, ResolutionInputMetadata, ResolutionMetadata,
+    ERROR_INVALID_DID, ERROR_NOT_FOUND,
+};
+use ssi::jwk::{Params, JWK};
+
+pub mod sidetree;
+
+use sidetree::{
+    CreateOperation, DIDStatePatch, DocumentState, Operation, PublicKeyEntry, SidetreeError,
+};
+
+const DID_ION_PREFIX: &str = "did:ion:";
+const DID_ION_TEST_PREFIX: &str = "did:ion:test:";
+
+/// did:ion Method
+///
+/// [Specification](https://identity.foundation/ion/)
+///
+/// Long-form DIDs, which carry their create operation, are resolved offline. Resolving
+/// short-form DIDs requires an ION node and is not supported.
+pub struct DIDION;
+
+/// A parsed did:ion DID.
+struct IonDID<'a> {
+    /// DID method prefix, including the network if any, e.g. `did:ion:test`
+    prefix: &'a str,
+    suffix: &'a str,
+    create_operation: Option<&'a str>,
+}
+
+impl<'a> IonDID<'a> {
+    fn parse(did: &'a str) -> Result<Self, SidetreeError> {
+        let prefix_len = if did.starts_with(DID_ION_TEST_PREFIX) {
+            DID_ION_TEST_PREFIX.len()
+        } else if did.starts_with(DID_ION_PREFIX) {
+            DID_ION_PREFIX.len()
+        } else {
+            return Err(SidetreeError::InvalidDID);
+        };
+        let mut parts = did[prefix_len..].splitn(2, ':');
+        let suffix = parts.next().ok_or(SidetreeError::InvalidDID)?;
+        if suffix.is_empty()
+            || !suffix
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(SidetreeError::InvalidDID);
+        }
+        Ok(Self {
+            prefix: &did[..prefix_len - 1],
+            suffix,
+            create_operation: parts.next(),
+        })
+    }
+}
+
+impl DIDION {
+    /// Construct a long-form did:ion DID, given the public keys for the next update and
+    /// recovery operations and the initial DID state.
+    pub fn create(
+        update_pk: &JWK,
+        recovery_pk: &JWK,
+        document: DocumentState,
+    ) -> Result<String, SidetreeError> {
+        let op = CreateOperation::new(
+            update_pk,
+            recovery_pk,
+            vec![DIDStatePatch::Replace { document }],
+        )?;
+        op.to_long_form_did(&DID_ION_PREFIX[..DID_ION_PREFIX.len() - 1])
+    }
+
+    /// Get the short-form DID corresponding to a short-form or long-form DID.
+    pub fn short_form(did: &str) -> Result<String, SidetreeError> {
+        let ion_did = IonDID::parse(did)?;
+        Ok(format!("{}:{}", ion_did.prefix, ion_did.suffix))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl DIDResolver for DIDION {
+    async fn resolve(
+        &self,
+        did: &str,
+        _input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        let ion_did = match IonDID::parse(did) {
+            Ok(ion_did) => ion_did,
+            Err(_) => {
+                return (
+                    ResolutionMetadata::from_error(ERROR_INVALID_DID),
+                    None,
+                    None,
+                )
+            }
+        };
+        let encoded_op = match ion_did.create_operation {
+            Some(encoded_op) => encoded_op,
+            // Short-form DIDs can only be resolved by an ION node.
+            None => return (ResolutionMetadata::from_error(ERROR_NOT_FOUND), None, None),
+        };
+        let op = match CreateOperation::from_long_form(ion_did.suffix, encoded_op) {
+            Ok(op) => op,
+            Err(_) => {
+                return (
+                    ResolutionMetadata::from_error(ERROR_INVALID_DID),
+                    None,
+                    None,
+                )
+            }
+        };
+        let state = match op.document_state() {
+            Ok(state) => state,
+            Err(_) => {
+                return (
+                    ResolutionMetadata::from_error(ERROR_INVALID_DID),
+                    None,
+                    None,
+                )
+            }
+        };
+        let doc = state.to_document(did);
+
+        // https://identity.foundation/sidetree/spec/#long-form-did-uris
+        let mut method_meta = HashMap::new();
+        method_meta.insert("published".to_string(), Metadata::Boolean(false));
+        method_meta.insert(
+            "recoveryCommitment".to_string(),
+            Metadata::String(op.suffix_data.recovery_commitment.clone()),
+        );
+        method_meta.insert(
+            "updateCommitment".to_string(),
+            Metadata::String(op.delta.update_commitment.clone()),
+        );
+        let mut property_set = HashMap::new();
+        property_set.insert("method".to_string(), Metadata::Map(method_meta));
+        property_set.insert(
+            "equivalentId".to_string(),
+            Metadata::List(vec![Metadata::String(format!(
+                "{}:{}",
+                ion_did.prefix, ion_did.suffix
+            ))]),
+        );
+        (
+            ResolutionMetadata::default(),
+            Some(doc),
+            Some(DocumentMetadata {
+                property_set: Some(property_set),
+                ..Default::default()
+            }),
+        )
+    }
+}
+
+impl DIDMethod for DIDION {
+    fn name(&self) -> &'static str {
+        "ion"
+    }
+
+    /// A did:ion DID commits to keys for updating and recovering it, which must be separate from
+    /// its verification key, so it cannot be generated from a single key. Use
+    /// [`create`](DIDMethod::create) instead.
+    fn generate(&self, _source: &Source) -> Result<String, GenerateError> {
+        Err(GenerateError::UnsupportedSource)
+    }
+
+    /// Create a long-form DID, with the secp256k1 `verification_key` as its verification method,
+    /// committing to the `update_key` and `recovery_key` for the next update and recover
+    /// operations. The three keys must be distinct. The transaction contains the long-form DID
+    /// (`did`) and the create operation (`operation`), which may be submitted to an ION node to
+    /// publish the DID.
+    fn create(&self, create: DIDCreate) -> Result<DIDMethodTransaction, DIDMethodError> {
+        DIDMethodError::check_options("create", &create.options, &[])?;
+        let update_pk = create
+            .update_key
+            .ok_or(DIDMethodError::MissingKey("update"))?
+            .to_public();
+        let recovery_pk = create
+            .recovery_key
+            .ok_or(DIDMethodError::MissingKey("recovery"))?
+            .to_public();
+        let verification_pk = create
+            .verification_key
+            .ok_or(DIDMethodError::MissingKey("verification"))?
+            .to_public();
+        match verification_pk.params {
+            Params::EC(ref params) if params.curve.as_deref() == Some("secp256k1") => {}
+            _ => {
+                return Err(DIDMethodError::Other(
+                    "Verification key must be a secp256k1 key".to_string(),
+                ))
+            }
+        }
+        if update_pk == recovery_pk
+            || update_pk == verification_pk
+            || recovery_pk == verification_pk
+        {
+            return Err(DIDMethodError::Other(
+                "Update, recovery and verification keys must be distinct".to_string(),
+            ));
+        }
+        let document = DocumentState {
+            public_keys: Some(vec![PublicKeyEntry {
+                id: "key-1".to_string(),
+                type_: "EcdsaSecp256k1VerificationKey2019".to_string(),
+                public_key_jwk: verification_pk,
+                purposes: vec![
+                    "authentication".to_string(),
+                    "assertionMethod".to_string(),
+                    "capabilityInvocation".to_string(),
+                    "capabilityDelegation".to_string(),
+                ],
+            }]),
+            services: None,
+        };
+        let op = CreateOperation::new(
+            &update_pk,
+            &recovery_pk,
+            vec![DIDStatePatch::Replace { document }],
+        )
+        .map_err(|e| DIDMethodError::Other(e.to_string()))?;
+        let did = op
+            .to_long_form_did(&DID_ION_PREFIX[..DID_ION_PREFIX.len() - 1])
+            .map_err(|e| DIDMethodError::Other(e.to_string()))?;
+        let operation = serde_json::to_value(Operation::Create(op))
+            .map_err(|e| DIDMethodError::Other(e.to_string()))?;
+        let mut value = BTreeMap::new();
+        value.insert("did".to_string(), Value::String(did));
+        value.insert("operation".to_string(), operation);
+        Ok(DIDMethodTransaction {
+            did_method: self.name().to_string(),
+            value,
+        })
+    }
+
+    fn to_resolver(&self) -> &dyn DIDResolver {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use sidetree::{
+        DeactivateOperation, Operation, RecoverOperation, ServiceEndpointEntry, UpdateOperation,
+    };
+    use ssi::did::{Resource, ServiceEndpoint};
+    use ssi::did_resolve::{dereference, Content, DereferencingInputMetadata};
+    use ssi::one_or_many::OneOrMany;
+
+    fn example_state(public_key: &JWK) -> DocumentState {
+        DocumentState {
+            public_keys: Some(vec![PublicKeyEntry {
+                id: "key-1".to_string(),
+                type_: "EcdsaSecp256k1VerificationKey2019".to_string(),
+                public_key_jwk: public_key.to_public(),
+                purposes: vec!["authentication".to_string(), "keyAgreement".to_string()],
+            }]),
+            services: Some(vec![ServiceEndpointEntry {
+                id: "domain-1".to_string(),
+                type_: "LinkedDomains".to_string(),
+                service_endpoint: json!("https://foo.example.com"),
+            }]),
+        }
+    }
+
+    fn create_did(key: &JWK) -> String {
+        let tx = DIDION
+            .create(DIDCreate {
+                update_key: Some(JWK::generate_secp256k1().unwrap()),
+                recovery_key: Some(JWK::generate_secp256k1().unwrap()),
+                verification_key: Some(key.clone()),
+                options: BTreeMap::new(),
+            })
+            .unwrap();
+        tx.value["did"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn create_requires_separate_keys() {
+        let key = JWK::generate_secp256k1().unwrap();
+        assert!(matches!(
+            DIDION.generate(&Source::Key(&key)),
+            Err(GenerateError::UnsupportedSource)
+        ));
+        let other_key = JWK::generate_secp256k1().unwrap();
+        assert!(matches!(
+            DIDION.create(DIDCreate {
+                verification_key: Some(key.clone()),
+                ..Default::default()
+            }),
+            Err(DIDMethodError::MissingKey(_))
+        ));
+        assert!(DIDION
+            .create(DIDCreate {
+                update_key: Some(key.clone()),
+                recovery_key: Some(other_key.clone()),
+                verification_key: Some(key.clone()),
+                options: BTreeMap::new(),
+            })
+            .is_err());
+
+        let update_key = JWK::generate_secp256k1().unwrap();
+        let tx = DIDION
+            .create(DIDCreate {
+                update_key: Some(update_key.clone()),
+                recovery_key: Some(other_key.clone()),
+                verification_key: Some(key.clone()),
+                options: BTreeMap::new(),
+            })
+            .unwrap();
+        assert_eq!(tx.did_method, "ion");
+        let op = match serde_json::from_value(tx.value["operation"].clone()).unwrap() {
+            Operation::Create(op) => op,
+            _ => panic!("expected create operation"),
+        };
+        assert_eq!(
+            op.suffix_data.recovery_commitment,
+            sidetree::commitment(&other_key.to_public()).unwrap()
+        );
+        assert_eq!(
+            op.delta.update_commitment,
+            sidetree::commitment(&update_key.to_public()).unwrap()
+        );
+    }
+
+    #[async_std::test]
+    async fn resolve_long_form() {
+        let key = JWK::generate_secp256k1().unwrap();
+        let update_key = JWK::generate_secp256k1().unwrap();
+        let recovery_key = JWK::generate_secp256k1().unwrap();
+        let did = DIDION::create(
+            &update_key.to_public(),
+            &recovery_key.to_public(),
+            example_state(&key),
+        )
+        .unwrap();
+        let short_did = DIDION::short_form(&did).unwrap();
+        assert!(did.starts_with(&(short_did.clone() + ":")));
+
+        let (res_meta, doc, doc_meta) = DIDION
+            .resolve(&did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, None);
+        let doc = doc.unwrap();
+        assert_eq!(doc.id, did);
+        assert_eq!(doc.authentication.as_ref().unwrap().len(), 1);
+        assert_eq!(doc.key_agreement.as_ref().unwrap().len(), 1);
+        assert!(doc.assertion_method.is_none());
+        let service = &doc.service.as_ref().unwrap()[0];
+        assert_eq!(service.id, format!("{}#domain-1", did));
+        assert_eq!(
+            service.service_endpoint,
+            Some(OneOrMany::One(ServiceEndpoint::URI(
+                "https://foo.example.com".to_string()
+            )))
+        );
+        let property_set = doc_meta.unwrap().property_set.unwrap();
+        match property_set.get("equivalentId") {
+            Some(Metadata::List(ids)) => match &ids[..] {
+                [Metadata::String(id)] => assert_eq!(id, &short_did),
+                _ => panic!("unexpected equivalentId"),
+            },
+            _ => panic!("missing equivalentId"),
+        }
+
+        let vm = format!("{}#key-1", did);
+        let (res_meta, object, _meta) =
+            dereference(&DIDION, &vm, &DereferencingInputMetadata::default()).await;
+        assert_eq!(res_meta.error, None);
+        let vm = match object {
+            Content::Object(Resource::VerificationMethod(vm)) => vm,
+            _ => unreachable!(),
+        };
+        assert_eq!(vm.public_key_jwk.unwrap(), key.to_public());
+
+        // Short-form DIDs need an ION node.
+        let (res_meta, _doc, _meta) = DIDION
+            .resolve(&short_did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, Some(ERROR_NOT_FOUND.to_string()));
+
+        // Tampering with the suffix is detected.
+        let other_did = create_did(&key);
+        let other_suffix = IonDID::parse(&other_did).unwrap().suffix;
+        let bad_did = did.replacen(IonDID::parse(&did).unwrap().suffix, other_suffix, 1);
+        let (res_meta, _doc, _meta) = DIDION
+            .resolve(&bad_did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, Some(ERROR_INVALID_DID.to_string()));
+    }
+
+    #[test]
+    fn operations() {
+        let key = JWK::generate_secp256k1().unwrap();
+        let update_key = JWK::generate_secp256k1().unwrap();
+        let recovery_key = JWK::generate_secp256k1().unwrap();
+        let create = CreateOperation::new(
+            &update_key.to_public(),
+            &recovery_key.to_public(),
+            vec![DIDStatePatch::Replace {
+                document: example_state(&key),
+            }],
+        )
+        .unwrap();
+        let did_suffix = create.did_suffix().unwrap();
+        let request = serde_json::to_value(Operation::Create(create.clone())).unwrap();
+        assert_eq!(request["type"], "create");
+        assert!(request["suffixData"]["deltaHash"].is_string());
+        assert_eq!(request["delta"]["patches"][0]["action"], "replace");
+
+        // Update: remove the service, signed with the committed update key.
+        let next_update_key = JWK::generate_secp256k1().unwrap();
+        let update = UpdateOperation::new(
+            &did_suffix,
+            &update_key,
+            &next_update_key.to_public(),
+            vec![DIDStatePatch::RemoveServices {
+                ids: vec!["domain-1".to_string()],
+            }],
+        )
+        .unwrap();
+        let claims = update.verify(&create.delta.update_commitment).unwrap();
+        assert_eq!(claims.update_key, update_key.to_public());
+        assert!(update
+            .verify(&create.suffix_data.recovery_commitment)
+            .is_err());
+        let mut state = create.document_state().unwrap();
+        update.delta.apply(&mut state).unwrap();
+        assert!(state.services.unwrap().is_empty());
+        let request = serde_json::to_value(Operation::Update(update.clone())).unwrap();
+        assert_eq!(request["type"], "update");
+        assert_eq!(request["delta"]["patches"][0]["action"], "remove-services");
+
+        // Tampering with the delta is detected.
+        let mut bad_update = update;
+        bad_update.delta.patches.clear();
+        assert!(bad_update.verify(&create.delta.update_commitment).is_err());
+
+        // Recover with the committed recovery key.
+        let next_recovery_key = JWK::generate_secp256k1().unwrap();
+        let recover = RecoverOperation::new(
+            &did_suffix,
+            &recovery_key,
+            &next_update_key.to_public(),
+            &next_recovery_key.to_public(),
+            vec![DIDStatePatch::Replace {
+                document: DocumentState::default(),
+            }],
+        )
+        .unwrap();
+        let claims = recover
+            .verify(&create.suffix_data.recovery_commitment)
+            .unwrap();
+        assert_eq!(
+            claims.recovery_commitment,
+            sidetree::commitment(&next_recovery_key.to_public()).unwrap()
+        );
+
+        // Deactivate with the next recovery key.
+        let deactivate = DeactivateOperation::new(&did_suffix, &next_recovery_key).unwrap();
+        deactivate.verify(&claims.recovery_commitment).unwrap();
+        assert!(DeactivateOperation::new("other", &next_recovery_key)
+            .unwrap()
+            .verify(&create.suffix_data.recovery_commitment)
+            .is_err());
+    }
+
+    #[async_std::test]
+    async fn credential_prove_verify_did_ion() {
+        use ssi::vc::{get_verification_method, Credential, Issuer, LinkedDataProofOptions, URI};
+        let key = JWK::generate_secp256k1().unwrap();
+        let did = create_did(&key);
+        let mut vc: Credential = serde_json::from_value(json!({
+            "@context": "https://www.w3.org/2018/credentials/v1",
+            "type": ["VerifiableCredential"],
+            "issuer": did.clone(),
+            "issuanceDate": "2021-02-18T20:17:46Z",
+            "credentialSubject": {
+                "id": "did:example:d23dd687a7dc6787646f2eb98d0"
+            }
+        }))
+        .unwrap();
+        let verification_method = get_verification_method(&did, &DIDION).await.unwrap();
+        let mut issue_options = LinkedDataProofOptions::default();
+        issue_options.verification_method = Some(URI::String(verification_method));
+        let proof = vc
+            .generate_proof(&key, &issue_options, &DIDION)
+            .await
+            .unwrap();
+        vc.add_proof(proof);
+        vc.validate().unwrap();
+        let verification_result = vc.verify(None, &DIDION).await;
+        println!("{:#?}", verification_result);
+        assert!(verification_result.errors.is_empty());
+
+        // test that issuer is verified
+        vc.issuer = Some(Issuer::URI(URI::String("did:example:bad".to_string())));
+        assert!(vc.verify(None, &DIDION).await.errors.len() > 0);
+    }
+}
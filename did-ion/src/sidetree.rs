@@ -0,0 +1,594 @@
+//! [Sidetree](https://identity.foundation/sidetree/spec/) operations and DID state.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use thiserror::Error;
+
+use ssi::did::{
+    Context, Contexts, Document, Service, ServiceEndpoint, VerificationMethod,
+    VerificationMethodMap, DEFAULT_CONTEXT, DIDURL,
+};
+use ssi::jwk::{Algorithm, JWK};
+use ssi::one_or_many::OneOrMany;
+
+/// Multihash prefix for SHA2-256, the Sidetree default hash algorithm.
+const MULTIHASH_SHA2_256_PREFIX: [u8; 2] = [0x12, 0x20];
+
+/// Maximum length of a public key or service id.
+const MAX_ID_LENGTH: usize = 50;
+
+const VERIFICATION_RELATIONSHIPS: [&str; 5] = [
+    "authentication",
+    "assertionMethod",
+    "capabilityInvocation",
+    "capabilityDelegation",
+    "keyAgreement",
+];
+
+#[derive(Error, Debug)]
+pub enum SidetreeError {
+    #[error("Invalid DID")]
+    InvalidDID,
+    #[error("DID suffix does not match suffix data")]
+    SuffixMismatch,
+    #[error("Delta hash does not match delta")]
+    DeltaHashMismatch,
+    #[error("Reveal value does not match commitment")]
+    CommitmentMismatch,
+    #[error("Invalid id: {0}")]
+    InvalidId(String),
+    #[error("Duplicate id: {0}")]
+    DuplicateId(String),
+    #[error("Invalid purpose: {0}")]
+    InvalidPurpose(String),
+    #[error("Unexpected private key")]
+    PrivateKey,
+    #[error("Signed data does not match operation")]
+    SignedDataMismatch,
+    #[error(transparent)]
+    Base64(#[from] base64::DecodeError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    JsonPatch(#[from] json_patch::PatchError),
+    #[error(transparent)]
+    SSI(#[from] ssi::error::Error),
+}
+
+/// Hash data with SHA2-256, encoded as a multihash.
+fn multihash(data: &[u8]) -> Result<Vec<u8>, SidetreeError> {
+    let hash = ssi::hash::sha256(data)?;
+    Ok([MULTIHASH_SHA2_256_PREFIX.to_vec(), hash.to_vec()].concat())
+}
+
+fn base64url(data: &[u8]) -> String {
+    base64::encode_config(data, base64::URL_SAFE_NO_PAD)
+}
+
+/// Canonicalize a value with JCS, then hash and base64url-encode it.
+pub fn hash_canonical<T: Serialize>(value: &T) -> Result<String, SidetreeError> {
+    let canonical = serde_jcs::to_vec(value)?;
+    Ok(base64url(&multihash(&canonical)?))
+}
+
+/// Compute the reveal value for a public key: the encoded multihash of its canonical form.
+pub fn reveal_value(public_key: &JWK) -> Result<String, SidetreeError> {
+    if public_key.to_public() != *public_key {
+        return Err(SidetreeError::PrivateKey);
+    }
+    hash_canonical(public_key)
+}
+
+/// Compute the commitment for a public key: the encoded multihash of the (plain SHA2-256) hash
+/// of its canonical form.
+pub fn commitment(public_key: &JWK) -> Result<String, SidetreeError> {
+    if public_key.to_public() != *public_key {
+        return Err(SidetreeError::PrivateKey);
+    }
+    let canonical = serde_jcs::to_vec(public_key)?;
+    let hash = ssi::hash::sha256(&canonical)?;
+    Ok(base64url(&multihash(&hash)?))
+}
+
+/// Check that a public key matches a commitment.
+fn check_commitment(public_key: &JWK, expected: &str) -> Result<(), SidetreeError> {
+    if commitment(public_key)? != expected {
+        return Err(SidetreeError::CommitmentMismatch);
+    }
+    Ok(())
+}
+
+fn validate_id(id: &str) -> Result<(), SidetreeError> {
+    if id.is_empty()
+        || id.len() > MAX_ID_LENGTH
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(SidetreeError::InvalidId(id.to_string()));
+    }
+    Ok(())
+}
+
+/// Sign claims as a compact JWS with ES256K, without a key id in the header.
+fn sign_claims<T: Serialize>(claims: &T, key: &JWK) -> Result<String, SidetreeError> {
+    let key = JWK {
+        key_id: None,
+        ..key.clone()
+    };
+    let payload = serde_jcs::to_string(claims)?;
+    Ok(ssi::jws::encode_sign(Algorithm::ES256K, &payload, &key)?)
+}
+
+/// Decode the claims of a signed data JWS, verifying it with the given public key.
+fn verify_claims<T: for<'de> Deserialize<'de>>(
+    jws: &str,
+    public_key: &JWK,
+) -> Result<T, SidetreeError> {
+    let (_header, payload) = ssi::jws::decode_verify(jws, public_key)?;
+    Ok(serde_json::from_slice(&payload)?)
+}
+
+/// Decode the claims of a signed data JWS without verifying it.
+fn decode_claims<T: for<'de> Deserialize<'de>>(jws: &str) -> Result<T, SidetreeError> {
+    let (_header, payload) = ssi::jws::decode_unverified(jws)?;
+    Ok(serde_json::from_slice(&payload)?)
+}
+
+/// <https://identity.foundation/sidetree/spec/#add-public-keys>
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyEntry {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub public_key_jwk: JWK,
+    pub purposes: Vec<String>,
+}
+
+/// <https://identity.foundation/sidetree/spec/#add-services>
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceEndpointEntry {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub service_endpoint: Value,
+}
+
+/// DID state document, as used in a `replace` patch.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_keys: Option<Vec<PublicKeyEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub services: Option<Vec<ServiceEndpointEntry>>,
+}
+
+/// <https://identity.foundation/sidetree/spec/#did-state-patches>
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "action")]
+#[serde(rename_all = "kebab-case")]
+pub enum DIDStatePatch {
+    AddPublicKeys {
+        #[serde(rename = "publicKeys")]
+        public_keys: Vec<PublicKeyEntry>,
+    },
+    RemovePublicKeys {
+        ids: Vec<String>,
+    },
+    AddServices {
+        services: Vec<ServiceEndpointEntry>,
+    },
+    RemoveServices {
+        ids: Vec<String>,
+    },
+    Replace {
+        document: DocumentState,
+    },
+    IetfJsonPatch {
+        patches: json_patch::Patch,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Delta {
+    pub patches: Vec<DIDStatePatch>,
+    pub update_commitment: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SuffixData {
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    pub delta_hash: String,
+    pub recovery_commitment: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_origin: Option<String>,
+}
+
+/// Claims of the signed data of an update operation.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateClaims {
+    pub update_key: JWK,
+    pub delta_hash: String,
+}
+
+/// Claims of the signed data of a recover operation.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryClaims {
+    pub recovery_commitment: String,
+    pub recovery_key: JWK,
+    pub delta_hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_origin: Option<String>,
+}
+
+/// Claims of the signed data of a deactivate operation.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeactivateClaims {
+    pub did_suffix: String,
+    pub recovery_key: JWK,
+}
+
+/// <https://identity.foundation/sidetree/spec/#create>
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOperation {
+    pub suffix_data: SuffixData,
+    pub delta: Delta,
+}
+
+/// <https://identity.foundation/sidetree/spec/#update>
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateOperation {
+    pub did_suffix: String,
+    pub reveal_value: String,
+    pub delta: Delta,
+    pub signed_data: String,
+}
+
+/// <https://identity.foundation/sidetree/spec/#recover>
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverOperation {
+    pub did_suffix: String,
+    pub reveal_value: String,
+    pub delta: Delta,
+    pub signed_data: String,
+}
+
+/// <https://identity.foundation/sidetree/spec/#deactivate>
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeactivateOperation {
+    pub did_suffix: String,
+    pub reveal_value: String,
+    pub signed_data: String,
+}
+
+/// Sidetree operation request, as submitted to a Sidetree node.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type")]
+#[serde(rename_all = "camelCase")]
+pub enum Operation {
+    Create(CreateOperation),
+    Update(UpdateOperation),
+    Recover(RecoverOperation),
+    Deactivate(DeactivateOperation),
+}
+
+impl DocumentState {
+    /// Apply a DID state patch.
+    pub fn apply_patch(&mut self, patch: &DIDStatePatch) -> Result<(), SidetreeError> {
+        match patch {
+            DIDStatePatch::AddPublicKeys { public_keys } => {
+                let existing = self.public_keys.get_or_insert_with(Vec::new);
+                for public_key in public_keys {
+                    existing.retain(|key| key.id != public_key.id);
+                    existing.push(public_key.clone());
+                }
+            }
+            DIDStatePatch::RemovePublicKeys { ids } => {
+                if let Some(ref mut public_keys) = self.public_keys {
+                    public_keys.retain(|key| !ids.contains(&key.id));
+                }
+            }
+            DIDStatePatch::AddServices { services } => {
+                let existing = self.services.get_or_insert_with(Vec::new);
+                for service in services {
+                    existing.retain(|s| s.id != service.id);
+                    existing.push(service.clone());
+                }
+            }
+            DIDStatePatch::RemoveServices { ids } => {
+                if let Some(ref mut services) = self.services {
+                    services.retain(|service| !ids.contains(&service.id));
+                }
+            }
+            DIDStatePatch::Replace { document } => {
+                *self = document.clone();
+            }
+            DIDStatePatch::IetfJsonPatch { patches } => {
+                let mut value = serde_json::to_value(&*self)?;
+                json_patch::patch(&mut value, patches)?;
+                *self = serde_json::from_value(value)?;
+            }
+        }
+        self.validate()
+    }
+
+    /// Check that ids are well-formed and unique, that purposes are known, and that public keys
+    /// do not contain private key material.
+    pub fn validate(&self) -> Result<(), SidetreeError> {
+        let mut ids = HashSet::new();
+        for public_key in self.public_keys.iter().flatten() {
+            validate_id(&public_key.id)?;
+            if !ids.insert(&public_key.id) {
+                return Err(SidetreeError::DuplicateId(public_key.id.clone()));
+            }
+            if public_key.public_key_jwk.to_public() != public_key.public_key_jwk {
+                return Err(SidetreeError::PrivateKey);
+            }
+            for purpose in &public_key.purposes {
+                if !VERIFICATION_RELATIONSHIPS.contains(&purpose.as_str()) {
+                    return Err(SidetreeError::InvalidPurpose(purpose.clone()));
+                }
+            }
+        }
+        let mut ids = HashSet::new();
+        for service in self.services.iter().flatten() {
+            validate_id(&service.id)?;
+            if !ids.insert(&service.id) {
+                return Err(SidetreeError::DuplicateId(service.id.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert the DID state to a DID document for the given DID.
+    pub fn to_document(&self, did: &str) -> Document {
+        let mut doc = Document {
+            context: Contexts::One(Context::URI(DEFAULT_CONTEXT.to_string())),
+            id: did.to_string(),
+            ..Default::default()
+        };
+        let mut verification_methods = Vec::new();
+        for public_key in self.public_keys.iter().flatten() {
+            let vm_didurl = DIDURL {
+                did: did.to_string(),
+                fragment: Some(public_key.id.clone()),
+                ..Default::default()
+            };
+            verification_methods.push(VerificationMethod::Map(VerificationMethodMap {
+                id: vm_didurl.to_string(),
+                type_: public_key.type_.clone(),
+                controller: did.to_string(),
+                public_key_jwk: Some(public_key.public_key_jwk.clone()),
+                ..Default::default()
+            }));
+            for purpose in &public_key.purposes {
+                let relationship = match &purpose[..] {
+                    "authentication" => &mut doc.authentication,
+                    "assertionMethod" => &mut doc.assertion_method,
+                    "capabilityInvocation" => &mut doc.capability_invocation,
+                    "capabilityDelegation" => &mut doc.capability_delegation,
+                    "keyAgreement" => &mut doc.key_agreement,
+                    _ => continue,
+                };
+                relationship
+                    .get_or_insert_with(Vec::new)
+                    .push(VerificationMethod::DIDURL(vm_didurl.clone()));
+            }
+        }
+        if !verification_methods.is_empty() {
+            doc.verification_method = Some(verification_methods);
+        }
+        let services: Vec<Service> = self
+            .services
+            .iter()
+            .flatten()
+            .map(|service| Service {
+                id: format!("{}#{}", did, service.id),
+                type_: OneOrMany::One(service.type_.clone()),
+                service_endpoint: Some(OneOrMany::One(match service.service_endpoint {
+                    Value::String(ref uri) => ServiceEndpoint::URI(uri.clone()),
+                    ref value => ServiceEndpoint::Map(value.clone()),
+                })),
+                property_set: None,
+            })
+            .collect();
+        if !services.is_empty() {
+            doc.service = Some(services);
+        }
+        doc
+    }
+}
+
+impl Delta {
+    fn new(patches: Vec<DIDStatePatch>, update_pk: &JWK) -> Result<Self, SidetreeError> {
+        Ok(Self {
+            patches,
+            update_commitment: commitment(update_pk)?,
+        })
+    }
+
+    /// Apply the patches of the delta to a DID state.
+    pub fn apply(&self, state: &mut DocumentState) -> Result<(), SidetreeError> {
+        for patch in &self.patches {
+            state.apply_patch(patch)?;
+        }
+        Ok(())
+    }
+}
+
+impl CreateOperation {
+    /// Construct a create operation, given the public keys for the next update and recovery
+    /// operations and the patches describing the initial DID state.
+    pub fn new(
+        update_pk: &JWK,
+        recovery_pk: &JWK,
+        patches: Vec<DIDStatePatch>,
+    ) -> Result<Self, SidetreeError> {
+        let delta = Delta::new(patches, update_pk)?;
+        delta.apply(&mut DocumentState::default())?;
+        let suffix_data = SuffixData {
+            type_: None,
+            delta_hash: hash_canonical(&delta)?,
+            recovery_commitment: commitment(recovery_pk)?,
+            anchor_origin: None,
+        };
+        Ok(Self { suffix_data, delta })
+    }
+
+    /// Compute the DID suffix: the encoded hash of the canonical suffix data.
+    pub fn did_suffix(&self) -> Result<String, SidetreeError> {
+        hash_canonical(&self.suffix_data)
+    }
+
+    /// Construct the long-form DID, `<prefix>:<suffix>:<encoded create operation>`, for a DID
+    /// method prefix such as `did:ion`.
+    pub fn to_long_form_did(&self, prefix: &str) -> Result<String, SidetreeError> {
+        let encoded = base64url(&serde_jcs::to_vec(self)?);
+        Ok(format!("{}:{}:{}", prefix, self.did_suffix()?, encoded))
+    }
+
+    /// Decode the create operation of a long-form DID, given its suffix and encoded create
+    /// operation, and check that the suffix and delta hash match.
+    pub fn from_long_form(suffix: &str, encoded: &str) -> Result<Self, SidetreeError> {
+        let data = base64::decode_config(encoded, base64::URL_SAFE_NO_PAD)?;
+        let op: Self = serde_json::from_slice(&data)?;
+        if op.did_suffix()? != suffix {
+            return Err(SidetreeError::SuffixMismatch);
+        }
+        if hash_canonical(&op.delta)? != op.suffix_data.delta_hash {
+            return Err(SidetreeError::DeltaHashMismatch);
+        }
+        Ok(op)
+    }
+
+    /// Compute the initial DID state.
+    pub fn document_state(&self) -> Result<DocumentState, SidetreeError> {
+        let mut state = DocumentState::default();
+        self.delta.apply(&mut state)?;
+        Ok(state)
+    }
+}
+
+impl UpdateOperation {
+    /// Construct an update operation signed with the private update key committed to by the
+    /// previous operation.
+    pub fn new(
+        did_suffix: &str,
+        update_key: &JWK,
+        next_update_pk: &JWK,
+        patches: Vec<DIDStatePatch>,
+    ) -> Result<Self, SidetreeError> {
+        let update_pk = update_key.to_public();
+        let delta = Delta::new(patches, next_update_pk)?;
+        let claims = UpdateClaims {
+            update_key: update_pk.clone(),
+            delta_hash: hash_canonical(&delta)?,
+        };
+        Ok(Self {
+            did_suffix: did_suffix.to_string(),
+            reveal_value: reveal_value(&update_pk)?,
+            delta,
+            signed_data: sign_claims(&claims, update_key)?,
+        })
+    }
+
+    /// Verify the operation against the current update commitment, returning the signed claims.
+    pub fn verify(&self, update_commitment: &str) -> Result<UpdateClaims, SidetreeError> {
+        let unverified: UpdateClaims = decode_claims(&self.signed_data)?;
+        check_commitment(&unverified.update_key, update_commitment)?;
+        let claims: UpdateClaims = verify_claims(&self.signed_data, &unverified.update_key)?;
+        if reveal_value(&claims.update_key)? != self.reveal_value
+            || hash_canonical(&self.delta)? != claims.delta_hash
+        {
+            return Err(SidetreeError::SignedDataMismatch);
+        }
+        Ok(claims)
+    }
+}
+
+impl RecoverOperation {
+    /// Construct a recover operation signed with the private recovery key committed to by the
+    /// previous create or recover operation.
+    pub fn new(
+        did_suffix: &str,
+        recovery_key: &JWK,
+        next_update_pk: &JWK,
+        next_recovery_pk: &JWK,
+        patches: Vec<DIDStatePatch>,
+    ) -> Result<Self, SidetreeError> {
+        let recovery_pk = recovery_key.to_public();
+        let delta = Delta::new(patches, next_update_pk)?;
+        let claims = RecoveryClaims {
+            recovery_commitment: commitment(next_recovery_pk)?,
+            recovery_key: recovery_pk.clone(),
+            delta_hash: hash_canonical(&delta)?,
+            anchor_origin: None,
+        };
+        Ok(Self {
+            did_suffix: did_suffix.to_string(),
+            reveal_value: reveal_value(&recovery_pk)?,
+            delta,
+            signed_data: sign_claims(&claims, recovery_key)?,
+        })
+    }
+
+    /// Verify the operation against the current recovery commitment, returning the signed
+    /// claims.
+    pub fn verify(&self, recovery_commitment: &str) -> Result<RecoveryClaims, SidetreeError> {
+        let unverified: RecoveryClaims = decode_claims(&self.signed_data)?;
+        check_commitment(&unverified.recovery_key, recovery_commitment)?;
+        let claims: RecoveryClaims = verify_claims(&self.signed_data, &unverified.recovery_key)?;
+        if reveal_value(&claims.recovery_key)? != self.reveal_value
+            || hash_canonical(&self.delta)? != claims.delta_hash
+        {
+            return Err(SidetreeError::SignedDataMismatch);
+        }
+        Ok(claims)
+    }
+}
+
+impl DeactivateOperation {
+    /// Construct a deactivate operation signed with the private recovery key committed to by
+    /// the previous create or recover operation.
+    pub fn new(did_suffix: &str, recovery_key: &JWK) -> Result<Self, SidetreeError> {
+        let recovery_pk = recovery_key.to_public();
+        let claims = DeactivateClaims {
+            did_suffix: did_suffix.to_string(),
+            recovery_key: recovery_pk.clone(),
+        };
+        Ok(Self {
+            did_suffix: did_suffix.to_string(),
+            reveal_value: reveal_value(&recovery_pk)?,
+            signed_data: sign_claims(&claims, recovery_key)?,
+        })
+    }
+
+    /// Verify the operation against the current recovery commitment, returning the signed
+    /// claims.
+    pub fn verify(&self, recovery_commitment: &str) -> Result<DeactivateClaims, SidetreeError> {
+        let unverified: DeactivateClaims = decode_claims(&self.signed_data)?;
+        check_commitment(&unverified.recovery_key, recovery_commitment)?;
+        let claims: DeactivateClaims = verify_claims(&self.signed_data, &unverified.recovery_key)?;
+        if reveal_value(&claims.recovery_key)? != self.reveal_value
+            || claims.did_suffix != self.did_suffix
+        {
+            return Err(SidetreeError::SignedDataMismatch);
+        }
+        Ok(claims)
+    }
+}
@@ -0,0 +1,239 @@
+use async_trait::async_trait;
+
+use ssi::did::{
+    Context, Contexts, DIDMethod, Document, GenerateError, Source, VerificationMethod,
+    VerificationMethodMap, DEFAULT_CONTEXT, DIDURL,
+};
+use ssi::did_resolve::{
+    DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata, ERROR_INVALID_DID,
+};
+use ssi::jsonld::W3ID_JWS2020_V1_CONTEXT;
+use ssi::jwk::JWK;
+
+/// did:jwk Method
+///
+/// [Specification](https://github.com/quartzjer/did-jwk/blob/main/spec.md)
+///
+/// The method-specific identifier is the base64url-encoded JSON of a public JWK. The JWK is
+/// serialized using the JSON Canonicalization Scheme when generating a DID.
+pub struct DIDJWK;
+
+/// Decode the public key of a did:jwk DID. Keys containing private key material are rejected.
+fn decode_jwk(did: &str) -> Option<JWK> {
+    let method_specific_id = did.strip_prefix("did:jwk:")?;
+    let data = base64::decode_config(method_specific_id, base64::URL_SAFE_NO_PAD).ok()?;
+    let jwk: JWK = serde_json::from_slice(&data).ok()?;
+    if jwk.to_public() != jwk {
+        return None;
+    }
+    Some(jwk)
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl DIDResolver for DIDJWK {
+    async fn resolve(
+        &self,
+        did: &str,
+        _input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        let jwk = match decode_jwk(did) {
+            Some(jwk) => jwk,
+            None => {
+                return (
+                    ResolutionMetadata::from_error(ERROR_INVALID_DID),
+                    None,
+                    None,
+                )
+            }
+        };
+        let vm_didurl = DIDURL {
+            did: did.to_string(),
+            fragment: Some("0".to_string()),
+            ..Default::default()
+        };
+        let vm_ref = || Some(vec![VerificationMethod::DIDURL(vm_didurl.clone())]);
+        // Signing keys get the signing relationships, encryption keys get keyAgreement, and keys
+        // without "use" get both.
+        let (sig, enc) = match jwk.public_key_use.as_deref() {
+            Some("sig") => (true, false),
+            Some("enc") => (false, true),
+            _ => (true, true),
+        };
+        let doc = Document {
+            context: Contexts::Many(vec![
+                Context::URI(DEFAULT_CONTEXT.to_string()),
+                Context::URI(W3ID_JWS2020_V1_CONTEXT.to_string()),
+            ]),
+            id: did.to_string(),
+            verification_method: Some(vec![VerificationMethod::Map(VerificationMethodMap {
+                id: vm_didurl.to_string(),
+                type_: "JsonWebKey2020".to_string(),
+                controller: did.to_string(),
+                public_key_jwk: Some(jwk),
+                ..Default::default()
+            })]),
+            authentication: if sig { vm_ref() } else { None },
+            assertion_method: if sig { vm_ref() } else { None },
+            capability_invocation: if sig { vm_ref() } else { None },
+            capability_delegation: if sig { vm_ref() } else { None },
+            key_agreement: if enc { vm_ref() } else { None },
+            ..Default::default()
+        };
+        (
+            ResolutionMetadata::default(),
+            Some(doc),
+            Some(DocumentMetadata::default()),
+        )
+    }
+}
+
+impl DIDMethod for DIDJWK {
+    fn name(&self) -> &'static str {
+        "jwk"
+    }
+
+    fn generate(&self, source: &Source) -> Result<String, GenerateError> {
+        let jwk = match source {
+            Source::Key(jwk) => jwk,
+            Source::KeyAndPattern(jwk, pattern) => {
+                if !pattern.is_empty() {
+                    return Err(GenerateError::UnsupportedPattern(pattern.to_string()));
+                }
+                jwk
+            }
+            _ => return Err(GenerateError::UnsupportedSource),
+        };
+        let json = serde_jcs::to_string(&jwk.to_public())
+            .map_err(|e| GenerateError::Other(e.to_string()))?;
+        Ok(format!(
+            "did:jwk:{}",
+            base64::encode_config(json, base64::URL_SAFE_NO_PAD)
+        ))
+    }
+
+    fn to_resolver(&self) -> &dyn DIDResolver {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ssi::did::Resource;
+    use ssi::did_resolve::{dereference, Content, DereferencingInputMetadata};
+
+    #[async_std::test]
+    async fn resolve_p256() {
+        // https://github.com/quartzjer/did-jwk/blob/main/spec.md#examples
+        let did = "did:jwk:eyJjcnYiOiJQLTI1NiIsImt0eSI6IkVDIiwieCI6ImFjYklRaXVNczNpOF91c3pFakoydHBUdFJNNEVVM3l6OTFQSDZDZEgyVjAiLCJ5IjoiX0tjeUxqOXZXTXB0bm1LdG00NkdxRHo4d2Y3NEk1TEtncmwyR3pIM25TRSJ9";
+        let (res_meta, doc, _meta) = DIDJWK
+            .resolve(did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, None);
+        let doc = doc.unwrap();
+        assert!(doc.authentication.is_some());
+        assert!(doc.assertion_method.is_some());
+        assert!(doc.key_agreement.is_some());
+
+        let vm = format!("{}#0", did);
+        let (res_meta, object, _meta) =
+            dereference(&DIDJWK, &vm, &DereferencingInputMetadata::default()).await;
+        assert_eq!(res_meta.error, None);
+        let vm = match object {
+            Content::Object(Resource::VerificationMethod(vm)) => vm,
+            _ => unreachable!(),
+        };
+        assert_eq!(vm.type_, "JsonWebKey2020");
+        let key = vm.public_key_jwk.unwrap();
+        let key_expected: JWK = serde_json::from_value(serde_json::json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "acbIQiuMs3i8_uszEjJ2tpTtRM4EU3yz91PH6CdH2V0",
+            "y": "_KcyLj9vWMptnmKtm46GqDz8wf74I5LKgrl2GzH3nSE"
+        }))
+        .unwrap();
+        assert_eq!(key, key_expected);
+
+        // convert back to DID from JWK
+        let did1 = DIDJWK.generate(&Source::Key(&key)).unwrap();
+        assert_eq!(did1, did);
+    }
+
+    #[async_std::test]
+    async fn resolve_x25519() {
+        // https://github.com/quartzjer/did-jwk/blob/main/spec.md#examples
+        let did = "did:jwk:eyJrdHkiOiJPS1AiLCJjcnYiOiJYMjU1MTkiLCJ1c2UiOiJlbmMiLCJ4IjoiM3A3YmZYdDl3YlRUVzJIQzdPUTFOei1EUThoYmVHZE5yZngtRkctSUswOCJ9";
+        let (res_meta, doc, _meta) = DIDJWK
+            .resolve(did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, None);
+        let doc = doc.unwrap();
+        assert!(doc.authentication.is_none());
+        assert!(doc.assertion_method.is_none());
+        assert_eq!(doc.key_agreement.unwrap().len(), 1);
+
+        // Private keys are not allowed.
+        let key = JWK::generate_ed25519().unwrap();
+        let json = serde_json::to_string(&key).unwrap();
+        let did = format!(
+            "did:jwk:{}",
+            base64::encode_config(json, base64::URL_SAFE_NO_PAD)
+        );
+        let (res_meta, _doc, _meta) = DIDJWK
+            .resolve(&did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, Some(ERROR_INVALID_DID.to_string()));
+    }
+
+    async fn prove_verify_did_jwk(key: &JWK) {
+        use ssi::vc::{get_verification_method, Credential, Issuer, LinkedDataProofOptions, URI};
+        let did = DIDJWK.generate(&Source::Key(key)).unwrap();
+        let mut vc: Credential = serde_json::from_value(serde_json::json!({
+            "@context": "https://www.w3.org/2018/credentials/v1",
+            "type": ["VerifiableCredential"],
+            "issuer": did.clone(),
+            "issuanceDate": "2021-02-18T20:17:46Z",
+            "credentialSubject": {
+                "id": "did:example:d23dd687a7dc6787646f2eb98d0"
+            }
+        }))
+        .unwrap();
+        let verification_method = get_verification_method(&did, &DIDJWK).await.unwrap();
+        let mut issue_options = LinkedDataProofOptions::default();
+        issue_options.verification_method = Some(URI::String(verification_method));
+        let proof = vc
+            .generate_proof(key, &issue_options, &DIDJWK)
+            .await
+            .unwrap();
+        assert_eq!(proof.type_, "JsonWebSignature2020");
+        vc.add_proof(proof);
+        vc.validate().unwrap();
+        let verification_result = vc.verify(None, &DIDJWK).await;
+        println!("{:#?}", verification_result);
+        assert!(verification_result.errors.is_empty());
+
+        // test that issuer is verified
+        vc.issuer = Some(Issuer::URI(URI::String("did:example:bad".to_string())));
+        assert!(vc.verify(None, &DIDJWK).await.errors.len() > 0);
+    }
+
+    #[async_std::test]
+    async fn credential_prove_verify_did_jwk() {
+        let key = JWK::generate_ed25519().unwrap();
+        prove_verify_did_jwk(&key).await;
+
+        // RSA keys without an algorithm use PS256, the RSA algorithm JsonWebSignature2020 allows.
+        let key: JWK =
+            serde_json::from_str(include_str!("../../tests/rsa2048-2020-08-25.json")).unwrap();
+        assert_eq!(key.get_algorithm(), Some(ssi::jwk::Algorithm::PS256));
+        prove_verify_did_jwk(&key).await;
+
+        let key = JWK::generate_p384().unwrap();
+        prove_verify_did_jwk(&key).await;
+    }
+}
@@ -4,8 +4,8 @@ use std::collections::BTreeMap;
 use thiserror::Error;
 
 use ssi::did::{
-    Context, Contexts, DIDMethod, Document, Source, VerificationMethod, VerificationMethodMap,
-    DEFAULT_CONTEXT, DIDURL,
+    Context, Contexts, DIDMethod, Document, GenerateError, Source, VerificationMethod,
+    VerificationMethodMap, DEFAULT_CONTEXT, DIDURL,
 };
 use ssi::did_resolve::{
     DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata, ERROR_INVALID_DID,
@@ -243,58 +243,50 @@ impl DIDMethod for DIDKey {
         "key"
     }
 
-    fn generate(&self, source: &Source) -> Option<String> {
+    fn generate(&self, source: &Source) -> Result<String, GenerateError> {
         let jwk = match source {
             Source::Key(jwk) => jwk,
             Source::KeyAndPattern(jwk, pattern) => {
                 if !pattern.is_empty() {
-                    // pattern not supported
-                    return None;
+                    return Err(GenerateError::UnsupportedPattern(pattern.to_string()));
                 }
                 jwk
             }
-            _ => return None,
+            _ => return Err(GenerateError::UnsupportedSource),
         };
         let did = match jwk.params {
-            Params::OKP(ref params) => {
-                match &params.curve[..] {
-                    "Ed25519" => {
-                        "did:key:".to_string()
-                            + &multibase::encode(
-                                multibase::Base::Base58Btc,
-                                [DID_KEY_ED25519_PREFIX.to_vec(), params.public_key.0.clone()]
-                                    .concat(),
-                            )
-                    }
-                    "Bls12381G2" => {
-                        "did:key:".to_string()
-                            + &multibase::encode(
-                                multibase::Base::Base58Btc,
-                                [
-                                    DID_KEY_BLS12381_G2_PREFIX.to_vec(),
-                                    params.public_key.0.clone(),
-                                ]
-                                .concat(),
-                            )
-                    }
-                    //_ => return Some(Err(DIDKeyError::UnsupportedCurve(params.curve.clone()))),
-                    _ => return None,
+            Params::OKP(ref params) => match &params.curve[..] {
+                "Ed25519" => {
+                    "did:key:".to_string()
+                        + &multibase::encode(
+                            multibase::Base::Base58Btc,
+                            [DID_KEY_ED25519_PREFIX.to_vec(), params.public_key.0.clone()].concat(),
+                        )
                 }
-            }
+                "Bls12381G2" => {
+                    "did:key:".to_string()
+                        + &multibase::encode(
+                            multibase::Base::Base58Btc,
+                            [
+                                DID_KEY_BLS12381_G2_PREFIX.to_vec(),
+                                params.public_key.0.clone(),
+                            ]
+                            .concat(),
+                        )
+                }
+                _ => return Err(GenerateError::UnsupportedCurve(params.curve.clone())),
+            },
             Params::EC(ref params) => {
                 let curve = match params.curve {
                     Some(ref curve) => curve,
-                    None => return None,
+                    None => return Err(GenerateError::Error(ssi::error::Error::MissingCurve)),
                 };
                 match &curve[..] {
                     #[cfg(feature = "secp256k1")]
                     "secp256k1" => {
                         use k256::elliptic_curve::sec1::ToEncodedPoint;
                         use std::convert::TryFrom;
-                        let pk = match k256::PublicKey::try_from(params) {
-                            Ok(pk) => pk,
-                            Err(_err) => return None,
-                        };
+                        let pk = k256::PublicKey::try_from(params)?;
                         "did:key:".to_string()
                             + &multibase::encode(
                                 multibase::Base::Base58Btc,
@@ -309,10 +301,7 @@ impl DIDMethod for DIDKey {
                     "P-256" => {
                         use p256::elliptic_curve::sec1::ToEncodedPoint;
                         use std::convert::TryFrom;
-                        let pk = match p256::PublicKey::try_from(params) {
-                            Ok(pk) => pk,
-                            Err(_err) => return None,
-                        };
+                        let pk = p256::PublicKey::try_from(params)?;
                         "did:key:".to_string()
                             + &multibase::encode(
                                 multibase::Base::Base58Btc,
@@ -323,21 +312,21 @@ impl DIDMethod for DIDKey {
                                 .concat(),
                             )
                     }
-                    //_ => return Some(Err(DIDKeyError::UnsupportedCurve(params.curve.clone()))),
-                    _ => return None,
+                    _ => return Err(GenerateError::UnsupportedCurve(curve.to_string())),
                 }
             }
             Params::RSA(ref params) => {
-                let der = simple_asn1::der_encode(&params.to_public()).ok()?;
+                let der = simple_asn1::der_encode(&params.to_public())
+                    .map_err(|e| GenerateError::Other(e.to_string()))?;
                 "did:key:".to_string()
                     + &multibase::encode(
                         multibase::Base::Base58Btc,
                         [DID_KEY_RSA_PREFIX.to_vec(), der.to_vec()].concat(),
                     )
             }
-            _ => return None, // _ => return Some(Err(DIDKeyError::UnsupportedKeyType)),
+            _ => return Err(GenerateError::UnsupportedKeyType),
         };
-        Some(did)
+        Ok(did)
     }
 
     fn to_resolver(&self) -> &dyn DIDResolver {
@@ -1,15 +1,162 @@
 use async_trait::async_trait;
+use sha3::{Digest, Sha3_256};
 use std::default::Default;
+use thiserror::Error;
 
-use ssi::did::{DIDMethod, Document};
+use ssi::did::{
+    DIDMethod, Document, GenerateError, Source, VerificationMethod, VerificationMethodMap, DIDURL,
+};
 use ssi::did_resolve::{
-    DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata, ERROR_INVALID_DID,
-    TYPE_DID_LD_JSON,
+    convert_representation, DIDResolver, DocumentMetadata, ResolutionInputMetadata,
+    ResolutionMetadata, ERROR_INVALID_DID, TYPE_DID_LD_JSON,
 };
+use ssi::jwk::{Base64urlUInt, OctetParams, Params, JWK};
 use ssi::USER_AGENT;
 
 const TOR_SOCKS_PORT: usize = 9050;
 
+/// Version byte of v3 onion addresses
+const ONION_VERSION: u8 = 3;
+
+/// Fragment of the verification method for the onion service key
+const ONION_KEY_FRAGMENT: &str = "onion";
+
+#[derive(Error, Debug)]
+pub enum OnionAddressError {
+    #[error("Expected 56-character v3 onion address")]
+    Length,
+    #[error("Onion address must be lowercase")]
+    Uppercase,
+    #[error("Invalid base32 in onion address")]
+    Base32,
+    #[error("Unsupported onion address version: {0}")]
+    Version(u8),
+    #[error("Onion address checksum mismatch")]
+    Checksum,
+    #[error("Expected Ed25519 public key")]
+    KeyType,
+}
+
+#[derive(Error, Debug)]
+pub enum OnionDocumentError {
+    #[error("DID document id does not match DID")]
+    IdMismatch,
+    #[error("Verification method {0} does not match onion service key")]
+    KeyMismatch(String),
+}
+
+/// Compute the checksum of a v3 onion address.
+///
+/// <https://gitweb.torproject.org/torspec.git/tree/rend-spec-v3.txt#n2135>
+fn onion_checksum(public_key: &[u8], version: u8) -> [u8; 2] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(b".onion checksum");
+    hasher.update(public_key);
+    hasher.update([version]);
+    let hash = hasher.finalize();
+    [hash[0], hash[1]]
+}
+
+/// Decode a v3 onion address (without the `.onion` suffix) into the onion service's Ed25519
+/// public key.
+///
+/// The address is the lowercase base32 encoding of the 32-byte public key, a 2-byte checksum and
+/// a version byte.
+pub fn decode_onion_address(address: &str) -> Result<JWK, OnionAddressError> {
+    if address.len() != 56 {
+        return Err(OnionAddressError::Length);
+    }
+    if address.bytes().any(|b| b.is_ascii_uppercase()) {
+        return Err(OnionAddressError::Uppercase);
+    }
+    let data = data_encoding::BASE32_NOPAD
+        .decode(address.to_ascii_uppercase().as_bytes())
+        .map_err(|_| OnionAddressError::Base32)?;
+    let (public_key, checksum, version) = (&data[0..32], &data[32..34], data[34]);
+    if version != ONION_VERSION {
+        return Err(OnionAddressError::Version(version));
+    }
+    if checksum != onion_checksum(public_key, version) {
+        return Err(OnionAddressError::Checksum);
+    }
+    Ok(JWK::from(Params::OKP(OctetParams {
+        curve: "Ed25519".to_string(),
+        public_key: Base64urlUInt(public_key.to_vec()),
+        private_key: None,
+    })))
+}
+
+/// Encode an Ed25519 public key as a v3 onion address (without the `.onion` suffix).
+pub fn encode_onion_address(jwk: &JWK) -> Result<String, OnionAddressError> {
+    let public_key = match jwk.params {
+        Params::OKP(ref params) if params.curve == "Ed25519" && params.public_key.0.len() == 32 => {
+            &params.public_key.0
+        }
+        _ => return Err(OnionAddressError::KeyType),
+    };
+    let mut data = public_key.clone();
+    data.extend_from_slice(&onion_checksum(public_key, ONION_VERSION));
+    data.push(ONION_VERSION);
+    Ok(data_encoding::BASE32_NOPAD
+        .encode(&data)
+        .to_ascii_lowercase())
+}
+
+/// Ensure that a did:onion DID document is controlled by the onion service key.
+///
+/// If the document already contains a verification method for the key, it is kept as is.
+/// Otherwise, an Ed25519 verification method with fragment `onion` is added, for authentication,
+/// assertion and capability invocation. A `#onion` verification method with a different key is
+/// rejected.
+fn verify_onion_key(did: &str, doc: &mut Document, key: &JWK) -> Result<(), OnionDocumentError> {
+    if doc.id != did {
+        return Err(OnionDocumentError::IdMismatch);
+    }
+    let vm_url = DIDURL {
+        did: did.to_string(),
+        fragment: Some(ONION_KEY_FRAGMENT.to_string()),
+        ..Default::default()
+    };
+    let vm_id = vm_url.to_string();
+    let mut has_key = false;
+    for vm in doc.verification_method.iter().flatten() {
+        let vm_map = match vm {
+            VerificationMethod::Map(vm_map) => vm_map,
+            _ => continue,
+        };
+        let matches = match vm_map.get_jwk() {
+            Ok(jwk) => jwk.to_public().params == key.params,
+            Err(_) => false,
+        };
+        if vm_map.get_id(did) == vm_id && !matches {
+            return Err(OnionDocumentError::KeyMismatch(vm_id));
+        }
+        has_key |= matches;
+    }
+    if has_key {
+        return Ok(());
+    }
+    doc.verification_method
+        .get_or_insert_with(Vec::new)
+        .push(VerificationMethod::Map(VerificationMethodMap {
+            id: vm_id,
+            type_: "Ed25519VerificationKey2018".to_string(),
+            controller: did.to_string(),
+            public_key_jwk: Some(key.clone()),
+            ..Default::default()
+        }));
+    for relationship in [
+        &mut doc.authentication,
+        &mut doc.assertion_method,
+        &mut doc.capability_invocation,
+    ] {
+        relationship
+            .get_or_insert_with(Vec::new)
+            .push(VerificationMethod::DIDURL(vm_url.clone()));
+    }
+    Ok(())
+}
+
 /// did:onion Method
 ///
 /// [Specification](https://blockchaincommons.github.io/did-method-onion/)
@@ -40,11 +187,10 @@ fn did_onion_url(did: &str) -> Result<String, ResolutionMetadata> {
             return Err(ResolutionMetadata::from_error(ERROR_INVALID_DID));
         }
     };
-    for c in onion_address.chars() {
-        // "The method specific identifier ... MUST NOT include IP addresses or port numbers"
-        if c == '.' || c == ':' {
-            return Err(ResolutionMetadata::from_error(ERROR_INVALID_DID));
-        }
+    // The method-specific identifier must be a valid v3 onion address, which also rules out IP
+    // addresses and port numbers.
+    if decode_onion_address(onion_address).is_err() {
+        return Err(ResolutionMetadata::from_error(ERROR_INVALID_DID));
     }
     let path = match parts.peek() {
         Some(_) => parts.collect::<Vec<&str>>().join("/"),
@@ -54,6 +200,20 @@ fn did_onion_url(did: &str) -> Result<String, ResolutionMetadata> {
     Ok(url)
 }
 
+/// Verify the onion service key in a JSON(-LD) DID document representation, returning the
+/// representation with the key added if needed.
+fn verify_onion_representation(did: &str, doc_representation: &[u8]) -> Result<Vec<u8>, String> {
+    let onion_address = did
+        .strip_prefix("did:onion:")
+        .and_then(|id| id.split(':').next())
+        .ok_or_else(|| ERROR_INVALID_DID.to_string())?;
+    let key = decode_onion_address(onion_address).map_err(|e| e.to_string())?;
+    let mut doc: Document =
+        serde_json::from_slice(doc_representation).map_err(|e| e.to_string())?;
+    verify_onion_key(did, &mut doc, &key).map_err(|e| e.to_string())?;
+    serde_json::to_vec(&doc).map_err(|e| e.to_string())
+}
+
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl DIDResolver for DIDOnion {
@@ -66,17 +226,24 @@ impl DIDResolver for DIDOnion {
         Option<Document>,
         Option<DocumentMetadata>,
     ) {
+        // The DID document is parsed from the published representation, so `accept` does not
+        // apply here.
+        let input_metadata = ResolutionInputMetadata {
+            accept: None,
+            ..input_metadata.clone()
+        };
         let (mut res_meta, doc_data, doc_meta_opt) =
-            self.resolve_representation(did, input_metadata).await;
+            self.resolve_representation(did, &input_metadata).await;
         let doc_opt = if doc_data.is_empty() {
             None
         } else {
-            match serde_json::from_slice(&doc_data) {
-                Ok(doc) => doc,
+            let content_type = res_meta.content_type.as_deref().unwrap_or(TYPE_DID_LD_JSON);
+            match Document::from_representation(&doc_data, content_type) {
+                Ok(doc) => Some(doc),
                 Err(err) => {
                     return (
                         ResolutionMetadata::from_error(
-                            &("JSON Error: ".to_string() + &err.to_string()),
+                            &("Error consuming DID document: ".to_string() + &err.to_string()),
                         ),
                         None,
                         None,
@@ -175,13 +342,27 @@ impl DIDResolver for DIDOnion {
                 )
             }
         };
+        // Check that the DID document is controlled by the onion service key, which the
+        // resolver has authenticated by connecting to the onion service.
+        let doc_representation = match verify_onion_representation(did, &doc_representation) {
+            Ok(doc_representation) => doc_representation,
+            Err(err) => {
+                return (
+                    ResolutionMetadata::from_error(&format!("Invalid DID document: {}", err)),
+                    Vec::new(),
+                    None,
+                )
+            }
+        };
+        // Convert the published DID document if another representation is requested.
+        let (res_meta, doc_representation) =
+            convert_representation(doc_representation, TYPE_DID_LD_JSON, input_metadata);
+        if res_meta.error.is_some() {
+            return (res_meta, Vec::new(), None);
+        }
         // TODO: set document created/updated metadata from HTTP headers?
         (
-            ResolutionMetadata {
-                error: None,
-                content_type: Some(TYPE_DID_LD_JSON.to_string()),
-                property_set: None,
-            },
+            res_meta,
             doc_representation,
             Some(DocumentMetadata::default()),
         )
@@ -193,6 +374,16 @@ impl DIDMethod for DIDOnion {
         "onion"
     }
 
+    /// Generate a did:onion DID from the Ed25519 public key of an onion service.
+    fn generate(&self, source: &Source) -> Result<String, GenerateError> {
+        let jwk = match source {
+            Source::Key(jwk) => jwk,
+            _ => return Err(GenerateError::UnsupportedSource),
+        };
+        let address = encode_onion_address(jwk).map_err(|e| GenerateError::Other(e.to_string()))?;
+        Ok(format!("did:onion:{}", address))
+    }
+
     fn to_resolver(&self) -> &dyn DIDResolver {
         self
     }
@@ -224,6 +415,95 @@ mod tests {
     const TORGAP_DEMO_DID: &str =
         "did:onion:fscst5exmlmr262byztwz4kzhggjlzumvc2ndvgytzoucr2tkgxf7mid";
 
+    #[test]
+    fn onion_address() {
+        let key = decode_onion_address(&TORGAP_DEMO_DID[10..]).unwrap();
+        match key.params {
+            Params::OKP(ref params) => assert_eq!(
+                params.public_key,
+                serde_json::from_value::<Base64urlUInt>(serde_json::json!(
+                    "LIUp9Jdi2R17QcZnbPFZOYyV5oyotNHU2J5dQUdTUa4"
+                ))
+                .unwrap()
+            ),
+            _ => panic!("expected OKP key"),
+        }
+        assert_eq!(
+            DIDOnion::default().generate(&Source::Key(&key)).unwrap(),
+            TORGAP_DEMO_DID
+        );
+
+        // Bad checksum
+        assert!(matches!(
+            decode_onion_address("fscst5exmlmr262byztwz4kzhggjlzumvc2ndvgytzoucr2tkgxf7mia"),
+            Err(OnionAddressError::Checksum) | Err(OnionAddressError::Version(_))
+        ));
+        assert!(
+            did_onion_url("did:onion:fscst5exmlmr262byztwz4kzhggjlzumvc2ndvgytzoucr2tkgxf7mi")
+                .is_err()
+        );
+        assert!(did_onion_url("did:onion:example.com").is_err());
+        assert!(matches!(
+            decode_onion_address(&TORGAP_DEMO_DID["did:onion:".len()..].to_ascii_uppercase()),
+            Err(OnionAddressError::Uppercase)
+        ));
+        assert!(did_onion_url(&TORGAP_DEMO_DID.replace("fscst5", "FSCST5")).is_err());
+    }
+
+    #[test]
+    fn verify_onion_document() {
+        let key = JWK::generate_ed25519().unwrap();
+        let did = DIDOnion::default().generate(&Source::Key(&key)).unwrap();
+        let public_key = key.to_public();
+        let vm_id = format!("{}#onion", did);
+
+        // The onion service key is added to a document without it.
+        let mut doc = Document::new(&did);
+        verify_onion_key(&did, &mut doc, &public_key).unwrap();
+        assert_eq!(doc.verification_method.as_ref().unwrap().len(), 1);
+        assert_eq!(doc.authentication.as_ref().unwrap()[0].get_id(&did), vm_id);
+        assert_eq!(
+            doc.assertion_method.as_ref().unwrap()[0].get_id(&did),
+            vm_id
+        );
+
+        // A document that already has it is unchanged.
+        let doc_before = doc.clone();
+        verify_onion_key(&did, &mut doc, &public_key).unwrap();
+        assert_eq!(doc, doc_before);
+
+        // A #onion verification method with another key is rejected.
+        let other_key = JWK::generate_ed25519().unwrap().to_public();
+        let mut doc: Document = serde_json::from_value(serde_json::json!({
+            "@context": "https://www.w3.org/ns/did/v1",
+            "id": did,
+            "verificationMethod": [{
+                "id": vm_id,
+                "type": "Ed25519VerificationKey2018",
+                "controller": did,
+                "publicKeyJwk": other_key
+            }]
+        }))
+        .unwrap();
+        assert!(matches!(
+            verify_onion_key(&did, &mut doc, &public_key),
+            Err(OnionDocumentError::KeyMismatch(_))
+        ));
+
+        // The document must be for the DID.
+        let mut doc = Document::new(TORGAP_DEMO_DID);
+        assert!(matches!(
+            verify_onion_key(&did, &mut doc, &public_key),
+            Err(OnionDocumentError::IdMismatch)
+        ));
+
+        // The representation check works on JSON documents.
+        let doc_json = serde_json::to_vec(&Document::new(&did)).unwrap();
+        let verified = verify_onion_representation(&did, &doc_json).unwrap();
+        let doc: Document = serde_json::from_slice(&verified).unwrap();
+        assert_eq!(doc.verification_method.unwrap().len(), 1);
+    }
+
     #[tokio::test]
     #[cfg_attr(not(feature = "tor-tests"), ignore)]
     async fn did_onion_resolve_live() {
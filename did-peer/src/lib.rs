@@ -0,0 +1,629 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+use ssi::did::{
+    Context, Contexts, DIDMethod, Document, GenerateError, Service, Source, VerificationMethod,
+    VerificationMethodMap, DEFAULT_CONTEXT, DIDURL,
+};
+use ssi::did_resolve::{
+    DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata, ERROR_INVALID_DID,
+};
+use ssi::jwk::{Base64urlUInt, OctetParams, Params, JWK};
+
+const DID_PEER_ED25519_PREFIX: [u8; 2] = [0xed, 0x01];
+const DID_PEER_X25519_PREFIX: [u8; 2] = [0xec, 0x01];
+
+// https://identity.foundation/peer-did-method-spec/#method-2-multiple-inception-key-without-doc
+const SERVICE_ABBREVIATIONS: [(&str, &str); 4] = [
+    ("type", "t"),
+    ("serviceEndpoint", "s"),
+    ("routingKeys", "r"),
+    ("accept", "a"),
+];
+const SERVICE_TYPE_ABBREVIATIONS: [(&str, &str); 1] = [("DIDCommMessaging", "dm")];
+
+#[derive(Error, Debug)]
+pub enum DIDPeerError {
+    #[error("Expected did:peer DID")]
+    NotPeerDID,
+    #[error("Unsupported numalgo: {0}")]
+    UnsupportedNumalgo(char),
+    #[error("Unsupported purpose code: {0}")]
+    UnsupportedPurpose(char),
+    #[error("Unsupported key type")]
+    UnsupportedKeyType,
+    #[error("Unsupported curve: {0}")]
+    UnsupportedCurve(String),
+    #[error("Invalid key length")]
+    InvalidKeyLength,
+    #[error("Expected multibase base58btc (z) encoded key")]
+    ExpectedMultibaseZ,
+    #[error("Invalid service")]
+    InvalidService,
+    #[error("Missing key")]
+    MissingKey,
+    #[error(transparent)]
+    Multibase(#[from] multibase::Error),
+    #[error(transparent)]
+    Base64(#[from] base64::DecodeError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Verification relationship of a key in a numalgo 2 `did:peer` DID, encoded as the purpose
+/// code prefixing the key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Purpose {
+    /// `A`: `assertionMethod`
+    Assertion,
+    /// `E`: `keyAgreement`
+    Encryption,
+    /// `V`: `authentication`
+    Verification,
+    /// `I`: `capabilityInvocation`
+    CapabilityInvocation,
+    /// `D`: `capabilityDelegation`
+    CapabilityDelegation,
+}
+
+impl Purpose {
+    pub fn code(&self) -> char {
+        match self {
+            Self::Assertion => 'A',
+            Self::Encryption => 'E',
+            Self::Verification => 'V',
+            Self::CapabilityInvocation => 'I',
+            Self::CapabilityDelegation => 'D',
+        }
+    }
+
+    pub fn from_code(code: char) -> Result<Self, DIDPeerError> {
+        Ok(match code {
+            'A' => Self::Assertion,
+            'E' => Self::Encryption,
+            'V' => Self::Verification,
+            'I' => Self::CapabilityInvocation,
+            'D' => Self::CapabilityDelegation,
+            _ => return Err(DIDPeerError::UnsupportedPurpose(code)),
+        })
+    }
+
+    /// Default purpose for a key: key agreement for X25519 keys, authentication otherwise.
+    pub fn for_key(jwk: &JWK) -> Self {
+        match jwk.params {
+            Params::OKP(ref params) if params.curve == "X25519" => Self::Encryption,
+            _ => Self::Verification,
+        }
+    }
+}
+
+/// Encode a public key as a multibase (base58btc) multicodec value.
+fn encode_key(jwk: &JWK) -> Result<String, DIDPeerError> {
+    let params = match jwk.params {
+        Params::OKP(ref params) => params,
+        _ => return Err(DIDPeerError::UnsupportedKeyType),
+    };
+    let prefix = match &params.curve[..] {
+        "Ed25519" => DID_PEER_ED25519_PREFIX,
+        "X25519" => DID_PEER_X25519_PREFIX,
+        _ => return Err(DIDPeerError::UnsupportedCurve(params.curve.clone())),
+    };
+    Ok(multibase::encode(
+        multibase::Base::Base58Btc,
+        [prefix.to_vec(), params.public_key.0.clone()].concat(),
+    ))
+}
+
+/// Decode a multibase multicodec public key, returning the JWK and verification method type.
+fn decode_key(encoded: &str) -> Result<(JWK, &'static str), DIDPeerError> {
+    let data = match multibase::decode(encoded)? {
+        (multibase::Base::Base58Btc, data) => data,
+        _ => return Err(DIDPeerError::ExpectedMultibaseZ),
+    };
+    if data.len() < 2 {
+        return Err(DIDPeerError::InvalidKeyLength);
+    }
+    let (curve, vm_type) = if data[..2] == DID_PEER_ED25519_PREFIX {
+        ("Ed25519", "Ed25519VerificationKey2018")
+    } else if data[..2] == DID_PEER_X25519_PREFIX {
+        ("X25519", "X25519KeyAgreementKey2019")
+    } else {
+        return Err(DIDPeerError::UnsupportedKeyType);
+    };
+    if data.len() - 2 != 32 {
+        return Err(DIDPeerError::InvalidKeyLength);
+    }
+    let jwk = JWK::from(Params::OKP(OctetParams {
+        curve: curve.to_string(),
+        public_key: Base64urlUInt(data[2..].to_vec()),
+        private_key: None,
+    }));
+    Ok((jwk, vm_type))
+}
+
+fn replace_keys(object: &mut serde_json::Map<String, Value>, from_abbreviated: bool) {
+    for (name, abbreviation) in SERVICE_ABBREVIATIONS.iter() {
+        let (from, to) = if from_abbreviated {
+            (abbreviation, name)
+        } else {
+            (name, abbreviation)
+        };
+        if let Some(value) = object.remove(*from) {
+            object.insert(to.to_string(), value);
+        }
+    }
+}
+
+fn replace_types(value: &mut Value, from_abbreviated: bool) {
+    match value {
+        Value::String(type_) => {
+            for (name, abbreviation) in SERVICE_TYPE_ABBREVIATIONS.iter() {
+                let (from, to) = if from_abbreviated {
+                    (abbreviation, name)
+                } else {
+                    (name, abbreviation)
+                };
+                if type_.as_str() == *from {
+                    *type_ = to.to_string();
+                }
+            }
+        }
+        Value::Array(types) => {
+            for type_ in types {
+                replace_types(type_, from_abbreviated);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Abbreviate or expand the property names and type values of a service. Service endpoint
+/// objects (`{"uri": ..., "r": ..., "a": ...}`) are transformed as well.
+fn transform_service(
+    mut service: serde_json::Map<String, Value>,
+    from_abbreviated: bool,
+) -> serde_json::Map<String, Value> {
+    replace_keys(&mut service, from_abbreviated);
+    let type_name = if from_abbreviated { "type" } else { "t" };
+    if let Some(type_) = service.get_mut(type_name) {
+        replace_types(type_, from_abbreviated);
+    }
+    let endpoint_name = if from_abbreviated {
+        "serviceEndpoint"
+    } else {
+        "s"
+    };
+    match service.get_mut(endpoint_name) {
+        Some(Value::Object(endpoint)) => replace_keys(endpoint, from_abbreviated),
+        Some(Value::Array(endpoints)) => {
+            for endpoint in endpoints {
+                if let Value::Object(endpoint) = endpoint {
+                    replace_keys(endpoint, from_abbreviated);
+                }
+            }
+        }
+        _ => {}
+    }
+    service
+}
+
+/// Encode a service as an abbreviated, base64url-encoded JSON object. The service `id` is not
+/// encoded; it is assigned during resolution.
+fn encode_service(service: &Service) -> Result<String, DIDPeerError> {
+    let mut object = match serde_json::to_value(service)? {
+        Value::Object(object) => object,
+        _ => return Err(DIDPeerError::InvalidService),
+    };
+    object.remove("id");
+    let abbreviated = transform_service(object, false);
+    Ok(base64::encode_config(
+        serde_json::to_string(&abbreviated)?,
+        base64::URL_SAFE_NO_PAD,
+    ))
+}
+
+/// Decode an encoded service element, which may contain a single service or an array of
+/// services.
+fn decode_services(encoded: &str) -> Result<Vec<serde_json::Map<String, Value>>, DIDPeerError> {
+    let json = base64::decode_config(encoded, base64::URL_SAFE_NO_PAD)?;
+    let objects = match serde_json::from_slice(&json)? {
+        Value::Object(object) => vec![object],
+        Value::Array(values) => values
+            .into_iter()
+            .map(|value| match value {
+                Value::Object(object) => Ok(object),
+                _ => Err(DIDPeerError::InvalidService),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        _ => return Err(DIDPeerError::InvalidService),
+    };
+    Ok(objects
+        .into_iter()
+        .map(|object| transform_service(object, true))
+        .collect())
+}
+
+/// Build the document for a numalgo 0 or numalgo 2 `did:peer` DID.
+fn resolve_peer(did: &str) -> Result<Document, DIDPeerError> {
+    let method_specific_id = did
+        .strip_prefix("did:peer:")
+        .ok_or(DIDPeerError::NotPeerDID)?;
+    let numalgo = method_specific_id
+        .chars()
+        .next()
+        .ok_or(DIDPeerError::NotPeerDID)?;
+    let mut keys: Vec<(Purpose, &str)> = Vec::new();
+    let mut services = Vec::new();
+    match numalgo {
+        '0' => {
+            let encoded_key = &method_specific_id[1..];
+            let (jwk, _) = decode_key(encoded_key)?;
+            keys.push((Purpose::for_key(&jwk), encoded_key));
+        }
+        '2' => {
+            if !method_specific_id[1..].starts_with('.') {
+                return Err(DIDPeerError::NotPeerDID);
+            }
+            for element in method_specific_id[1..].split('.').skip(1) {
+                let mut chars = element.chars();
+                let code = chars.next().ok_or(DIDPeerError::NotPeerDID)?;
+                let value = chars.as_str();
+                if code == 'S' {
+                    services.append(&mut decode_services(value)?);
+                } else {
+                    keys.push((Purpose::from_code(code)?, value));
+                }
+            }
+        }
+        _ => return Err(DIDPeerError::UnsupportedNumalgo(numalgo)),
+    }
+    if keys.is_empty() {
+        return Err(DIDPeerError::MissingKey);
+    }
+
+    let mut context = BTreeMap::new();
+    context.insert(
+        "publicKeyJwk".to_string(),
+        serde_json::json!({
+            "@id": "https://w3id.org/security#publicKeyJwk",
+            "@type": "@json"
+        }),
+    );
+    let mut doc = Document {
+        id: did.to_string(),
+        ..Default::default()
+    };
+    let mut verification_methods = Vec::new();
+    for (i, (purpose, encoded_key)) in keys.into_iter().enumerate() {
+        let (jwk, vm_type) = decode_key(encoded_key)?;
+        context.insert(
+            vm_type.to_string(),
+            Value::String(format!("https://w3id.org/security#{}", vm_type)),
+        );
+        // numalgo 0 uses the key itself as the fragment, as in did:key.
+        let fragment = if numalgo == '0' {
+            encoded_key.to_string()
+        } else {
+            format!("key-{}", i + 1)
+        };
+        let vm_didurl = DIDURL {
+            did: did.to_string(),
+            fragment: Some(fragment),
+            ..Default::default()
+        };
+        verification_methods.push(VerificationMethod::Map(VerificationMethodMap {
+            id: vm_didurl.to_string(),
+            type_: vm_type.to_string(),
+            controller: did.to_string(),
+            public_key_jwk: Some(jwk),
+            ..Default::default()
+        }));
+        let vm_ref = VerificationMethod::DIDURL(vm_didurl);
+        let relationships = match purpose {
+            Purpose::Verification if numalgo == '0' => {
+                vec![&mut doc.authentication, &mut doc.assertion_method]
+            }
+            Purpose::Verification => vec![&mut doc.authentication],
+            Purpose::Assertion => vec![&mut doc.assertion_method],
+            Purpose::Encryption => vec![&mut doc.key_agreement],
+            Purpose::CapabilityInvocation => vec![&mut doc.capability_invocation],
+            Purpose::CapabilityDelegation => vec![&mut doc.capability_delegation],
+        };
+        for relationship in relationships {
+            relationship
+                .get_or_insert_with(Vec::new)
+                .push(vm_ref.clone());
+        }
+    }
+    doc.verification_method = Some(verification_methods);
+    if !services.is_empty() {
+        let services = services
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut object)| {
+                let id = match i {
+                    0 => format!("{}#service", did),
+                    _ => format!("{}#service-{}", did, i),
+                };
+                object.insert("id".to_string(), Value::String(id));
+                serde_json::from_value(Value::Object(object))
+            })
+            .collect::<Result<Vec<Service>, _>>()?;
+        doc.service = Some(services);
+    }
+    doc.context = Contexts::Many(vec![
+        Context::URI(DEFAULT_CONTEXT.to_string()),
+        Context::Object(context),
+    ]);
+    Ok(doc)
+}
+
+/// did:peer Method
+///
+/// [Specification](https://identity.foundation/peer-did-method-spec/)
+///
+/// Supports numalgo 0 (inception key without document) and numalgo 2 (multiple inception keys,
+/// with optional abbreviated services). Ed25519 keys are used for authentication and X25519
+/// keys for key agreement.
+pub struct DIDPeer;
+
+impl DIDPeer {
+    /// Generate a numalgo 0 DID from an Ed25519 or X25519 public key.
+    pub fn generate_numalgo0(jwk: &JWK) -> Result<String, DIDPeerError> {
+        Ok(format!("did:peer:0{}", encode_key(jwk)?))
+    }
+
+    /// Generate a numalgo 2 DID from keys with their purposes, and services.
+    pub fn generate_numalgo2(
+        keys: &[(Purpose, &JWK)],
+        services: &[Service],
+    ) -> Result<String, DIDPeerError> {
+        if keys.is_empty() {
+            return Err(DIDPeerError::MissingKey);
+        }
+        let mut did = "did:peer:2".to_string();
+        for (purpose, jwk) in keys {
+            did.push('.');
+            did.push(purpose.code());
+            did.push_str(&encode_key(jwk)?);
+        }
+        for service in services {
+            did.push_str(".S");
+            did.push_str(&encode_service(service)?);
+        }
+        Ok(did)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl DIDResolver for DIDPeer {
+    async fn resolve(
+        &self,
+        did: &str,
+        _input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        match resolve_peer(did) {
+            Ok(doc) => (
+                ResolutionMetadata::default(),
+                Some(doc),
+                Some(DocumentMetadata::default()),
+            ),
+            // TODO: pass through these errors somehow
+            Err(_err) => (
+                ResolutionMetadata::from_error(ERROR_INVALID_DID),
+                None,
+                None,
+            ),
+        }
+    }
+}
+
+impl DIDMethod for DIDPeer {
+    fn name(&self) -> &'static str {
+        "peer"
+    }
+
+    /// Generate a DID from a single key. The pattern selects the numalgo: `0` (the default) or
+    /// `2`, in which case the key's purpose is derived from its curve.
+    fn generate(&self, source: &Source) -> Result<String, GenerateError> {
+        let (jwk, pattern) = match source {
+            Source::Key(jwk) => (jwk, ""),
+            Source::KeyAndPattern(jwk, pattern) => (jwk, *pattern),
+            _ => return Err(GenerateError::UnsupportedSource),
+        };
+        match pattern {
+            "" | "0" => Self::generate_numalgo0(jwk),
+            "2" => Self::generate_numalgo2(&[(Purpose::for_key(jwk), jwk)], &[]),
+            _ => return Err(GenerateError::UnsupportedPattern(pattern.to_string())),
+        }
+        .map_err(|e| GenerateError::Other(e.to_string()))
+    }
+
+    fn to_resolver(&self) -> &dyn DIDResolver {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ssi::did::{Resource, ServiceEndpoint};
+    use ssi::did_resolve::{dereference, Content, DereferencingInputMetadata};
+    use ssi::one_or_many::OneOrMany;
+
+    // https://identity.foundation/peer-did-method-spec/#example-peer-did-2
+    const DID_NUMALGO2: &str = "did:peer:2.Ez6LSbysY2xFMRpGMhb7tFTLMpeuPRaqaWM1yECx2AtzE3KCc.Vz6MkqRYqQiSgvZQdnBytw86Qbs2ZWUkGv22od935YF4s8M7V.SeyJ0IjoiZG0iLCJzIjoiaHR0cHM6Ly9leGFtcGxlLmNvbS9lbmRwb2ludCIsInIiOlsiZGlkOmV4YW1wbGU6c29tZW1lZGlhdG9yI3NvbWVrZXkiXSwiYSI6WyJkaWRjb21tL3YyIiwiZGlkY29tbS9haXAyO2Vudj1yZmM1ODciXX0";
+
+    #[async_std::test]
+    async fn resolve_numalgo0() {
+        let did = "did:peer:0z6MkpTHR8VNsBxYAAWHut2Geadd9jSwuBV8xRoAnwWsdvktH";
+        let vm = format!("{}#z6MkpTHR8VNsBxYAAWHut2Geadd9jSwuBV8xRoAnwWsdvktH", did);
+        let (res_meta, object, _meta) =
+            dereference(&DIDPeer, &vm, &DereferencingInputMetadata::default()).await;
+        assert_eq!(res_meta.error, None);
+        let vm = match object {
+            Content::Object(Resource::VerificationMethod(vm)) => vm,
+            _ => unreachable!(),
+        };
+        assert_eq!(vm.type_, "Ed25519VerificationKey2018");
+        let key = vm.public_key_jwk.unwrap();
+
+        // convert back to DID from JWK
+        let did1 = DIDPeer.generate(&Source::Key(&key)).unwrap();
+        assert_eq!(did1, did);
+
+        let (res_meta, _doc, _meta) = DIDPeer
+            .resolve("did:peer:1zQmZ", &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, Some(ERROR_INVALID_DID.to_string()));
+
+        // Keys must be base58btc-encoded.
+        let (_base, data) =
+            multibase::decode("z6MkpTHR8VNsBxYAAWHut2Geadd9jSwuBV8xRoAnwWsdvktH").unwrap();
+        let did = format!(
+            "did:peer:0{}",
+            multibase::encode(multibase::Base::Base32Lower, data)
+        );
+        let (res_meta, _doc, _meta) = DIDPeer
+            .resolve(&did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, Some(ERROR_INVALID_DID.to_string()));
+    }
+
+    #[async_std::test]
+    async fn resolve_numalgo2() {
+        let (res_meta, doc, _meta) = DIDPeer
+            .resolve(DID_NUMALGO2, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, None);
+        let doc = doc.unwrap();
+        let key_agreement = format!("{}#key-1", DID_NUMALGO2);
+        let authentication = format!("{}#key-2", DID_NUMALGO2);
+        let vms = doc.verification_method.as_ref().unwrap();
+        assert_eq!(vms.len(), 2);
+        assert_eq!(vms[0].get_id(DID_NUMALGO2), key_agreement);
+        assert_eq!(
+            doc.key_agreement.as_ref().unwrap()[0].get_id(DID_NUMALGO2),
+            key_agreement
+        );
+        assert_eq!(
+            doc.authentication.as_ref().unwrap()[0].get_id(DID_NUMALGO2),
+            authentication
+        );
+        assert!(doc.assertion_method.is_none());
+
+        let services = doc.service.as_ref().unwrap();
+        assert_eq!(services.len(), 1);
+        let service = &services[0];
+        assert_eq!(service.id, format!("{}#service", DID_NUMALGO2));
+        assert_eq!(
+            service.type_,
+            OneOrMany::One("DIDCommMessaging".to_string())
+        );
+        assert_eq!(
+            service.service_endpoint,
+            Some(OneOrMany::One(ServiceEndpoint::URI(
+                "https://example.com/endpoint".to_string()
+            )))
+        );
+        let properties = service.property_set.as_ref().unwrap();
+        assert_eq!(
+            properties.get("routingKeys").unwrap(),
+            &serde_json::json!(["did:example:somemediator#somekey"])
+        );
+        assert_eq!(
+            properties.get("accept").unwrap(),
+            &serde_json::json!(["didcomm/v2", "didcomm/aip2;env=rfc587"])
+        );
+        doc.validate().unwrap();
+    }
+
+    #[async_std::test]
+    async fn generate_numalgo2() {
+        let signing_key = JWK::generate_ed25519().unwrap();
+        // Only the encoding is under test, so reuse the key bytes as an X25519 key.
+        let mut agreement_key = signing_key.to_public();
+        if let Params::OKP(ref mut params) = agreement_key.params {
+            params.curve = "X25519".to_string();
+        }
+        let service: Service = serde_json::from_value(serde_json::json!({
+            "id": "#didcomm",
+            "type": "DIDCommMessaging",
+            "serviceEndpoint": {
+                "uri": "https://example.com/endpoint",
+                "accept": ["didcomm/v2"],
+                "routingKeys": []
+            }
+        }))
+        .unwrap();
+        let did = DIDPeer::generate_numalgo2(
+            &[
+                (Purpose::Encryption, &agreement_key),
+                (Purpose::Verification, &signing_key),
+            ],
+            std::slice::from_ref(&service),
+        )
+        .unwrap();
+        assert!(did.starts_with("did:peer:2.Ez6LS"));
+
+        let (res_meta, doc, _meta) = DIDPeer
+            .resolve(&did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, None);
+        let doc = doc.unwrap();
+        assert_eq!(doc.key_agreement.as_ref().unwrap().len(), 1);
+        assert_eq!(doc.authentication.as_ref().unwrap().len(), 1);
+        let resolved_service = &doc.service.as_ref().unwrap()[0];
+        assert_eq!(resolved_service.type_, service.type_);
+        assert_eq!(resolved_service.service_endpoint, service.service_endpoint);
+
+        // Single key via DIDMethod::generate
+        let did = DIDPeer
+            .generate(&Source::KeyAndPattern(&agreement_key, "2"))
+            .unwrap();
+        assert!(did.starts_with("did:peer:2.Ez6LS"));
+        assert!(matches!(
+            DIDPeer.generate(&Source::KeyAndPattern(&signing_key, "1")),
+            Err(GenerateError::UnsupportedPattern(_))
+        ));
+    }
+
+    #[async_std::test]
+    async fn credential_prove_verify_did_peer() {
+        use ssi::vc::{get_verification_method, Credential, Issuer, LinkedDataProofOptions, URI};
+        let key = JWK::generate_ed25519().unwrap();
+        let did = DIDPeer.generate(&Source::Key(&key)).unwrap();
+        let mut vc: Credential = serde_json::from_value(serde_json::json!({
+            "@context": "https://www.w3.org/2018/credentials/v1",
+            "type": ["VerifiableCredential"],
+            "issuer": did.clone(),
+            "issuanceDate": "2021-02-18T20:17:46Z",
+            "credentialSubject": {
+                "id": "did:example:d23dd687a7dc6787646f2eb98d0"
+            }
+        }))
+        .unwrap();
+        let verification_method = get_verification_method(&did, &DIDPeer).await.unwrap();
+        let mut issue_options = LinkedDataProofOptions::default();
+        issue_options.verification_method = Some(URI::String(verification_method));
+        let proof = vc
+            .generate_proof(&key, &issue_options, &DIDPeer)
+            .await
+            .unwrap();
+        vc.add_proof(proof);
+        vc.validate().unwrap();
+        let verification_result = vc.verify(None, &DIDPeer).await;
+        println!("{:#?}", verification_result);
+        assert!(verification_result.errors.is_empty());
+
+        vc.issuer = Some(Issuer::URI(URI::String("did:example:bad".to_string())));
+        assert!(vc.verify(None, &DIDPeer).await.errors.len() > 0);
+    }
+}
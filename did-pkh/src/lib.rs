@@ -6,8 +6,8 @@ use std::str::FromStr;
 use ssi::caip10::BlockchainAccountId;
 use ssi::caip2::ChainId;
 use ssi::did::{
-    Context, Contexts, DIDMethod, Document, Source, VerificationMethod, VerificationMethodMap,
-    DEFAULT_CONTEXT, DIDURL,
+    Context, Contexts, DIDMethod, Document, GenerateError, Source, VerificationMethod,
+    VerificationMethodMap, DEFAULT_CONTEXT, DIDURL,
 };
 use ssi::did_resolve::{
     DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata, ERROR_INVALID_DID,
@@ -31,6 +31,17 @@ const REFERENCE_TEZOS_MAINNET: &str = "NetXdQprcVkpaWU";
 // https://github.com/ChainAgnostic/CAIPs/blob/master/CAIPs/caip-30.md
 const REFERENCE_SOLANA_MAINNET: &str = "4sGjMW1sUnHzSxGspuhpqLDx6wiyjNtZ";
 
+// https://github.com/ChainAgnostic/CAIPs/blob/master/CAIPs/caip-5.md
+const REFERENCE_COSMOS_HUB: &str = "cosmoshub-4";
+
+// https://github.com/ChainAgnostic/CAIPs/blob/master/CAIPs/caip-13.md
+const REFERENCE_POLKADOT_MAINNET: &str = "91b171bb158e2d3848fa23a9f1c25182";
+
+const REFERENCE_POLKADOT_KUSAMA: &str = "b0a8d493285c2df73290dfb7e61f870f";
+
+// https://github.com/ChainAgnostic/namespaces/blob/main/algorand/caip2.md
+const REFERENCE_ALGORAND_MAINNET: &str = "wGHE2Pwdvd7S12BL5FaOP20EGYesN73k";
+
 /// did:pkh DID Method
 pub struct DIDPKH;
 
@@ -365,6 +376,197 @@ async fn resolve_bip122(did: &str, account_address: String, reference: &str) ->
     resolution_result(doc)
 }
 
+async fn resolve_cosmos(did: &str, account_address: String, reference: &str) -> ResolutionResult {
+    // Lowercase Bech32 address of a 20-byte (account) or 32-byte (module account) hash
+    use bech32::FromBase32;
+    if account_address.contains(|c: char| c.is_ascii_uppercase()) {
+        return resolution_error(ERROR_INVALID_DID);
+    }
+    let (hrp, data) = match bech32::decode(&account_address) {
+        Ok((hrp, data, bech32::Variant::Bech32)) => (hrp, data),
+        _ => return resolution_error(ERROR_INVALID_DID),
+    };
+    if !hrp
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    {
+        return resolution_error(ERROR_INVALID_DID);
+    }
+    match Vec::<u8>::from_base32(&data) {
+        Ok(hash) if hash.len() == 20 || hash.len() == 32 => {}
+        _ => return resolution_error(ERROR_INVALID_DID),
+    }
+    let blockchain_account_id = BlockchainAccountId {
+        account_address,
+        chain_id: ChainId {
+            namespace: "cosmos".to_string(),
+            reference: reference.to_string(),
+        },
+    };
+    let vm_url = DIDURL {
+        did: did.to_string(),
+        fragment: Some("blockchainAccountId".to_string()),
+        ..Default::default()
+    };
+    let vm = VerificationMethod::Map(VerificationMethodMap {
+        id: String::from(vm_url.clone()),
+        type_: "EcdsaSecp256k1RecoveryMethod2020".to_string(),
+        controller: did.to_string(),
+        blockchain_account_id: Some(blockchain_account_id.to_string()),
+        ..Default::default()
+    });
+    let mut context = BTreeMap::new();
+    context.insert(
+        "blockchainAccountId".to_string(),
+        Value::String("https://w3id.org/security#blockchainAccountId".to_string()),
+    );
+    context.insert(
+        "EcdsaSecp256k1RecoveryMethod2020".to_string(),
+        Value::String("https://identity.foundation/EcdsaSecp256k1RecoverySignature2020#EcdsaSecp256k1RecoveryMethod2020".to_string()),
+    );
+    let doc = Document {
+        context: Contexts::Many(vec![
+            Context::URI(DEFAULT_CONTEXT.to_string()),
+            Context::Object(context),
+        ]),
+        id: did.to_string(),
+        verification_method: Some(vec![vm]),
+        authentication: Some(vec![VerificationMethod::DIDURL(vm_url.clone())]),
+        assertion_method: Some(vec![VerificationMethod::DIDURL(vm_url)]),
+        ..Default::default()
+    };
+    resolution_result(doc)
+}
+
+/// Resolve a DID for an account identified by an Ed25519 public key, such as Algorand accounts.
+fn resolve_ed25519_account(
+    did: &str,
+    public_key_bytes: Vec<u8>,
+    blockchain_account_id: BlockchainAccountId,
+) -> ResolutionResult {
+    let mut context = BTreeMap::new();
+    context.insert(
+        "blockchainAccountId".to_string(),
+        Value::String("https://w3id.org/security#blockchainAccountId".to_string()),
+    );
+    context.insert(
+        "publicKeyJwk".to_string(),
+        serde_json::json!({
+            "@id": "https://w3id.org/security#publicKeyJwk",
+            "@type": "@json"
+        }),
+    );
+    context.insert(
+        "Ed25519VerificationKey2018".to_string(),
+        Value::String("https://w3id.org/security#Ed25519VerificationKey2018".to_string()),
+    );
+    let pk_jwk = JWK::from(Params::OKP(OctetParams {
+        curve: "Ed25519".to_string(),
+        public_key: Base64urlUInt(public_key_bytes),
+        private_key: None,
+    }));
+    let vm_url = DIDURL {
+        did: did.to_string(),
+        fragment: Some("blockchainAccountId".to_string()),
+        ..Default::default()
+    };
+    let vm = VerificationMethod::Map(VerificationMethodMap {
+        id: vm_url.to_string(),
+        type_: "Ed25519VerificationKey2018".to_string(),
+        public_key_jwk: Some(pk_jwk),
+        controller: did.to_string(),
+        blockchain_account_id: Some(blockchain_account_id.to_string()),
+        ..Default::default()
+    });
+    let doc = Document {
+        context: Contexts::Many(vec![
+            Context::URI(DEFAULT_CONTEXT.to_string()),
+            Context::Object(context),
+        ]),
+        id: did.to_string(),
+        verification_method: Some(vec![vm]),
+        authentication: Some(vec![VerificationMethod::DIDURL(vm_url.clone())]),
+        assertion_method: Some(vec![VerificationMethod::DIDURL(vm_url)]),
+        ..Default::default()
+    };
+    resolution_result(doc)
+}
+
+async fn resolve_polkadot(did: &str, account_address: String, reference: &str) -> ResolutionResult {
+    let (address_type, _account_id) = match ssi::ss58::decode(&account_address) {
+        Ok(decoded) => decoded,
+        Err(_) => return resolution_error(ERROR_INVALID_DID),
+    };
+    match (reference, address_type) {
+        (REFERENCE_POLKADOT_MAINNET, ssi::ss58::POLKADOT)
+        | (REFERENCE_POLKADOT_KUSAMA, ssi::ss58::KUSAMA) => {}
+        (REFERENCE_POLKADOT_MAINNET, _) | (REFERENCE_POLKADOT_KUSAMA, _) => {
+            return resolution_error(ERROR_INVALID_DID)
+        }
+        _ => {
+            // Unknown network: no address type check
+        }
+    }
+    let blockchain_account_id = BlockchainAccountId {
+        account_address,
+        chain_id: ChainId {
+            namespace: "polkadot".to_string(),
+            reference: reference.to_string(),
+        },
+    };
+    // An SS58 address does not say whether its account id is an Sr25519 (the wallet default),
+    // Ed25519 or ECDSA public key, so the verification method is given by the account id alone.
+    // Ed25519Signature2018 proofs for it carry the public key, checked against the account id.
+    let mut context = BTreeMap::new();
+    context.insert(
+        "blockchainAccountId".to_string(),
+        Value::String("https://w3id.org/security#blockchainAccountId".to_string()),
+    );
+    context.insert(
+        "BlockchainVerificationMethod2021".to_string(),
+        Value::String("https://w3id.org/security#BlockchainVerificationMethod2021".to_string()),
+    );
+    let vm_url = DIDURL {
+        did: did.to_string(),
+        fragment: Some("blockchainAccountId".to_string()),
+        ..Default::default()
+    };
+    let vm = VerificationMethod::Map(VerificationMethodMap {
+        id: vm_url.to_string(),
+        type_: "BlockchainVerificationMethod2021".to_string(),
+        controller: did.to_string(),
+        blockchain_account_id: Some(blockchain_account_id.to_string()),
+        ..Default::default()
+    });
+    let doc = Document {
+        context: Contexts::Many(vec![
+            Context::URI(DEFAULT_CONTEXT.to_string()),
+            Context::Object(context),
+        ]),
+        id: did.to_string(),
+        verification_method: Some(vec![vm]),
+        authentication: Some(vec![VerificationMethod::DIDURL(vm_url.clone())]),
+        assertion_method: Some(vec![VerificationMethod::DIDURL(vm_url)]),
+        ..Default::default()
+    };
+    resolution_result(doc)
+}
+
+async fn resolve_algorand(did: &str, account_address: String, reference: &str) -> ResolutionResult {
+    let public_key_bytes = match ssi::algorand::decode_address(&account_address) {
+        Ok(bytes) => bytes,
+        Err(_) => return resolution_error(ERROR_INVALID_DID),
+    };
+    let blockchain_account_id = BlockchainAccountId {
+        account_address,
+        chain_id: ChainId {
+            namespace: "algorand".to_string(),
+            reference: reference.to_string(),
+        },
+    };
+    resolve_ed25519_account(did, public_key_bytes, blockchain_account_id)
+}
+
 async fn resolve_caip10(did: &str, account_id: String) -> ResolutionResult {
     let account_id = match BlockchainAccountId::from_str(&account_id) {
         Ok(account_id) => account_id,
@@ -377,6 +579,9 @@ async fn resolve_caip10(did: &str, account_id: String) -> ResolutionResult {
         "eip155" => resolve_eip155(did, account_id.account_address, &reference, false).await,
         "bip122" => resolve_bip122(did, account_id.account_address, &reference).await,
         "solana" => resolve_solana(did, account_id.account_address, &reference).await,
+        "cosmos" => resolve_cosmos(did, account_id.account_address, &reference).await,
+        "polkadot" => resolve_polkadot(did, account_id.account_address, &reference).await,
+        "algorand" => resolve_algorand(did, account_id.account_address, &reference).await,
         _ => resolution_error(ERROR_INVALID_DID),
     }
 }
@@ -524,6 +729,61 @@ fn generate_caip10_solana(
     })
 }
 
+fn generate_caip10_cosmos(
+    key: &JWK,
+    ref_opt: Option<String>,
+) -> Result<BlockchainAccountId, String> {
+    let reference = ref_opt.unwrap_or_else(|| REFERENCE_COSMOS_HUB.to_string());
+    // Address prefixes of other Cosmos chains are not known.
+    let hrp = match &reference[..] {
+        "cosmoshub-3" | "cosmoshub-4" => "cosmos",
+        _ => return Err("Unknown Cosmos chain".to_string()),
+    };
+    let addr = ssi::ripemd::hash_public_key_bech32(key, hrp)?;
+    Ok(BlockchainAccountId {
+        account_address: addr,
+        chain_id: ChainId {
+            namespace: "cosmos".to_string(),
+            reference,
+        },
+    })
+}
+
+fn generate_caip10_polkadot(
+    key: &JWK,
+    ref_opt: Option<String>,
+) -> Result<BlockchainAccountId, String> {
+    let reference = ref_opt.unwrap_or_else(|| REFERENCE_POLKADOT_MAINNET.to_string());
+    let address_type = match &reference[..] {
+        REFERENCE_POLKADOT_MAINNET => ssi::ss58::POLKADOT,
+        REFERENCE_POLKADOT_KUSAMA => ssi::ss58::KUSAMA,
+        _ => ssi::ss58::SUBSTRATE,
+    };
+    let addr = ssi::ss58::encode_public_key(key, address_type).map_err(|e| e.to_string())?;
+    Ok(BlockchainAccountId {
+        account_address: addr,
+        chain_id: ChainId {
+            namespace: "polkadot".to_string(),
+            reference,
+        },
+    })
+}
+
+fn generate_caip10_algorand(
+    key: &JWK,
+    ref_opt: Option<String>,
+) -> Result<BlockchainAccountId, String> {
+    let reference = ref_opt.unwrap_or_else(|| REFERENCE_ALGORAND_MAINNET.to_string());
+    let addr = ssi::algorand::encode_public_key(key).map_err(|e| e.to_string())?;
+    Ok(BlockchainAccountId {
+        account_address: addr,
+        chain_id: ChainId {
+            namespace: "algorand".to_string(),
+            reference,
+        },
+    })
+}
+
 fn generate_caip10_did(key: &JWK, name: &str) -> Result<String, String> {
     // Require name to be a either CAIP-2 namespace or a
     // full CAIP-2 string - namespace and reference (e.g. internal
@@ -541,6 +801,9 @@ fn generate_caip10_did(key: &JWK, name: &str) -> Result<String, String> {
         "eip155" => generate_caip10_eip155(key, reference_opt)?,
         "bip122" => generate_caip10_bip122(key, reference_opt)?,
         "solana" => generate_caip10_solana(key, reference_opt)?,
+        "cosmos" => generate_caip10_cosmos(key, reference_opt)?,
+        "polkadot" => generate_caip10_polkadot(key, reference_opt)?,
+        "algorand" => generate_caip10_algorand(key, reference_opt)?,
         _ => return Err("Namespace not supported".to_string()),
     };
     Ok(format!("did:pkh:{}", account_id))
@@ -551,28 +814,25 @@ impl DIDMethod for DIDPKH {
         "pkh"
     }
 
-    fn generate(&self, source: &Source) -> Option<String> {
+    fn generate(&self, source: &Source) -> Result<String, GenerateError> {
         let (key, pkh_name) = match source {
             Source::KeyAndPattern(key, pattern) => (key, pattern),
-            _ => return None,
+            _ => return Err(GenerateError::UnsupportedSource),
         };
-        let addr = match match &pkh_name[..] {
+        let addr = match &pkh_name[..] {
             // Aliases for did:pkh pre-CAIP-10. Deprecate?
-            "tz" => ssi::blakesig::hash_public_key(key).ok(),
-            "eth" => ssi::keccak_hash::hash_public_key(key).ok(),
-            "celo" => ssi::keccak_hash::hash_public_key(key).ok(),
-            "poly" => ssi::keccak_hash::hash_public_key(key).ok(),
-            "sol" => generate_sol(key),
-            "btc" => generate_btc(key).ok(),
-            "doge" => generate_doge(key).ok(),
+            "tz" => ssi::blakesig::hash_public_key(key)?,
+            "eth" => ssi::keccak_hash::hash_public_key(key)?,
+            "celo" => ssi::keccak_hash::hash_public_key(key)?,
+            "poly" => ssi::keccak_hash::hash_public_key(key)?,
+            "sol" => generate_sol(key).ok_or(GenerateError::UnsupportedKeyType)?,
+            "btc" => generate_btc(key).map_err(GenerateError::Other)?,
+            "doge" => generate_doge(key).map_err(GenerateError::Other)?,
             // CAIP-10/CAIP-2 chain id
-            name => return generate_caip10_did(key, name).ok(),
-        } {
-            Some(addr) => addr,
-            None => return None,
+            name => return generate_caip10_did(key, name).map_err(GenerateError::Other),
         };
         let did = format!("did:pkh:{}:{}", pkh_name, addr);
-        Some(did)
+        Ok(did)
     }
 
     fn to_resolver(&self) -> &dyn DIDResolver {
@@ -645,6 +905,31 @@ mod tests {
             "tz",
             "did:pkh:tz:tz3agP9LGe2cXmKQyYn6T68BHKjjktDbbSWX",
         );
+        test_generate(
+            secp256k1_pk.clone(),
+            "cosmos",
+            "did:pkh:cosmos:cosmoshub-4:cosmos1q8nmdyu99f50u7uv8309vuua4z3ew6xk0tgjpz",
+        );
+        let ed25519_pk = json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "x": "G80iskrv_nE69qbGLSpeOHJgmV4MKIzsy5l5iT6pCww"
+        });
+        test_generate(
+            ed25519_pk.clone(),
+            "polkadot",
+            "did:pkh:polkadot:91b171bb158e2d3848fa23a9f1c25182:1dTEPeJBU76DFJsEFq6aHzkD7frp1LB2BUAg6bnESv1KtLg",
+        );
+        test_generate(
+            ed25519_pk.clone(),
+            "polkadot:b0a8d493285c2df73290dfb7e61f870f",
+            "did:pkh:polkadot:b0a8d493285c2df73290dfb7e61f870f:DCmkNj6x3rYXN7o3Kb9L6XbW5xSvNbDQ4aRuTtPAA6ytDQF",
+        );
+        test_generate(
+            ed25519_pk.clone(),
+            "algorand",
+            "did:pkh:algorand:wGHE2Pwdvd7S12BL5FaOP20EGYesN73k:DPGSFMSK577HCOXWU3DC2KS6HBZGBGK6BQUIZ3GLTF4YSPVJBMGJ2F44EE",
+        );
     }
 
     async fn test_resolve(did: &str, doc_str_expected: &str) {
@@ -729,6 +1014,26 @@ mod tests {
             include_str!("../tests/did-doge.jsonld"),
         )
         .await;
+        test_resolve(
+            "did:pkh:cosmos:cosmoshub-4:cosmos1q8nmdyu99f50u7uv8309vuua4z3ew6xk0tgjpz",
+            include_str!("../tests/did-cosmos.jsonld"),
+        )
+        .await;
+        test_resolve(
+            "did:pkh:polkadot:91b171bb158e2d3848fa23a9f1c25182:1dTEPeJBU76DFJsEFq6aHzkD7frp1LB2BUAg6bnESv1KtLg",
+            include_str!("../tests/did-polkadot.jsonld"),
+        )
+        .await;
+        test_resolve(
+            "did:pkh:polkadot:b0a8d493285c2df73290dfb7e61f870f:DCmkNj6x3rYXN7o3Kb9L6XbW5xSvNbDQ4aRuTtPAA6ytDQF",
+            include_str!("../tests/did-kusama.jsonld"),
+        )
+        .await;
+        test_resolve(
+            "did:pkh:algorand:wGHE2Pwdvd7S12BL5FaOP20EGYesN73k:DPGSFMSK577HCOXWU3DC2KS6HBZGBGK6BQUIZ3GLTF4YSPVJBMGJ2F44EE",
+            include_str!("../tests/did-algorand.jsonld"),
+        )
+        .await;
 
         // non-CAIP-10 (deprecated)
         test_resolve(
@@ -779,6 +1084,29 @@ mod tests {
 
         test_resolve_error("did:pkh:tz:foo", ERROR_INVALID_DID).await;
         test_resolve_error("did:pkh:eth:bar", ERROR_INVALID_DID).await;
+        // Kusama address on Polkadot
+        test_resolve_error(
+            "did:pkh:polkadot:91b171bb158e2d3848fa23a9f1c25182:DCmkNj6x3rYXN7o3Kb9L6XbW5xSvNbDQ4aRuTtPAA6ytDQF",
+            ERROR_INVALID_DID,
+        )
+        .await;
+        // Bad checksum
+        test_resolve_error(
+            "did:pkh:algorand:wGHE2Pwdvd7S12BL5FaOP20EGYesN73k:EPGSFMSK577HCOXWU3DC2KS6HBZGBGK6BQUIZ3GLTF4YSPVJBMGJ2F44EE",
+            ERROR_INVALID_DID,
+        )
+        .await;
+        test_resolve_error(
+            "did:pkh:cosmos:cosmoshub-4:Cosmos1q8nmdyu99f50u7uv8309vuua4z3ew6xk0tgjpz",
+            ERROR_INVALID_DID,
+        )
+        .await;
+        // Bad Bech32 checksum
+        test_resolve_error(
+            "did:pkh:cosmos:cosmoshub-4:cosmos1q8nmdyu99f50u7uv8309vuua4z3ew6xk0tgjpy",
+            ERROR_INVALID_DID,
+        )
+        .await;
     }
 
     fn fuzz_proof_value(proof: &mut Option<OneOrMany<Proof>>) {
@@ -1302,6 +1630,78 @@ mod tests {
         )
         .await;
 
+        println!("did:pkh:btc - BitcoinMessageSignature2021");
+        credential_prove_verify_did_pkh(
+            key_secp256k1_recovery.clone(),
+            other_key_secp256k1.clone(),
+            "btc",
+            "#blockchainAccountId",
+            &ssi::ldp::BitcoinMessageSignature2021,
+            None,
+            None,
+        )
+        .await;
+
+        println!("did:pkh:doge - BitcoinMessageSignature2021");
+        credential_prove_verify_did_pkh(
+            key_secp256k1_recovery.clone(),
+            other_key_secp256k1.clone(),
+            "doge",
+            "#blockchainAccountId",
+            &ssi::ldp::BitcoinMessageSignature2021,
+            None,
+            None,
+        )
+        .await;
+
+        println!("did:pkh:cosmos");
+        credential_prove_verify_did_pkh(
+            key_secp256k1_recovery.clone(),
+            other_key_secp256k1.clone(),
+            "cosmos",
+            "#blockchainAccountId",
+            &ssi::ldp::EcdsaSecp256k1RecoverySignature2020,
+            None,
+            None,
+        )
+        .await;
+
+        println!("did:pkh:polkadot");
+        credential_prove_verify_did_pkh(
+            key_ed25519.clone(),
+            other_key_ed25519.clone(),
+            "polkadot",
+            "#blockchainAccountId",
+            &ssi::ldp::Ed25519Signature2018,
+            None,
+            None,
+        )
+        .await;
+
+        println!("did:pkh:polkadot - Kusama");
+        credential_prove_verify_did_pkh(
+            key_ed25519.clone(),
+            other_key_ed25519.clone(),
+            "polkadot:b0a8d493285c2df73290dfb7e61f870f",
+            "#blockchainAccountId",
+            &ssi::ldp::Ed25519Signature2018,
+            None,
+            None,
+        )
+        .await;
+
+        println!("did:pkh:algorand");
+        credential_prove_verify_did_pkh(
+            key_ed25519.clone(),
+            other_key_ed25519.clone(),
+            "algorand",
+            "#blockchainAccountId",
+            &ssi::ldp::Ed25519Signature2018,
+            None,
+            None,
+        )
+        .await;
+
         println!("did:pkh:tz:tz1 - TezosMethod2021");
         key_ed25519.algorithm = Some(Algorithm::EdBlake2b);
         other_key_ed25519.algorithm = Some(Algorithm::EdBlake2b);
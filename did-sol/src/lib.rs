@@ -5,8 +5,8 @@ use std::collections::BTreeMap;
 use ssi::caip10::BlockchainAccountId;
 use ssi::caip2::ChainId;
 use ssi::did::{
-    Context, Contexts, DIDMethod, Document, Source, VerificationMethod, VerificationMethodMap,
-    DEFAULT_CONTEXT, DIDURL,
+    Context, Contexts, DIDMethod, Document, GenerateError, Source, VerificationMethod,
+    VerificationMethodMap, DEFAULT_CONTEXT, DIDURL,
 };
 use ssi::did_resolve::{
     DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata, ERROR_INVALID_DID,
@@ -167,29 +167,25 @@ impl DIDMethod for DIDSol {
         "sol"
     }
 
-    fn generate(&self, source: &Source) -> Option<String> {
+    fn generate(&self, source: &Source) -> Result<String, GenerateError> {
         let jwk = match source {
             Source::Key(jwk) => jwk,
             Source::KeyAndPattern(jwk, pattern) => {
                 if !pattern.is_empty() {
-                    // pattern not supported
-                    return None;
+                    return Err(GenerateError::UnsupportedPattern(pattern.to_string()));
                 }
                 jwk
             }
-            _ => return None,
+            _ => return Err(GenerateError::UnsupportedSource),
         };
         let did = match jwk.params {
             Params::OKP(ref params) if params.curve == "Ed25519" => {
                 let addr = bs58::encode(&params.public_key.0).into_string();
                 format!("did:sol:{}", addr)
             }
-            _ => {
-                dbg!(&jwk.params);
-                return None;
-            }
+            _ => return Err(GenerateError::UnsupportedKeyType),
         };
-        Some(did)
+        Ok(did)
     }
 
     fn to_resolver(&self) -> &dyn DIDResolver {
@@ -9,9 +9,19 @@ use ssi::did_resolve::{
     dereference, Content, ContentMetadata, DIDResolver, DereferencingInputMetadata,
     DereferencingMetadata, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata,
     ERROR_INVALID_DID, ERROR_INVALID_DID_URL, ERROR_NOT_FOUND, ERROR_REPRESENTATION_NOT_SUPPORTED,
-    TYPE_DID_LD_JSON,
+    TYPE_DID_JSON, TYPE_DID_LD_JSON,
 };
 
+/// DID document representations for which test vectors are generated
+const SUPPORTED_CONTENT_TYPES: [&str; 2] = [TYPE_DID_LD_JSON, TYPE_DID_JSON];
+
+fn supported_content_types() -> Vec<ContentType> {
+    SUPPORTED_CONTENT_TYPES
+        .iter()
+        .map(|content_type| content_type.to_string())
+        .collect()
+}
+
 #[allow(clippy::upper_case_acronyms)]
 type DID = String;
 type ContentType = String;
@@ -152,36 +162,38 @@ async fn did_method_vector(resolver: &dyn DIDResolver, did: &str) -> DIDVector {
     assert_eq!(res_meta.content_type, None);
     let mut did_data = Map::new();
 
-    let input_meta = ResolutionInputMetadata {
-        accept: Some(TYPE_DID_LD_JSON.to_string()),
-        ..Default::default()
-    };
-    let (res_repr_meta, doc_repr, _doc_repr_meta_opt) =
-        resolver.resolve_representation(did, &input_meta).await;
-    assert_eq!(res_repr_meta.error, None);
-    let representation = String::from_utf8(doc_repr).unwrap();
-    let content_type = res_repr_meta.content_type.clone().unwrap();
-    assert_eq!(content_type, TYPE_DID_LD_JSON);
-
     let mut doc_value = serde_json::to_value(doc).unwrap();
-    let mut representation_specific_entries = RepresentationSpecificEntries::default();
-    match &content_type[..] {
-        TYPE_DID_LD_JSON => {
-            representation_specific_entries.context =
-                doc_value.as_object_mut().unwrap().remove("@context");
+    let context = doc_value.as_object_mut().unwrap().remove("@context");
+    let properties: Map<String, Value> = serde_json::from_value(doc_value).unwrap();
+    for content_type in &SUPPORTED_CONTENT_TYPES {
+        let input_meta = ResolutionInputMetadata {
+            accept: Some(content_type.to_string()),
+            ..Default::default()
+        };
+        let (res_repr_meta, doc_repr, _doc_repr_meta_opt) =
+            resolver.resolve_representation(did, &input_meta).await;
+        assert_eq!(res_repr_meta.error, None);
+        let representation = String::from_utf8(doc_repr).unwrap();
+        assert_eq!(res_repr_meta.content_type.as_deref(), Some(*content_type));
+
+        let mut representation_specific_entries = RepresentationSpecificEntries::default();
+        match *content_type {
+            TYPE_DID_LD_JSON => {
+                representation_specific_entries.context = context.clone();
+            }
+            TYPE_DID_JSON => {}
+            _ => unreachable!(),
         }
-        _ => unreachable!(),
+        let resolution_result = DIDData {
+            did_document_data_model: DIDDocumentDataModel2 {
+                representation_specific_entries,
+            },
+            representation,
+            did_document_metadata: doc_meta.clone(),
+            did_resolution_metadata: res_repr_meta,
+        };
+        did_data.insert(content_type.to_string(), resolution_result);
     }
-    let properties: Map<String, Value> = serde_json::from_value(doc_value).unwrap();
-    let resolution_result = DIDData {
-        did_document_data_model: DIDDocumentDataModel2 {
-            representation_specific_entries,
-        },
-        representation,
-        did_document_metadata: doc_meta,
-        did_resolution_metadata: res_repr_meta,
-    };
-    did_data.insert(content_type, resolution_result);
     DIDVector {
         did_document_data_model: DIDDocumentDataModel { properties },
         did_data,
@@ -191,7 +203,7 @@ async fn did_method_vector(resolver: &dyn DIDResolver, did: &str) -> DIDVector {
 async fn report_method_key() {
     let did_parameters = Map::new();
     let mut did_vectors = Map::new();
-    let supported_content_types = vec![TYPE_DID_LD_JSON.to_string()];
+    let supported_content_types = supported_content_types();
 
     for did in &[
         "did:key:z6MkpTHR8VNsBxYAAWHut2Geadd9jSwuBV8xRoAnwWsdvktH", // Ed25519
@@ -225,7 +237,7 @@ async fn report_method_web() {
     );
 
     let mut did_vectors = Map::new();
-    let supported_content_types = vec![TYPE_DID_LD_JSON.to_string()];
+    let supported_content_types = supported_content_types();
 
     let did = "did:web:demo.spruceid.com:2021:07:08";
     let did_vector = did_method_vector(&did_web::DIDWeb, did).await;
@@ -249,7 +261,7 @@ async fn report_method_tz() {
     let did_tz = did_tz::DIDTz::default();
     let did_parameters = Map::new();
     let mut did_vectors = Map::new();
-    let supported_content_types = vec![TYPE_DID_LD_JSON.to_string()];
+    let supported_content_types = supported_content_types();
 
     for did in &[
         "did:tz:tz1YwA1FwpgLtc1G8DKbbZ6e6PTb1dQMRn5x",
@@ -279,7 +291,7 @@ async fn report_method_onion() {
     let resolver = did_onion::DIDOnion::default();
     let did_parameters = Map::new();
     let mut did_vectors = Map::new();
-    let supported_content_types = vec![TYPE_DID_LD_JSON.to_string()];
+    let supported_content_types = supported_content_types();
 
     for did in &["did:onion:fscst5exmlmr262byztwz4kzhggjlzumvc2ndvgytzoucr2tkgxf7mid"] {
         let did_vector = did_method_vector(&resolver, did).await;
@@ -304,7 +316,7 @@ async fn report_method_pkh() {
     let resolver = did_pkh::DIDPKH;
     let did_parameters = Map::new();
     let mut did_vectors = Map::new();
-    let supported_content_types = vec![TYPE_DID_LD_JSON.to_string()];
+    let supported_content_types = supported_content_types();
 
     for did in &[
         "did:pkh:doge:DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L",
@@ -336,7 +348,7 @@ async fn report_method_webkey() {
     let resolver = did_webkey::DIDWebKey;
     let did_parameters = Map::new();
     let mut did_vectors = Map::new();
-    let supported_content_types = vec![TYPE_DID_LD_JSON.to_string()];
+    let supported_content_types = supported_content_types();
 
     for did in &["did:webkey:ssh:demo.spruceid.com:2021:07:14:keys"] {
         let did_vector = did_method_vector(&resolver, did).await;
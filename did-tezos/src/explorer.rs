@@ -1,90 +1,198 @@
-use anyhow::Result;
-use serde::Deserialize;
+//! Tier-2 resolution lookups of [DID manager contracts](https://did-tezos.spruceid.com/#on-chain-did-manager).
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use ssi::did::{Service, ServiceEndpoint, VerificationMethod, DIDURL};
 use ssi::one_or_many::OneOrMany;
 use ssi::USER_AGENT;
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::path::Path;
 use url::Url;
 
-pub async fn retrieve_did_manager(tzkt_url: &str, address: &str) -> Result<Option<String>> {
-    let client = reqwest::Client::builder().build()?;
-    let url = Url::parse(tzkt_url)?;
-    let contracts: Vec<String> = client
-        .get(url.join("/v1/contracts")?)
-        .query(&[
-            ("creator", address),
-            ("sort", "lastActivity"),
-            ("select", "address"),
-            // TODO using codeHash while all contracts have the same code and until tezedge-client provide a way to fetch TZIP-016 metadata.
-            ("codeHash", "1222545108"),
-        ])
-        .send()
-        .await?
-        .json()
-        .await?;
-
-    if !contracts.is_empty() {
-        Ok(Some(contracts[0].clone()))
-    } else {
-        Ok(None)
+/// Source of on-chain DID manager data, used for tier-2 resolution.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait Explorer: Send + Sync {
+    /// Find the DID manager contract originated by an implicit account, if any.
+    async fn retrieve_did_manager(&self, address: &str) -> Result<Option<String>>;
+
+    /// Get the discovery service of a DID manager contract.
+    async fn execute_service_view(&self, did: &str, contract: &str) -> Result<Service>;
+
+    /// Get the verification method of a DID manager contract.
+    async fn execute_auth_view(&self, contract: &str) -> Result<VerificationMethod>;
+}
+
+/// Storage of a DID manager contract, as returned by TzKT.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DIDManagerStorage {
+    pub verification_method: String,
+    pub service: DIDManagerService,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DIDManagerService {
+    pub type_: String,
+    pub endpoint: String,
+}
+
+impl DIDManagerStorage {
+    fn service(&self, did: &str) -> Service {
+        Service {
+            id: format!("{}{}", did, "#discovery"),
+            type_: OneOrMany::One(self.service.type_.clone()),
+            service_endpoint: Some(OneOrMany::One(ServiceEndpoint::URI(
+                self.service.endpoint.clone(),
+            ))),
+            property_set: None,
+        }
+    }
+
+    fn verification_method(&self) -> Result<VerificationMethod> {
+        Ok(VerificationMethod::DIDURL(DIDURL::try_from(
+            self.verification_method.clone(),
+        )?))
     }
 }
 
-#[derive(Deserialize)]
-struct ServiceResult {
-    service: ServiceResultService,
+/// [TzKT](https://api.tzkt.io/) indexer API.
+#[derive(Debug, Clone)]
+pub struct TzKT {
+    url: String,
 }
 
-#[derive(Deserialize)]
-struct ServiceResultService {
-    type_: String,
-    endpoint: String,
+impl TzKT {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+        }
+    }
+
+    async fn storage(&self, contract: &str) -> Result<DIDManagerStorage> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            "User-Agent",
+            reqwest::header::HeaderValue::from_static(USER_AGENT),
+        );
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()?;
+        let url = Url::parse(&self.url)?;
+        let storage = client
+            .get(url.join(&format!("/v1/contracts/{}/storage", contract))?)
+            .send()
+            .await?
+            .json()
+            .await?;
+        Ok(storage)
+    }
 }
 
-// Not using TZIP-016 for now as TzKT doesn't have an endpoint to execute views and tezedge-client doesn't support it yet.
-pub async fn execute_service_view(tzkt_url: &str, did: &str, contract: &str) -> Result<Service> {
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "User-Agent",
-        reqwest::header::HeaderValue::from_static(USER_AGENT),
-    );
-    let client = reqwest::Client::builder()
-        .default_headers(headers)
-        .build()?;
-    let url = Url::parse(tzkt_url)?;
-    let service_result: ServiceResult = client
-        .get(url.join(&format!("/v1/contracts/{}/storage", contract))?)
-        .send()
-        .await?
-        .json()
-        .await?;
-    Ok(Service {
-        id: format!("{}{}", did, "#discovery"),
-        type_: OneOrMany::One(service_result.service.type_.clone()),
-        service_endpoint: Some(OneOrMany::One(ServiceEndpoint::URI(
-            service_result.service.endpoint,
-        ))),
-        property_set: None,
-    })
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Explorer for TzKT {
+    async fn retrieve_did_manager(&self, address: &str) -> Result<Option<String>> {
+        let client = reqwest::Client::builder().build()?;
+        let url = Url::parse(&self.url)?;
+        let contracts: Vec<String> = client
+            .get(url.join("/v1/contracts")?)
+            .query(&[
+                ("creator", address),
+                ("sort", "lastActivity"),
+                ("select", "address"),
+                // TODO using codeHash while all contracts have the same code and until tezedge-client provide a way to fetch TZIP-016 metadata.
+                ("codeHash", "1222545108"),
+            ])
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if !contracts.is_empty() {
+            Ok(Some(contracts[0].clone()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // Not using TZIP-016 for now as TzKT doesn't have an endpoint to execute views and tezedge-client doesn't support it yet.
+    async fn execute_service_view(&self, did: &str, contract: &str) -> Result<Service> {
+        Ok(self.storage(contract).await?.service(did))
+    }
+
+    async fn execute_auth_view(&self, contract: &str) -> Result<VerificationMethod> {
+        self.storage(contract).await?.verification_method()
+    }
 }
 
-#[derive(Deserialize)]
-struct AuthResult {
-    verification_method: String,
+/// In-memory DID manager data, for use without network access.
+///
+/// Fixtures can be loaded from JSON files of the form:
+/// ```json
+/// {
+///   "did_managers": { "tz1...": "KT1..." },
+///   "storage": {
+///     "KT1...": {
+///       "verification_method": "did:pkh:tz:tz1...#TezosMethod2021",
+///       "service": { "type_": "TezosDiscoveryService", "endpoint": "https://example.com" }
+///     }
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FixtureExplorer {
+    /// DID manager contract of each implicit account
+    #[serde(default)]
+    pub did_managers: HashMap<String, String>,
+    /// Storage of each DID manager contract
+    #[serde(default)]
+    pub storage: HashMap<String, DIDManagerStorage>,
 }
 
-pub async fn execute_auth_view(tzkt_url: &str, contract: &str) -> Result<VerificationMethod> {
-    let client = reqwest::Client::builder().build()?;
-    let url = Url::parse(tzkt_url)?;
-    let auth_result: AuthResult = client
-        .get(url.join(&format!("/v1/contracts/{}/storage", contract))?)
-        .send()
-        .await?
-        .json()
-        .await?;
-    Ok(VerificationMethod::DIDURL(DIDURL::try_from(
-        auth_result.verification_method,
-    )?))
+impl FixtureExplorer {
+    /// Load fixtures from a JSON file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let file = std::fs::read(path)?;
+        Ok(serde_json::from_slice(&file)?)
+    }
+
+    /// Add a DID manager contract, optionally originated by an implicit account.
+    pub fn insert_did_manager(
+        &mut self,
+        creator: Option<&str>,
+        contract: &str,
+        storage: DIDManagerStorage,
+    ) {
+        if let Some(creator) = creator {
+            self.did_managers
+                .insert(creator.to_string(), contract.to_string());
+        }
+        self.storage.insert(contract.to_string(), storage);
+    }
+
+    fn storage(&self, contract: &str) -> Result<&DIDManagerStorage> {
+        self.storage
+            .get(contract)
+            .ok_or_else(|| anyhow!("Unknown DID manager contract: {}", contract))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Explorer for FixtureExplorer {
+    async fn retrieve_did_manager(&self, address: &str) -> Result<Option<String>> {
+        Ok(self.did_managers.get(address).cloned())
+    }
+
+    async fn execute_service_view(&self, did: &str, contract: &str) -> Result<Service> {
+        Ok(self.storage(contract)?.service(did))
+    }
+
+    async fn execute_auth_view(&self, contract: &str) -> Result<VerificationMethod> {
+        self.storage(contract)?.verification_method()
+    }
 }
 
 #[cfg(test)]
@@ -96,21 +204,17 @@ mod tests {
     const LIVE_NETWORK: &str = "mainnet";
     const LIVE_DID_MANAGER: &str = "KT1ACXxefCq3zVG9cth4whZqS1XYK9Qsn8Gi";
 
-    #[tokio::test]
-    async fn test_retrieve_did_manager() {
-        let did_manager = retrieve_did_manager(TZKT_URL, LIVE_TZ1).await;
+    async fn check_explorer(explorer: &dyn Explorer) {
+        let did_manager = explorer.retrieve_did_manager(LIVE_TZ1).await;
         assert!(did_manager.is_ok());
         assert_eq!(did_manager.unwrap().unwrap(), LIVE_DID_MANAGER.to_string());
-    }
 
-    #[tokio::test]
-    async fn test_execute_view() {
-        let service_endpoint = execute_service_view(
-            TZKT_URL,
-            &format!("did:tz:{}:{}", LIVE_NETWORK, LIVE_TZ1),
-            LIVE_DID_MANAGER,
-        )
-        .await;
+        let service_endpoint = explorer
+            .execute_service_view(
+                &format!("did:tz:{}:{}", LIVE_NETWORK, LIVE_TZ1),
+                LIVE_DID_MANAGER,
+            )
+            .await;
         assert!(service_endpoint.is_ok());
         match service_endpoint.unwrap().service_endpoint.unwrap() {
             OneOrMany::One(ServiceEndpoint::URI(endpoint)) => {
@@ -118,7 +222,7 @@ mod tests {
             }
             _ => panic!("Should have many."),
         };
-        let verification_method = execute_auth_view(TZKT_URL, LIVE_DID_MANAGER).await;
+        let verification_method = explorer.execute_auth_view(LIVE_DID_MANAGER).await;
         assert!(verification_method.is_ok());
         match verification_method.unwrap() {
             VerificationMethod::DIDURL(did_url) => {
@@ -130,4 +234,23 @@ mod tests {
             _ => panic!("Impossible format."),
         };
     }
+
+    #[tokio::test]
+    async fn test_tzkt() {
+        check_explorer(&TzKT::new(TZKT_URL)).await;
+    }
+
+    #[tokio::test]
+    async fn test_fixture() {
+        let fixture = FixtureExplorer::from_file("tests/explorer.json").unwrap();
+        check_explorer(&fixture).await;
+        assert_eq!(
+            fixture
+                .retrieve_did_manager(LIVE_DID_MANAGER)
+                .await
+                .unwrap(),
+            None
+        );
+        assert!(fixture.execute_auth_view(LIVE_TZ1).await.is_err());
+    }
 }
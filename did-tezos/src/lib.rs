@@ -1,7 +1,8 @@
 use ssi::blakesig::hash_public_key;
 use ssi::did::{
-    Context, Contexts, DIDMethod, Document, Service, Source, VerificationMethod,
-    VerificationMethodMap, DEFAULT_CONTEXT, DIDURL,
+    Context, Contexts, DIDDocumentOperation, DIDMethod, DIDMethodError, DIDMethodTransaction,
+    DIDUpdate, Document, GenerateError, Service, Source, VerificationMethod, VerificationMethodMap,
+    DEFAULT_CONTEXT, DIDURL,
 };
 use ssi::did_resolve::{
     dereference, DIDResolver, DereferencingInputMetadata, DocumentMetadata, Metadata,
@@ -14,12 +15,14 @@ use ssi::jwk::secp256k1_parse;
 use ssi::jwk::{Base64urlUInt, OctetParams, Params, JWK};
 use ssi::jws::{decode_unverified, decode_verify};
 
-mod explorer;
+pub mod explorer;
+pub mod update;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use explorer::{Explorer, TzKT};
 use json_patch::patch;
-use serde::Deserialize;
-use serde_json::Value;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::collections::BTreeMap;
 use std::convert::TryInto;
 use std::default::Default;
@@ -31,11 +34,36 @@ use std::string::ToString;
 /// [Specification](https://github.com/spruceid/did-tezos/)
 pub struct DIDTz {
     tzkt_url: Option<String>,
+    explorer: Option<Box<dyn Explorer>>,
 }
 
 impl Default for DIDTz {
     fn default() -> Self {
-        Self { tzkt_url: None }
+        Self {
+            tzkt_url: None,
+            explorer: None,
+        }
+    }
+}
+
+impl DIDTz {
+    /// Use a custom TzKT endpoint URL for tier-2 resolution, instead of the network's public one.
+    pub fn with_tzkt_url(tzkt_url: &str) -> Self {
+        Self {
+            tzkt_url: Some(tzkt_url.to_string()),
+            explorer: None,
+        }
+    }
+
+    /// Use an [`Explorer`] for tier-2 resolution, e.g. a [`FixtureExplorer`](explorer::FixtureExplorer)
+    /// for use without network access.
+    ///
+    /// The `tzkt_url` resolution option still takes precedence.
+    pub fn with_explorer(explorer: impl Explorer + 'static) -> Self {
+        Self {
+            tzkt_url: None,
+            explorer: Some(Box::new(explorer)),
+        }
     }
 }
 
@@ -73,6 +101,28 @@ impl ToString for Prefix {
     }
 }
 
+/// Parse a did:tz DID into its network name and address.
+fn parse_did(did: &str) -> Option<(&str, &str)> {
+    match did.split(':').collect::<Vec<&str>>().as_slice() {
+        ["did", "tz", address] if address.len() == 36 => Some(("mainnet", address)),
+        ["did", "tz", network, address] if address.len() == 36 => Some((network, address)),
+        _ => None,
+    }
+}
+
+/// Get the genesis block hash of a Tezos network, as used in its
+/// [CAIP-2 chain id](https://github.com/ChainAgnostic/CAIPs/blob/master/CAIPs/caip-26.md).
+fn genesis_block_hash(network: &str) -> Option<&'static str> {
+    Some(match network {
+        "mainnet" => "NetXdQprcVkpaWU",
+        "delphinet" => "NetXm8tYqnMWky1",
+        "granadanet" => "NetXz969SFaFn8k",
+        "edonet" => "NetXSgo1ZT2DRUG",
+        "florencenet" => "NetXxkAx4woPLyu",
+        _ => return None,
+    })
+}
+
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl DIDResolver for DIDTz {
@@ -97,12 +147,9 @@ impl DIDResolver for DIDTz {
         Option<Document>,
         Option<DocumentMetadata>,
     ) {
-        let (network, address) = match did.split(':').collect::<Vec<&str>>().as_slice() {
-            ["did", "tz", address] if address.len() == 36 => ("mainnet", address.to_string()),
-            ["did", "tz", network, address] if address.len() == 36 => {
-                (*network, address.to_string())
-            }
-            _ => {
+        let (network, address) = match parse_did(did) {
+            Some((network, address)) => (network, address.to_string()),
+            None => {
                 return (
                     ResolutionMetadata::from_error(ERROR_INVALID_DID),
                     None,
@@ -110,14 +157,9 @@ impl DIDResolver for DIDTz {
                 )
             }
         };
-        // https://github.com/ChainAgnostic/CAIPs/blob/master/CAIPs/caip-26.md
-        let genesis_block_hash = match network {
-            "mainnet" => "NetXdQprcVkpaWU",
-            "delphinet" => "NetXm8tYqnMWky1",
-            "granadanet" => "NetXz969SFaFn8k",
-            "edonet" => "NetXSgo1ZT2DRUG",
-            "florencenet" => "NetXxkAx4woPLyu",
-            _ => {
+        let genesis_block_hash = match genesis_block_hash(network) {
+            Some(genesis_block_hash) => genesis_block_hash,
+            None => {
                 return (
                     ResolutionMetadata::from_error(ERROR_INVALID_DID),
                     None,
@@ -183,15 +225,11 @@ impl DIDResolver for DIDTz {
             public_key,
         );
 
-        let default_url = match &self.tzkt_url {
-            Some(u) => u.clone(),
-            None => format!("https://api.{}.tzkt.io", network),
-        };
-        let mut tzkt_url = &default_url;
+        let mut tzkt_url = self.tzkt_url.clone();
         if let Some(s) = &input_metadata.property_set {
             if let Some(url) = s.get("tzkt_url") {
                 match url {
-                    Metadata::String(u) => tzkt_url = u,
+                    Metadata::String(u) => tzkt_url = Some(u.clone()),
                     _ => {
                         return (
                             ResolutionMetadata {
@@ -205,9 +243,19 @@ impl DIDResolver for DIDTz {
                 }
             }
         };
+        let tzkt;
+        let explorer: &dyn Explorer = match (&self.explorer, tzkt_url) {
+            (Some(explorer), None) => explorer.as_ref(),
+            (_, tzkt_url) => {
+                tzkt = TzKT::new(
+                    &tzkt_url.unwrap_or_else(|| format!("https://api.{}.tzkt.io", network)),
+                );
+                &tzkt
+            }
+        };
 
         if let (Some(service), Some(vm)) =
-            match DIDTz::tier2_resolution(prefix.clone(), tzkt_url, did, &address).await {
+            match DIDTz::tier2_resolution(prefix.clone(), explorer, did, &address).await {
                 Ok(res) => res,
                 Err(e) => {
                     return (
@@ -348,24 +396,62 @@ impl DIDMethod for DIDTz {
     }
 
     // TODO need to handle different networks
-    fn generate(&self, source: &Source) -> Option<String> {
+    fn generate(&self, source: &Source) -> Result<String, GenerateError> {
         let jwk = match source {
             Source::Key(jwk) => jwk,
             Source::KeyAndPattern(jwk, pattern) => {
                 if !pattern.is_empty() {
                     // TODO: support pattern
-                    return None;
+                    return Err(GenerateError::UnsupportedPattern(pattern.to_string()));
                 }
                 jwk
             }
-            _ => return None,
-        };
-        let hash = match hash_public_key(jwk) {
-            Ok(hash) => hash,
-            _ => return None,
+            _ => return Err(GenerateError::UnsupportedSource),
         };
+        let hash = hash_public_key(jwk)?;
         let did = "did:tz:".to_string() + &hash;
-        Some(did)
+        Ok(did)
+    }
+
+    /// Create a signed [off-chain DID document update](https://did-tezos.spruceid.com/#off-chain-did-document-updates),
+    /// signed by the `update_key`, which must be the key of the DID's implicit account.
+    ///
+    /// The resulting transaction is not submitted to the chain: its `updates` property is to be
+    /// passed as the `updates` resolution option.
+    fn update(&self, update: DIDUpdate) -> Result<DIDMethodTransaction, DIDMethodError> {
+        DIDMethodError::check_options("update", &update.options, &[])?;
+        if update.new_update_key.is_some() {
+            return Err(DIDMethodError::OptionNotSupported {
+                operation: "update",
+                option: "new_update_key".to_string(),
+            });
+        }
+        let key = update
+            .update_key
+            .ok_or(DIDMethodError::MissingKey("update"))?;
+        match parse_did(&update.did).and_then(|(_, address)| Prefix::from_str(&address[0..3]).ok())
+        {
+            Some(Prefix::KT1) => {
+                return Err(DIDMethodError::NotImplemented(
+                    "Off-chain updates for KT1 DIDs",
+                ))
+            }
+            Some(_) => {}
+            None => return Err(DIDMethodError::InvalidDID(update.did)),
+        }
+        let json_patch = operation_to_json_patch(update.operation)?;
+        let jws = update::sign_update(&update.did, json_patch, &key)
+            .map_err(|e| DIDMethodError::Other(e.to_string()))?;
+        let updates = Updates::SignedIetfJsonPatch(vec![jws]);
+        let mut value = BTreeMap::new();
+        value.insert(
+            "updates".to_string(),
+            serde_json::to_value(updates).map_err(|e| DIDMethodError::Other(e.to_string()))?,
+        );
+        Ok(DIDMethodTransaction {
+            did_method: "tz".to_string(),
+            value,
+        })
     }
 
     fn to_resolver(&self) -> &dyn DIDResolver {
@@ -373,19 +459,116 @@ impl DIDMethod for DIDTz {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 struct SignedIetfJsonPatchPayload {
     ietf_json_patch: serde_json::Value,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(tag = "type", content = "value")]
 enum Updates {
     SignedIetfJsonPatch(Vec<String>),
 }
 
+/// Convert a DID document operation to a JSON Patch, for an off-chain update.
+fn operation_to_json_patch(operation: DIDDocumentOperation) -> Result<Value, DIDMethodError> {
+    // https://datatracker.ietf.org/doc/html/rfc6901#section-3
+    fn pointer(property: &str) -> String {
+        "/".to_string() + &property.replace('~', "~0").replace('/', "~1")
+    }
+    let ops: Vec<Value> = match operation {
+        DIDDocumentOperation::SetDidDocument(doc) => {
+            vec![json!({ "op": "replace", "path": "", "value": doc })]
+        }
+        DIDDocumentOperation::AddToDidDocument(properties) => properties
+            .into_iter()
+            .map(|(property, value)| json!({ "op": "add", "path": pointer(&property), "value": value }))
+            .collect(),
+        DIDDocumentOperation::RemoveFromDidDocument(properties) => properties
+            .iter()
+            .map(|property| json!({ "op": "remove", "path": pointer(property) }))
+            .collect(),
+        _ => {
+            return Err(DIDMethodError::NotImplemented(
+                "DID document operation for off-chain updates",
+            ))
+        }
+    };
+    Ok(Value::Array(ops))
+}
+
+/// Get the JWK for a Base58Check-encoded public key of a Tezos account.
+fn public_key_to_jwk(prefix: &Prefix, public_key: String) -> Result<JWK> {
+    let curve = prefix_to_curve_type(prefix.clone()).0.to_string();
+    let jwk = match prefix {
+        Prefix::TZ1 | Prefix::KT1 => {
+            let pk = bs58::decode(public_key)
+                .with_check(None)
+                .into_vec()
+                .map_err(|e| anyhow!("Couldn't decode public key: {}", e))?[4..]
+                .to_vec();
+            JWK {
+                params: Params::OKP(OctetParams {
+                    curve,
+                    public_key: Base64urlUInt(pk),
+                    private_key: None,
+                }),
+                public_key_use: None,
+                key_operations: None,
+                algorithm: None,
+                key_id: None,
+                x509_url: None,
+                x509_thumbprint_sha1: None,
+                x509_certificate_chain: None,
+                x509_thumbprint_sha256: None,
+            }
+        }
+        #[cfg(feature = "secp256k1")]
+        Prefix::TZ2 => {
+            let pk = bs58::decode(public_key)
+                .with_check(None)
+                .into_vec()
+                .map_err(|e| anyhow!("Couldn't decode public key: {}", e))?[4..]
+                .to_vec();
+            secp256k1_parse(&pk)
+                .map_err(|e| anyhow!("Couldn't create JWK from secp256k1 public key: {}", e))?
+        }
+        #[cfg(feature = "secp256r1")]
+        Prefix::TZ3 => {
+            let pk = bs58::decode(public_key)
+                .with_check(None)
+                .into_vec()
+                .map_err(|e| anyhow!("Couldn't decode public key: {}", e))?[4..]
+                .to_vec();
+            p256_parse(&pk)
+                .map_err(|e| anyhow!("Couldn't create JWK from P-256 public key: {}", e))?
+        }
+        #[allow(unreachable_patterns)]
+        p => return Err(anyhow!("{} support not enabled.", p.to_string())),
+    };
+    Ok(jwk)
+}
+
+/// Verify a [signed JSON Patch](https://did-tezos.spruceid.com/#off-chain-did-document-updates)
+/// and apply it to a DID document.
+fn apply_signed_patch(doc: &mut Document, jws: &str, jwk: &JWK) -> Result<()> {
+    let mut doc_json = serde_json::to_value(&*doc)?;
+    let (_, patch_) = decode_verify(jws, jwk)?;
+    patch(
+        &mut doc_json,
+        &serde_json::from_slice(
+            serde_json::from_slice::<SignedIetfJsonPatchPayload>(&patch_)?
+                .ietf_json_patch
+                .to_string()
+                .as_bytes(),
+        )?,
+    )?;
+    *doc = serde_json::from_value(doc_json)?;
+    Ok(())
+}
+
 impl DIDTz {
     fn tier1_derivation(
         did: &str,
@@ -445,17 +628,17 @@ impl DIDTz {
 
     async fn tier2_resolution(
         prefix: Prefix,
-        tzkt_url: &str,
+        explorer: &dyn Explorer,
         did: &str,
         address: &str,
     ) -> Result<(Option<Service>, Option<VerificationMethod>)> {
         if let Some(did_manager) = match prefix {
             Prefix::KT1 => Some(address.to_string()),
-            _ => explorer::retrieve_did_manager(tzkt_url, address).await?,
+            _ => explorer.retrieve_did_manager(address).await?,
         } {
             Ok((
-                Some(explorer::execute_service_view(tzkt_url, did, &did_manager).await?),
-                Some(explorer::execute_auth_view(tzkt_url, &did_manager).await?),
+                Some(explorer.execute_service_view(did, &did_manager).await?),
+                Some(explorer.execute_auth_view(&did_manager).await?),
             ))
         } else {
             Ok((None, None))
@@ -471,9 +654,7 @@ impl DIDTz {
         match updates {
             Updates::SignedIetfJsonPatch(patches) => {
                 for jws in patches {
-                    let mut doc_json = serde_json::to_value(&mut *doc)?;
                     let (patch_metadata, _) = decode_unverified(&jws)?;
-                    let curve = prefix_to_curve_type(prefix.clone()).0.to_string();
                     let kid = match patch_metadata.key_id {
                         Some(k) => k,
                         None => return Err(anyhow!("No kid in JWS JSON patch.")),
@@ -498,68 +679,8 @@ impl DIDTz {
                         }
                     };
                     if let Some(public_key) = get_public_key_from_doc(&kid_doc, &kid) {
-                        let jwk = match prefix {
-                            Prefix::TZ1 | Prefix::KT1 => {
-                                let pk = bs58::decode(public_key)
-                                    .with_check(None)
-                                    .into_vec()
-                                    .map_err(|e| anyhow!("Couldn't decode public key: {}", e))?
-                                    [4..]
-                                    .to_vec();
-                                JWK {
-                                    params: Params::OKP(OctetParams {
-                                        curve,
-                                        public_key: Base64urlUInt(pk),
-                                        private_key: None,
-                                    }),
-                                    public_key_use: None,
-                                    key_operations: None,
-                                    algorithm: None,
-                                    key_id: None,
-                                    x509_url: None,
-                                    x509_thumbprint_sha1: None,
-                                    x509_certificate_chain: None,
-                                    x509_thumbprint_sha256: None,
-                                }
-                            }
-                            #[cfg(feature = "secp256k1")]
-                            Prefix::TZ2 => {
-                                let pk = bs58::decode(public_key)
-                                    .with_check(None)
-                                    .into_vec()
-                                    .map_err(|e| anyhow!("Couldn't decode public key: {}", e))?
-                                    [4..]
-                                    .to_vec();
-                                secp256k1_parse(&pk).map_err(|e| {
-                                    anyhow!("Couldn't create JWK from secp256k1 public key: {}", e)
-                                })?
-                            }
-                            #[cfg(feature = "secp256r1")]
-                            Prefix::TZ3 => {
-                                let pk = bs58::decode(public_key)
-                                    .with_check(None)
-                                    .into_vec()
-                                    .map_err(|e| anyhow!("Couldn't decode public key: {}", e))?
-                                    [4..]
-                                    .to_vec();
-                                p256_parse(&pk).map_err(|e| {
-                                    anyhow!("Couldn't create JWK from P-256 public key: {}", e)
-                                })?
-                            }
-                            #[allow(unreachable_patterns)]
-                            p => return Err(anyhow!("{} support not enabled.", p.to_string())),
-                        };
-                        let (_, patch_) = decode_verify(&jws, &jwk)?;
-                        patch(
-                            &mut doc_json,
-                            &serde_json::from_slice(
-                                serde_json::from_slice::<SignedIetfJsonPatchPayload>(&patch_)?
-                                    .ietf_json_patch
-                                    .to_string()
-                                    .as_bytes(),
-                            )?,
-                        )?;
-                        *doc = serde_json::from_value(doc_json)?;
+                        let jwk = public_key_to_jwk(&prefix, public_key)?;
+                        apply_signed_patch(doc, &jws, &jwk)?;
                     } else {
                         return Err(anyhow!("Need public key for signed patches"));
                     }
@@ -598,7 +719,10 @@ mod tests {
                                     }
                                 ]}"#;
 
-    const DIDTZ: DIDTz = DIDTz { tzkt_url: None };
+    const DIDTZ: DIDTz = DIDTz {
+        tzkt_url: None,
+        explorer: None,
+    };
 
     #[test]
     fn jwk_to_did_tezos() {
@@ -741,9 +865,7 @@ mod tests {
           .mount(&mock_server)
           .await;
 
-        let didtz = DIDTz {
-            tzkt_url: Some(mock_server.uri()),
-        };
+        let didtz = DIDTz::with_tzkt_url(&mock_server.uri());
 
         let vc_str = r###"{
             "@context": [
@@ -1187,6 +1309,59 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_update_tz1() {
+        let address = "tz1VSUr8wwNhLAzempoch5d6hLRiTh8Cjcjb";
+        let pk = "edpkvGfYw3LyB1UcCahKQk4rF2tvbMUk8GFiTuMjL75uGXrpvKXhjn";
+        let sk = "edsk3QoqBuvdamxouPhin7swCvkQNgq4jP5KZPbwWNnwdZpSpJiEbq";
+        let did = format!("did:tz:{}:{}", "sandbox", address);
+        let mut doc: Document = serde_json::from_value(json!({
+          "@context": "https://www.w3.org/ns/did/v1",
+          "id": did,
+          "authentication": [{
+            "id": format!("{}#blockchainAccountId", did),
+            "type": "Ed25519PublicKeyBLAKE2BDigestSize20Base58CheckEncoded2021",
+            "controller": did,
+            "blockchainAccountId": format!("tezos:sandbox:{}", address),
+            "publicKeyBase58": pk
+          }]
+        }))
+        .unwrap();
+        let mut properties = Map::new();
+        properties.insert(
+            "service".to_string(),
+            json!([{
+                "id": "test_service_id",
+                "type": "test_service",
+                "serviceEndpoint": "test_service_endpoint"
+            }]),
+        );
+        let tx = DIDTZ
+            .update(DIDUpdate {
+                did: did.clone(),
+                update_key: Some(ssi::tzkey::jwk_from_tezos_key(sk).unwrap()),
+                new_update_key: None,
+                operation: DIDDocumentOperation::AddToDidDocument(properties),
+                options: Map::new(),
+            })
+            .unwrap();
+        assert_eq!(tx.did_method, "tz");
+        let updates: Updates = serde_json::from_value(tx.value["updates"].clone()).unwrap();
+        DIDTZ
+            .tier3_updates(Prefix::TZ1, &mut doc, updates)
+            .await
+            .unwrap();
+        assert_eq!(doc.service.unwrap()[0].id, "test_service_id");
+
+        // Deactivation is not possible off-chain
+        assert!(DIDTZ
+            .deactivate(ssi::did::DIDDeactivate {
+                did,
+                ..Default::default()
+            })
+            .is_err());
+    }
+
     #[tokio::test]
     #[cfg(feature = "secp256k1")]
     async fn test_json_patch_tz2() {
@@ -1439,6 +1614,52 @@ mod tests {
         assert_eq!(d.service, expected.service);
     }
 
+    #[tokio::test]
+    async fn test_full_resolution_fixture() {
+        let fixture = explorer::FixtureExplorer::from_file("tests/explorer.json").unwrap();
+        let didtz = DIDTz::with_explorer(fixture);
+        for did in &[
+            format!("did:tz:{}", LIVE_TZ1),
+            format!("did:tz:{}", LIVE_KT1),
+        ] {
+            let (res_meta, res_doc, _res_doc_meta) = didtz
+                .resolve(did, &ResolutionInputMetadata::default())
+                .await;
+            assert_eq!(res_meta.error, None);
+            let d = res_doc.unwrap();
+            let vms = d.verification_method.unwrap();
+            assert_eq!(
+                vms[1],
+                VerificationMethod::DIDURL(DIDURL {
+                    did: format!("did:pkh:tz:{}", LIVE_TZ1),
+                    fragment: Some("TezosMethod2021".to_string()),
+                    ..Default::default()
+                })
+            );
+            assert_eq!(
+                d.service.unwrap(),
+                vec![Service {
+                    id: format!("{}#discovery", did),
+                    type_: OneOrMany::One("TezosDiscoveryService".to_string()),
+                    service_endpoint: Some(OneOrMany::One(ServiceEndpoint::URI(
+                        "http://example.com".to_string(),
+                    ))),
+                    property_set: None,
+                }]
+            );
+        }
+
+        // Implicit accounts without a DID manager only have the derived document.
+        let did = format!("did:tz:{}", "tz1YwA1FwpgLtc1G8DKbbZ6e6PTb1dQMRn5x");
+        let (res_meta, res_doc, _res_doc_meta) = didtz
+            .resolve(&did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, None);
+        let d = res_doc.unwrap();
+        assert_eq!(d.service, None);
+        assert_eq!(d.verification_method.unwrap().len(), 1);
+    }
+
     #[tokio::test]
     #[cfg(feature = "secp256r1")]
     async fn credential_prove_verify_did_tz3() {
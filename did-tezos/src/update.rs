@@ -0,0 +1,291 @@
+//! Authoring of [off-chain DID document updates](https://did-tezos.spruceid.com/#off-chain-did-document-updates).
+//!
+//! Updates are JSON Patches signed by the key of the DID's implicit account, as JWSs with the
+//! Tezos signing algorithm of the account (`EdBlake2b`, `ESBlake2bK` or `ESBlake2b`), so that
+//! they can also be signed by Tezos wallets. The resulting `updates` value is passed to the
+//! resolver as the `updates` resolution option.
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use ssi::blakesig::hash_public_key;
+use ssi::did::{Document, DIDURL};
+use ssi::did_resolve::{Metadata, ResolutionInputMetadata};
+use ssi::jwk::{Algorithm, JWK};
+use ssi::jws::Header;
+use ssi::tzkey::{decode_tzsig, jwk_to_tezos_key, sign_tezos};
+use ssi::vc::base64_encode_json;
+
+use crate::{
+    apply_signed_patch, genesis_block_hash, get_public_key_from_doc, parse_did,
+    prefix_to_curve_type, public_key_to_jwk, DIDTz, Prefix, SignedIetfJsonPatchPayload, Updates,
+};
+
+/// Get the prefix and Tezos signing algorithm of the implicit account of a did:tz DID.
+fn account_algorithm(did: &str) -> Result<(Prefix, Algorithm)> {
+    let (_, address) = parse_did(did).ok_or_else(|| anyhow!("Invalid did:tz DID: {}", did))?;
+    let prefix = Prefix::from_str(&address[0..3])
+        .map_err(|_| anyhow!("Invalid did:tz address: {}", address))?;
+    let algorithm = match prefix {
+        Prefix::TZ1 => Algorithm::EdBlake2b,
+        Prefix::TZ2 => Algorithm::ESBlake2bK,
+        Prefix::TZ3 => Algorithm::ESBlake2b,
+        Prefix::KT1 => return Err(anyhow!("Off-chain updates for KT1 DIDs are not supported")),
+    };
+    Ok((prefix, algorithm))
+}
+
+/// An off-chain update to be signed
+#[derive(Debug, Clone)]
+pub struct PreparedUpdate {
+    algorithm: Algorithm,
+    signing_input: String,
+}
+
+impl PreparedUpdate {
+    /// Tezos signing algorithm to use for the update.
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Bytes to sign with the key of the DID's implicit account.
+    pub fn signing_input(&self) -> &[u8] {
+        self.signing_input.as_bytes()
+    }
+
+    /// Complete the update with a Base58Check-encoded Tezos signature (`edsig...`, `spsig1...` or
+    /// `p2sig...`) of the signing input, returning the signed JSON Patch JWS.
+    pub fn complete(self, tzsig: &str) -> Result<String> {
+        let (algorithm, sig) = decode_tzsig(tzsig)?;
+        if algorithm != self.algorithm {
+            return Err(anyhow!(
+                "Expected {:?} signature but found {:?}",
+                self.algorithm,
+                algorithm
+            ));
+        }
+        let sig_b64 = base64::encode_config(sig, base64::URL_SAFE_NO_PAD);
+        Ok([self.signing_input, sig_b64].join("."))
+    }
+}
+
+/// Prepare an off-chain update of a did:tz DID document, applying a JSON Patch
+/// ([RFC 6902](https://datatracker.ietf.org/doc/html/rfc6902)).
+pub fn prepare_update(did: &str, json_patch: Value) -> Result<PreparedUpdate> {
+    let (_, algorithm) = account_algorithm(did)?;
+    if !json_patch.is_array() {
+        return Err(anyhow!("JSON Patch must be an array of operations"));
+    }
+    let header = Header {
+        algorithm,
+        key_id: Some(format!("{}#blockchainAccountId", did)),
+        ..Default::default()
+    };
+    let payload = SignedIetfJsonPatchPayload {
+        ietf_json_patch: json_patch,
+    };
+    let header_b64 = base64_encode_json(&header)?;
+    let payload_b64 =
+        base64::encode_config(serde_json::to_string(&payload)?, base64::URL_SAFE_NO_PAD);
+    Ok(PreparedUpdate {
+        algorithm,
+        signing_input: header_b64 + "." + &payload_b64,
+    })
+}
+
+/// Sign an off-chain update of a did:tz DID document, returning the signed JSON Patch JWS.
+///
+/// The key must be the key of the DID's implicit account.
+pub fn sign_update(did: &str, json_patch: Value, key: &JWK) -> Result<String> {
+    let (_, address) = parse_did(did).ok_or_else(|| anyhow!("Invalid did:tz DID: {}", did))?;
+    if hash_public_key(key)? != address {
+        return Err(anyhow!("Key does not match address {}", address));
+    }
+    let prepared = prepare_update(did, json_patch)?;
+    let tzsig = sign_tezos(prepared.signing_input(), prepared.algorithm(), key)?;
+    prepared.complete(&tzsig)
+}
+
+/// A chain of off-chain updates of a did:tz DID document.
+///
+/// Each update is applied to the document resulting from the previous updates, and verified as
+/// the resolver does, against the `publicKeyBase58` of the document's `#blockchainAccountId`
+/// authentication method.
+#[derive(Debug, Clone)]
+pub struct UpdateChain {
+    prefix: Prefix,
+    public_key: Option<String>,
+    doc: Document,
+    updates: Vec<String>,
+}
+
+impl UpdateChain {
+    /// Start a chain of updates from a DID document, e.g. as resolved without updates.
+    pub fn from_document(doc: Document) -> Result<Self> {
+        let (prefix, _) = account_algorithm(&doc.id)?;
+        Ok(Self {
+            prefix,
+            public_key: None,
+            doc,
+            updates: Vec::new(),
+        })
+    }
+
+    /// Start a chain of updates from the DID document [derived](https://did-tezos.spruceid.com/#deriving-did-documents)
+    /// from a DID and the public key of its implicit account.
+    ///
+    /// The document does not include services from on-chain DID managers.
+    pub fn from_did(did: &str, public_key: &JWK) -> Result<Self> {
+        let (network, address) =
+            parse_did(did).ok_or_else(|| anyhow!("Invalid did:tz DID: {}", did))?;
+        let genesis_block_hash = genesis_block_hash(network)
+            .ok_or_else(|| anyhow!("Unknown Tezos network: {}", network))?;
+        let (prefix, _) = account_algorithm(did)?;
+        if hash_public_key(public_key)? != address {
+            return Err(anyhow!("Public key does not match address {}", address));
+        }
+        let public_key = jwk_to_tezos_key(&public_key.to_public())?;
+        let (_, proof_type, proof_type_iri) = prefix_to_curve_type(prefix.clone());
+        let vm_didurl = DIDURL {
+            did: did.to_string(),
+            fragment: Some("blockchainAccountId".to_string()),
+            ..Default::default()
+        };
+        let doc = DIDTz::tier1_derivation(
+            did,
+            &vm_didurl,
+            proof_type,
+            proof_type_iri,
+            address,
+            genesis_block_hash,
+            Some(public_key.clone()),
+        );
+        Ok(Self {
+            prefix,
+            public_key: Some(public_key),
+            doc,
+            updates: Vec::new(),
+        })
+    }
+
+    /// Sign a JSON Patch with the key of the DID's implicit account, and add it to the chain.
+    pub fn sign(&mut self, json_patch: Value, key: &JWK) -> Result<&str> {
+        let jws = sign_update(&self.doc.id, json_patch, key)?;
+        self.push(jws)
+    }
+
+    /// Add a signed JSON Patch JWS to the chain, e.g. one completed from a
+    /// [`PreparedUpdate`] signed by a wallet.
+    pub fn push(&mut self, jws: String) -> Result<&str> {
+        let kid = format!("{}#blockchainAccountId", self.doc.id);
+        let public_key = get_public_key_from_doc(&self.doc, &kid)
+            .ok_or_else(|| anyhow!("Need public key for signed patches"))?;
+        let jwk = public_key_to_jwk(&self.prefix, public_key)?;
+        apply_signed_patch(&mut self.doc, &jws, &jwk)?;
+        self.updates.push(jws);
+        Ok(self.updates.last().unwrap())
+    }
+
+    /// The DID document resulting from the updates.
+    pub fn document(&self) -> &Document {
+        &self.doc
+    }
+
+    /// The signed JSON Patch JWSs, in order.
+    pub fn updates(&self) -> &[String] {
+        &self.updates
+    }
+
+    /// Get the value of the `updates` resolution option.
+    pub fn updates_metadata(&self) -> Result<Metadata> {
+        let updates = Updates::SignedIetfJsonPatch(self.updates.clone());
+        Ok(Metadata::String(serde_json::to_string(&updates)?))
+    }
+
+    /// Get resolution input metadata for resolving the DID with the updates, including the
+    /// `public_key` option if the chain was started from the DID.
+    pub fn resolution_input_metadata(&self) -> Result<ResolutionInputMetadata> {
+        let mut property_set = HashMap::new();
+        property_set.insert("updates".to_string(), self.updates_metadata()?);
+        if let Some(ref public_key) = self.public_key {
+            property_set.insert(
+                "public_key".to_string(),
+                Metadata::String(public_key.clone()),
+            );
+        }
+        Ok(ResolutionInputMetadata {
+            property_set: Some(property_set),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DID: &str = "did:tz:tz1VSUr8wwNhLAzempoch5d6hLRiTh8Cjcjb";
+    const SK: &str = "edsk3QoqBuvdamxouPhin7swCvkQNgq4jP5KZPbwWNnwdZpSpJiEbq";
+
+    #[tokio::test]
+    async fn update_chain_tz1() {
+        let key = ssi::tzkey::jwk_from_tezos_key(SK).unwrap();
+        let mut chain = UpdateChain::from_did(DID, &key).unwrap();
+        chain
+            .sign(
+                json!([{
+                    "op": "add",
+                    "path": "/service",
+                    "value": [{
+                        "id": format!("{}#linked-domain", DID),
+                        "type": "LinkedDomains",
+                        "serviceEndpoint": "https://example.org"
+                    }]
+                }]),
+                &key,
+            )
+            .unwrap();
+        // The second update builds on the first.
+        let prepared = prepare_update(
+            DID,
+            json!([{ "op": "replace", "path": "/service/0/serviceEndpoint", "value": "https://example.com" }]),
+        )
+        .unwrap();
+        assert_eq!(prepared.algorithm(), Algorithm::EdBlake2b);
+        let tzsig = sign_tezos(prepared.signing_input(), prepared.algorithm(), &key).unwrap();
+        assert!(tzsig.starts_with("edsig"));
+        chain.push(prepared.complete(&tzsig).unwrap()).unwrap();
+        assert_eq!(chain.updates().len(), 2);
+        let service = &chain.document().service.as_ref().unwrap()[0];
+        assert_eq!(service.id, format!("{}#linked-domain", DID));
+
+        // The resolver applies the updates in the same way.
+        let mut doc = UpdateChain::from_did(DID, &key).unwrap().doc;
+        let updates: Updates = match chain.updates_metadata().unwrap() {
+            Metadata::String(updates) => serde_json::from_str(&updates).unwrap(),
+            _ => unreachable!(),
+        };
+        DIDTz::default()
+            .tier3_updates(Prefix::TZ1, &mut doc, updates)
+            .await
+            .unwrap();
+        assert_eq!(&doc, chain.document());
+        let metadata = chain.resolution_input_metadata().unwrap();
+        assert!(metadata.property_set.unwrap().contains_key("public_key"));
+
+        // Updates must be signed by the DID's key, and patches must apply.
+        let other_key = JWK::generate_ed25519().unwrap();
+        assert!(chain.sign(json!([]), &other_key).is_err());
+        assert!(chain
+            .sign(json!([{ "op": "remove", "path": "/nonexistent" }]), &key)
+            .is_err());
+        let jws = sign_update(DID, json!([]), &key).unwrap();
+        let bad_jws = jws[..jws.len() - 4].to_string() + "AAAA";
+        assert!(chain.push(bad_jws).is_err());
+        assert_eq!(chain.updates().len(), 2);
+        assert!(UpdateChain::from_did(DID, &other_key).is_err());
+    }
+}
@@ -1,10 +1,21 @@
 use async_trait::async_trait;
 
-use ssi::did::{DIDMethod, Document};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use ssi::did::{
+    Context, Contexts, DIDCreate, DIDDeactivate, DIDDocumentOperation, DIDMethod, DIDMethodError,
+    DIDMethodTransaction, DIDUpdate, Document, Service, VerificationMethod, VerificationMethodMap,
+    DEFAULT_CONTEXT, DIDURL,
+};
 use ssi::did_resolve::{
-    DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata, ERROR_INVALID_DID,
-    ERROR_NOT_FOUND, TYPE_DID_LD_JSON,
+    convert_representation, DIDResolver, DocumentMetadata, ResolutionInputMetadata,
+    ResolutionMetadata, ERROR_INVALID_DID, ERROR_NOT_FOUND, TYPE_DID_LD_JSON,
 };
+use ssi::jsonld::W3ID_JWS2020_V1_CONTEXT;
+use ssi::jwk::JWK;
 use ssi::USER_AGENT;
 
 // For testing, enable handling requests at localhost.
@@ -20,31 +31,171 @@ thread_local! {
 /// [Specification](https://w3c-ccg.github.io/did-method-web/)
 pub struct DIDWeb;
 
-fn did_web_url(did: &str) -> Result<String, ResolutionMetadata> {
-    let mut parts = did.split(':').peekable();
-    let domain_name = match (parts.next(), parts.next(), parts.next()) {
-        (Some("did"), Some("web"), Some(domain_name)) => domain_name,
-        _ => {
-            return Err(ResolutionMetadata::from_error(ERROR_INVALID_DID));
+#[derive(Error, Debug)]
+pub enum DIDWebError {
+    #[error("Not a did:web DID")]
+    NotDIDWeb,
+    #[error("Invalid domain name: {0}")]
+    InvalidDomain(String),
+    #[error("Invalid port: {0}")]
+    InvalidPort(String),
+    #[error("Invalid path: {0}")]
+    InvalidPath(String),
+    #[error("Unsupported URL: {0}")]
+    UnsupportedURL(String),
+    #[error(transparent)]
+    URL(#[from] url::ParseError),
+    #[error(transparent)]
+    JWK(#[from] ssi::error::Error),
+    #[error(transparent)]
+    JSON(#[from] serde_json::Error),
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+}
+
+/// Characters to percent-encode in path segments of a did:web DID
+const PATH_ENCODE_SET: &percent_encoding::AsciiSet = &percent_encoding::CONTROLS
+    .add(b' ')
+    .add(b'%')
+    .add(b':')
+    .add(b'/')
+    .add(b'?')
+    .add(b'#');
+
+/// Validate and normalize a domain name, converting internationalized domain names to their
+/// ASCII (punycode) form. IP addresses are not allowed.
+fn normalize_domain(domain: &str) -> Result<String, DIDWebError> {
+    let invalid = || DIDWebError::InvalidDomain(domain.to_string());
+    let ascii = match url::Host::parse(domain).map_err(|_| invalid())? {
+        url::Host::Domain(ascii) => ascii,
+        url::Host::Ipv4(_) | url::Host::Ipv6(_) => return Err(invalid()),
+    };
+    if ascii.len() > 253 {
+        return Err(invalid());
+    }
+    for label in ascii.split('.') {
+        if label.is_empty()
+            || label.len() > 63
+            || label.starts_with('-')
+            || label.ends_with('-')
+            || !label
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        {
+            return Err(invalid());
+        }
+    }
+    Ok(ascii)
+}
+
+/// Parse the host of a did:web DID, given as the first segment of the method-specific id, into a
+/// normalized domain name and optional port.
+fn parse_host(host: &str) -> Result<(String, Option<u16>), DIDWebError> {
+    let host = percent_encoding::percent_decode_str(host)
+        .decode_utf8()
+        .map_err(|_| DIDWebError::InvalidDomain(host.to_string()))?;
+    let (domain, port) = match host.rsplit_once(':') {
+        Some((domain, port)) => {
+            let port = port
+                .parse::<u16>()
+                .map_err(|_| DIDWebError::InvalidPort(port.to_string()))?;
+            (domain, Some(port))
         }
+        None => (&host[..], None),
     };
-    // TODO:
-    // - Validate domain name: alphanumeric, hyphen, dot. no IP address.
-    // - Ensure domain name matches TLS certificate common name
-    // - Support punycode?
-    // - Support query strings?
-    let path = match parts.peek() {
-        Some(_) => parts.collect::<Vec<&str>>().join("/"),
-        None => ".well-known".to_string(),
+    Ok((normalize_domain(domain)?, port))
+}
+
+/// Get the URL of the DID document of a did:web DID.
+///
+/// The domain name may be internationalized, and a port may be given percent-encoded (`%3A`).
+/// DID documents of `localhost` DIDs are retrieved over HTTP, for testing.
+pub fn did_to_url(did: &str) -> Result<String, DIDWebError> {
+    let method_specific_id = did.strip_prefix("did:web:").ok_or(DIDWebError::NotDIDWeb)?;
+    let mut parts = method_specific_id.split(':');
+    let (domain, port) = parse_host(parts.next().unwrap_or_default())?;
+    let path = parts
+        .map(|segment| {
+            if segment.is_empty() || segment == "." || segment == ".." {
+                return Err(DIDWebError::InvalidPath(segment.to_string()));
+            }
+            Ok(segment)
+        })
+        .collect::<Result<Vec<&str>, DIDWebError>>()?;
+    let path = if path.is_empty() {
+        ".well-known".to_string()
+    } else {
+        path.join("/")
     };
     // Use http for localhost, for testing purposes.
-    let proto = if domain_name == "localhost" {
+    let proto = if domain == "localhost" {
         "http"
     } else {
         "https"
     };
+    let host = match port {
+        Some(port) => format!("{}:{}", domain, port),
+        None => domain,
+    };
+    Ok(format!("{}://{}/{}/did.json", proto, host, path))
+}
+
+/// Get the did:web DID for a URL where a DID document is published.
+///
+/// The URL may be the location of the `did.json` file, or of the directory containing it. Domain
+/// names are converted to punycode.
+pub fn url_to_did(url: &str) -> Result<String, DIDWebError> {
+    let url = url::Url::parse(url)?;
+    let unsupported = || DIDWebError::UnsupportedURL(url.to_string());
+    let domain = match url.host() {
+        Some(url::Host::Domain(domain)) => normalize_domain(domain)?,
+        _ => return Err(unsupported()),
+    };
+    match (url.scheme(), &domain[..]) {
+        ("https", _) | ("http", "localhost") => {}
+        _ => return Err(unsupported()),
+    }
+    if url.query().is_some() || url.fragment().is_some() || !url.username().is_empty() {
+        return Err(unsupported());
+    }
+    let path = url.path();
+    let (path, is_document) = match path.strip_suffix("/did.json") {
+        Some(path) => (path, true),
+        // Only `did.json` files are DID documents.
+        None if path.ends_with(".json") => return Err(unsupported()),
+        None => (path, false),
+    };
+    let segments: Vec<&str> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let mut did = format!("did:web:{}", domain);
+    if let Some(port) = url.port() {
+        did += &format!("%3A{}", port);
+    }
+    if segments == [".well-known"] {
+        return Ok(did);
+    }
+    if is_document && segments.is_empty() {
+        // The DID document of a bare domain is at /.well-known/did.json.
+        return Err(unsupported());
+    }
+    for segment in segments {
+        let segment = percent_encoding::percent_decode_str(segment)
+            .decode_utf8()
+            .map_err(|_| DIDWebError::InvalidPath(segment.to_string()))?;
+        did.push(':');
+        did.extend(percent_encoding::utf8_percent_encode(
+            &segment,
+            PATH_ENCODE_SET,
+        ));
+    }
+    Ok(did)
+}
+
+fn did_web_url(did: &str) -> Result<String, ResolutionMetadata> {
     #[allow(unused_mut)]
-    let mut url = format!("{}://{}/{}/did.json", proto, domain_name, path);
+    let mut url = did_to_url(did).map_err(|_| ResolutionMetadata::from_error(ERROR_INVALID_DID))?;
     #[cfg(test)]
     PROXY.with(|proxy| {
         if let Some(ref proxy) = *proxy.borrow() {
@@ -54,6 +205,76 @@ fn did_web_url(did: &str) -> Result<String, ResolutionMetadata> {
     Ok(url)
 }
 
+/// Build a DID document for a did:web DID, with the given keys as JsonWebKey2020 verification
+/// methods (identified by their JWK thumbprints) for authentication and assertion, and the given
+/// services.
+pub fn build_document(
+    did: &str,
+    keys: &[JWK],
+    services: Vec<Service>,
+) -> Result<Document, DIDWebError> {
+    did_to_url(did)?;
+    let mut vms = Vec::new();
+    let mut vm_urls = Vec::new();
+    for jwk in keys {
+        let vm_url = DIDURL {
+            did: did.to_string(),
+            fragment: Some(jwk.thumbprint()?),
+            ..Default::default()
+        };
+        vms.push(VerificationMethod::Map(VerificationMethodMap {
+            id: vm_url.to_string(),
+            type_: "JsonWebKey2020".to_string(),
+            controller: did.to_string(),
+            public_key_jwk: Some(jwk.to_public()),
+            ..Default::default()
+        }));
+        vm_urls.push(VerificationMethod::DIDURL(vm_url));
+    }
+    let some_if_any = |list: Vec<VerificationMethod>| Some(list).filter(|l| !l.is_empty());
+    Ok(Document {
+        context: Contexts::Many(vec![
+            Context::URI(DEFAULT_CONTEXT.to_string()),
+            Context::URI(W3ID_JWS2020_V1_CONTEXT.to_string()),
+        ]),
+        id: did.to_string(),
+        verification_method: some_if_any(vms),
+        authentication: some_if_any(vm_urls.clone()),
+        assertion_method: some_if_any(vm_urls),
+        service: Some(services).filter(|s| !s.is_empty()),
+        ..Default::default()
+    })
+}
+
+/// Get the path of the `did.json` file of a did:web DID, relative to the web root of its domain.
+///
+/// Path segments are percent-decoded.
+pub fn did_json_path(did: &str) -> Result<PathBuf, DIDWebError> {
+    let url = url::Url::parse(&did_to_url(did)?)?;
+    let mut path = PathBuf::new();
+    for segment in url.path_segments().into_iter().flatten() {
+        let segment = percent_encoding::percent_decode_str(segment)
+            .decode_utf8()
+            .map_err(|_| DIDWebError::InvalidPath(segment.to_string()))?;
+        if segment.contains(std::path::is_separator) {
+            return Err(DIDWebError::InvalidPath(segment.to_string()));
+        }
+        path.push(&*segment);
+    }
+    Ok(path)
+}
+
+/// Write a DID document to its `did.json` path under a web root directory, creating any parent
+/// directories. Returns the path of the written file.
+pub fn write_document(web_root: &Path, doc: &Document) -> Result<PathBuf, DIDWebError> {
+    let path = web_root.join(did_json_path(&doc.id)?);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, serde_json::to_vec_pretty(doc)?)?;
+    Ok(path)
+}
+
 /// <https://w3c-ccg.github.io/did-method-web/#read-resolve>
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -67,17 +288,24 @@ impl DIDResolver for DIDWeb {
         Option<Document>,
         Option<DocumentMetadata>,
     ) {
+        // The DID document is parsed from the published representation, so `accept` does not
+        // apply here.
+        let input_metadata = ResolutionInputMetadata {
+            accept: None,
+            ..input_metadata.clone()
+        };
         let (mut res_meta, doc_data, doc_meta_opt) =
-            self.resolve_representation(did, input_metadata).await;
+            self.resolve_representation(did, &input_metadata).await;
         let doc_opt = if doc_data.is_empty() {
             None
         } else {
-            match serde_json::from_slice(&doc_data) {
-                Ok(doc) => doc,
+            let content_type = res_meta.content_type.as_deref().unwrap_or(TYPE_DID_LD_JSON);
+            match Document::from_representation(&doc_data, content_type) {
+                Ok(doc) => Some(doc),
                 Err(err) => {
                     return (
                         ResolutionMetadata::from_error(
-                            &("JSON Error: ".to_string() + &err.to_string()),
+                            &("Error consuming DID document: ".to_string() + &err.to_string()),
                         ),
                         None,
                         None,
@@ -165,24 +393,106 @@ impl DIDResolver for DIDWeb {
                 )
             }
         };
+        // Convert the published DID document if another representation is requested.
+        let (res_meta, doc_representation) =
+            convert_representation(doc_representation, TYPE_DID_LD_JSON, input_metadata);
+        if res_meta.error.is_some() {
+            return (res_meta, Vec::new(), None);
+        }
         // TODO: set document created/updated metadata from HTTP headers?
         (
-            ResolutionMetadata {
-                error: None,
-                content_type: Some(TYPE_DID_LD_JSON.to_string()),
-                property_set: None,
-            },
+            res_meta,
             doc_representation,
             Some(DocumentMetadata::default()),
         )
     }
 }
 
+/// Build a transaction for publishing (or, if `doc` is `None`, removing) the DID document of a
+/// did:web DID. The transaction contains the `url` where the DID document is to be published, and
+/// the `didDocument`.
+fn publish_transaction(
+    did: &str,
+    doc: Option<Document>,
+) -> Result<DIDMethodTransaction, DIDMethodError> {
+    let url = did_to_url(did).map_err(|_| DIDMethodError::InvalidDID(did.to_string()))?;
+    let doc_value = match doc {
+        Some(doc) => serde_json::to_value(doc).map_err(|e| DIDMethodError::Other(e.to_string()))?,
+        None => Value::Null,
+    };
+    let mut value = BTreeMap::new();
+    value.insert("url".to_string(), Value::String(url));
+    value.insert("didDocument".to_string(), doc_value);
+    Ok(DIDMethodTransaction {
+        did_method: "web".to_string(),
+        value,
+    })
+}
+
+/// did:web DID documents are published by the controller of the web domain, so operations produce
+/// transactions containing the DID document file to publish at the given URL, rather than being
+/// submitted by ssi.
 impl DIDMethod for DIDWeb {
     fn name(&self) -> &'static str {
         "web"
     }
 
+    /// Create a DID document for the DID given in the `did` option, with the `verification_key`
+    /// as a JsonWebKey2020 verification method.
+    fn create(&self, create: DIDCreate) -> Result<DIDMethodTransaction, DIDMethodError> {
+        DIDMethodError::check_options("create", &create.options, &["did"])?;
+        for (option, key) in &[
+            ("update_key", &create.update_key),
+            ("recovery_key", &create.recovery_key),
+        ] {
+            if key.is_some() {
+                return Err(DIDMethodError::OptionNotSupported {
+                    operation: "create",
+                    option: option.to_string(),
+                });
+            }
+        }
+        let did = create
+            .options
+            .get("did")
+            .and_then(Value::as_str)
+            .ok_or(DIDMethodError::MissingOption("did"))?;
+        let jwk = create
+            .verification_key
+            .ok_or(DIDMethodError::MissingKey("verification"))?;
+        let doc = build_document(did, &[jwk], Vec::new()).map_err(|e| match e {
+            DIDWebError::JWK(e) => DIDMethodError::from(e),
+            _ => DIDMethodError::InvalidDID(did.to_string()),
+        })?;
+        publish_transaction(did, Some(doc))
+    }
+
+    /// Replace the DID document. Only [`DIDDocumentOperation::SetDidDocument`] is supported.
+    fn update(&self, update: DIDUpdate) -> Result<DIDMethodTransaction, DIDMethodError> {
+        DIDMethodError::check_options("update", &update.options, &[])?;
+        let doc = match update.operation {
+            DIDDocumentOperation::SetDidDocument(doc) => doc,
+            _ => {
+                return Err(DIDMethodError::NotImplemented(
+                    "DID document operation other than setDidDocument",
+                ))
+            }
+        };
+        if doc.id != update.did {
+            return Err(DIDMethodError::InvalidDID(doc.id));
+        }
+        publish_transaction(&update.did, Some(doc))
+    }
+
+    /// Deactivate the DID, by removing its DID document.
+    fn deactivate(
+        &self,
+        deactivate: DIDDeactivate,
+    ) -> Result<DIDMethodTransaction, DIDMethodError> {
+        DIDMethodError::check_options("deactivate", &deactivate.options, &[])?;
+        publish_transaction(&deactivate.did, None)
+    }
+
     fn to_resolver(&self) -> &dyn DIDResolver {
         self
     }
@@ -211,6 +521,171 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_did_web_host() {
+        // https://w3c-ccg.github.io/did-method-web/#example-creating-the-did-with-optional-path-and-port
+        assert_eq!(
+            did_to_url("did:web:example.com%3A3000:user:alice").unwrap(),
+            "https://example.com:3000/user/alice/did.json"
+        );
+        // Internationalized domain names are converted to punycode.
+        assert_eq!(
+            did_to_url("did:web:b%C3%BCcher.example").unwrap(),
+            "https://xn--bcher-kva.example/.well-known/did.json"
+        );
+        for did in &[
+            "did:web:",
+            "did:web:127.0.0.1",
+            "did:web:%5B%3A%3A1%5D",
+            "did:web:-example.com",
+            "did:web:exa_mple.com",
+            "did:web:example.com%3Ahttp",
+            "did:web:example.com::alice",
+            "did:web:example.com:..",
+            "did:key:example.com",
+        ] {
+            assert!(did_to_url(did).is_err(), "{}", did);
+        }
+    }
+
+    #[test]
+    fn url_to_did_web() {
+        for (url, did) in &[
+            (
+                "https://w3c-ccg.github.io/.well-known/did.json",
+                "did:web:w3c-ccg.github.io",
+            ),
+            ("https://w3c-ccg.github.io/", "did:web:w3c-ccg.github.io"),
+            (
+                "https://example.com:3000/user/alice/did.json",
+                "did:web:example.com%3A3000:user:alice",
+            ),
+            (
+                "https://bücher.example/u/bob/",
+                "did:web:xn--bcher-kva.example:u:bob",
+            ),
+            ("http://localhost/.well-known/did.json", "did:web:localhost"),
+        ] {
+            assert_eq!(url_to_did(url).unwrap(), *did);
+        }
+        for url in &[
+            "http://example.com/",
+            "https://127.0.0.1/",
+            "https://example.com/did.json?versionId=1",
+            "https://example.com/did.json",
+            "https://example.com/user/alicedid.json",
+            "https://example.com/user/alice.json",
+        ] {
+            assert!(url_to_did(url).is_err(), "{}", url);
+        }
+        // Round trip
+        let did = "did:web:example.com%3A8443:users:alice";
+        assert_eq!(url_to_did(&did_to_url(did).unwrap()).unwrap(), did);
+    }
+
+    #[test]
+    fn build_and_write_document() {
+        use ssi::did::ServiceEndpoint;
+        use ssi::one_or_many::OneOrMany;
+        let key: JWK =
+            serde_json::from_str(include_str!("../../tests/ed25519-2020-10-18.json")).unwrap();
+        let did = "did:web:example.com:u:bob";
+        let service = Service {
+            id: format!("{}#linked-domain", did),
+            type_: OneOrMany::One("LinkedDomains".to_string()),
+            service_endpoint: Some(OneOrMany::One(ServiceEndpoint::URI(
+                "https://bob.example.com".to_string(),
+            ))),
+            property_set: None,
+        };
+        let doc = build_document(did, std::slice::from_ref(&key), vec![service]).unwrap();
+        let vm_id = format!("{}#{}", did, key.thumbprint().unwrap());
+        assert_eq!(doc.authentication.as_ref().unwrap()[0].get_id(did), vm_id);
+        assert_eq!(doc.service.as_ref().unwrap().len(), 1);
+        assert!(build_document("did:web:127.0.0.1", &[key], Vec::new()).is_err());
+
+        assert_eq!(
+            did_json_path("did:web:example.com").unwrap(),
+            Path::new(".well-known/did.json")
+        );
+        assert_eq!(did_json_path(did).unwrap(), Path::new("u/bob/did.json"));
+
+        // Remove the temporary web root even if an assertion fails.
+        struct TempDir(std::path::PathBuf);
+        impl Drop for TempDir {
+            fn drop(&mut self) {
+                let _ = std::fs::remove_dir_all(&self.0);
+            }
+        }
+        let web_root =
+            TempDir(std::env::temp_dir().join(format!("did-web-test-{}", std::process::id())));
+        let path = write_document(&web_root.0, &doc).unwrap();
+        assert_eq!(path, web_root.0.join("u/bob/did.json"));
+        let written: Document = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(
+            serde_json::to_value(written).unwrap(),
+            serde_json::to_value(doc).unwrap()
+        );
+    }
+
+    #[test]
+    fn create_update_deactivate() {
+        use ssi::jwk::JWK;
+        let key: JWK =
+            serde_json::from_str(include_str!("../../tests/ed25519-2020-10-18.json")).unwrap();
+        let mut options = BTreeMap::new();
+        options.insert(
+            "did".to_string(),
+            Value::String("did:web:example.com:u:bob".to_string()),
+        );
+        let tx = DIDWeb
+            .create(DIDCreate {
+                verification_key: Some(key.clone()),
+                options,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(tx.did_method, "web");
+        assert_eq!(tx.value["url"], "https://example.com/u/bob/did.json");
+        let doc: Document = serde_json::from_value(tx.value["didDocument"].clone()).unwrap();
+        assert_eq!(doc.id, "did:web:example.com:u:bob");
+        let vm_id = format!("did:web:example.com:u:bob#{}", key.thumbprint().unwrap());
+        match doc.verification_method.as_ref().unwrap().as_slice() {
+            [VerificationMethod::Map(vmm)] => {
+                assert_eq!(vmm.id, vm_id);
+                assert_eq!(vmm.public_key_jwk, Some(key.to_public()));
+            }
+            vms => panic!("Unexpected verification methods: {:?}", vms),
+        }
+
+        // Missing DID
+        assert!(DIDWeb
+            .create(DIDCreate {
+                verification_key: Some(key),
+                ..Default::default()
+            })
+            .is_err());
+
+        let tx = DIDWeb
+            .update(DIDUpdate {
+                did: "did:web:example.com:u:bob".to_string(),
+                update_key: None,
+                new_update_key: None,
+                operation: DIDDocumentOperation::SetDidDocument(doc.clone()),
+                options: BTreeMap::new(),
+            })
+            .unwrap();
+        assert_eq!(tx.value["didDocument"], serde_json::to_value(&doc).unwrap());
+
+        let tx = DIDWeb
+            .deactivate(DIDDeactivate {
+                did: "did:web:example.com:u:bob".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(tx.value["didDocument"], Value::Null);
+    }
+
     const DID_URL: &str = "http://localhost/.well-known/did.json";
     const DID_JSON: &str = r#"{
       "@context": "https://www.w3.org/ns/did/v1",
@@ -280,7 +755,23 @@ mod tests {
             .await;
         assert_eq!(res_meta.error, None);
         let doc_expected: Document = serde_json::from_str(DID_JSON).unwrap();
+        assert_eq!(doc_opt, Some(doc_expected.clone()));
+        // accept only applies to resolving a representation.
+        let input_metadata = ResolutionInputMetadata {
+            accept: Some("text/plain".to_string()),
+            ..Default::default()
+        };
+        let (res_meta, doc_opt, _doc_meta) =
+            DIDWeb.resolve("did:web:localhost", &input_metadata).await;
+        assert_eq!(res_meta.error, None);
         assert_eq!(doc_opt, Some(doc_expected));
+        let (res_meta, _, _) = DIDWeb
+            .resolve_representation("did:web:localhost", &input_metadata)
+            .await;
+        assert_eq!(
+            res_meta.error.as_deref(),
+            Some(ssi::did_resolve::ERROR_REPRESENTATION_NOT_SUPPORTED)
+        );
         PROXY.with(|proxy| {
             proxy.replace(None);
         });
@@ -3,13 +3,17 @@ use core::str::FromStr;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+use pgp::PublicKeyMaterial;
 use sshkeys::PublicKeyKind;
 use ssi::did::{DIDMethod, Document, VerificationMethod, VerificationMethodMap, DIDURL};
 use ssi::did_resolve::{
     DIDResolver, DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata, ERROR_INVALID_DID,
 };
+use ssi::jwk::{Base64urlUInt, ECParams, OctetParams, Params, RSAParams, JWK};
 use ssi::ssh::ssh_pkk_to_jwk;
 
+mod pgp;
+
 // For testing, enable handling requests at localhost.
 #[cfg(test)]
 use std::cell::RefCell;
@@ -38,12 +42,118 @@ impl FromStr for DIDWebKeyType {
     }
 }
 
-fn parse_pubkeys_gpg(
-    _did: &str,
-    _bytes: Vec<u8>,
-) -> Result<(Vec<VerificationMethodMap>, Vec<DIDURL>), String> {
-    // TODO
-    Err(String::from("GPG Key Type Not Implemented"))
+/// Verification methods, and the verification relationships they are used in
+#[derive(Default)]
+struct VerificationMethods {
+    vm_maps: Vec<VerificationMethodMap>,
+    authentication: Vec<DIDURL>,
+    assertion_method: Vec<DIDURL>,
+    key_agreement: Vec<DIDURL>,
+}
+
+/// OpenPGP curve OIDs
+const ED25519_OID: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xda, 0x47, 0x0f, 0x01];
+const CURVE25519_OID: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0x97, 0x55, 0x01, 0x05, 0x01];
+const P256_OID: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+const SECP256K1_OID: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x0a];
+
+/// Convert an uncompressed SEC1 elliptic curve point to a JWK.
+fn ec_point_to_jwk(curve: &str, point: &[u8]) -> Result<JWK, String> {
+    if point.len() != 65 || point[0] != 0x04 {
+        return Err(format!("Invalid {} public key", curve));
+    }
+    Ok(JWK::from(Params::EC(ECParams {
+        curve: Some(curve.to_string()),
+        x_coordinate: Some(Base64urlUInt(point[1..33].to_vec())),
+        y_coordinate: Some(Base64urlUInt(point[33..65].to_vec())),
+        ecc_private_key: None,
+    })))
+}
+
+/// Convert an OpenPGP 0x40-prefixed native curve point to an OKP JWK.
+fn native_point_to_jwk(curve: &str, point: &[u8]) -> Result<JWK, String> {
+    match point {
+        [0x40, public_key @ ..] if public_key.len() == 32 => {
+            Ok(JWK::from(Params::OKP(OctetParams {
+                curve: curve.to_string(),
+                public_key: Base64urlUInt(public_key.to_vec()),
+                private_key: None,
+            })))
+        }
+        _ => Err(format!("Invalid {} public key", curve)),
+    }
+}
+
+/// Get the verification method type and JWK for an OpenPGP public key, or `None` if the key
+/// type is not supported.
+fn gpg_pk_to_jwk(pk: &PublicKeyMaterial) -> Result<Option<(&'static str, JWK)>, String> {
+    Ok(Some(match pk {
+        PublicKeyMaterial::EdDsa { curve_oid, point } if curve_oid == ED25519_OID => (
+            "Ed25519VerificationKey2018",
+            native_point_to_jwk("Ed25519", point)?,
+        ),
+        PublicKeyMaterial::Ecdsa { curve_oid, point } if curve_oid == P256_OID => (
+            "EcdsaSecp256r1VerificationKey2019",
+            ec_point_to_jwk("P-256", point)?,
+        ),
+        PublicKeyMaterial::Ecdsa { curve_oid, point } if curve_oid == SECP256K1_OID => (
+            "EcdsaSecp256k1VerificationKey2019",
+            ec_point_to_jwk("secp256k1", point)?,
+        ),
+        PublicKeyMaterial::Ecdh { curve_oid, point } if curve_oid == CURVE25519_OID => (
+            "X25519KeyAgreementKey2019",
+            native_point_to_jwk("X25519", point)?,
+        ),
+        PublicKeyMaterial::Rsa { e, n } => (
+            "RsaVerificationKey2018",
+            JWK::from(Params::RSA(RSAParams::new_public(e, n))),
+        ),
+        _ => return Ok(None),
+    }))
+}
+
+/// Parse ASCII-armored OpenPGP certificates. Each valid primary key and subkey of a supported
+/// type becomes a verification method, identified by its fingerprint. Keys flagged for signing
+/// are used for authentication and assertion, keys flagged for authentication are used for
+/// authentication, and keys flagged for encryption are used for key agreement.
+fn parse_pubkeys_gpg(did: &str, bytes: Vec<u8>) -> Result<VerificationMethods, String> {
+    let text =
+        String::from_utf8(bytes).map_err(|e| format!("Unable to parse OpenPGP keys: {}", e))?;
+    let keys = pgp::parse_public_keys(&text, chrono::Utc::now().timestamp())
+        .map_err(|e| format!("Unable to parse OpenPGP keys: {}", e))?;
+    let mut vms = VerificationMethods::default();
+    for key in keys {
+        let (type_, jwk) = match gpg_pk_to_jwk(&key.material)? {
+            Some(key) => key,
+            None => continue,
+        };
+        let vm_url = DIDURL {
+            did: did.to_string(),
+            fragment: Some(key.fingerprint),
+            ..Default::default()
+        };
+        vms.vm_maps.push(VerificationMethodMap {
+            id: vm_url.to_string(),
+            type_: type_.to_string(),
+            public_key_jwk: Some(jwk),
+            controller: did.to_string(),
+            ..Default::default()
+        });
+        let flags = match key.flags {
+            Some(flags) => flags,
+            None => continue,
+        };
+        if flags & pgp::FLAG_SIGN != 0 {
+            vms.authentication.push(vm_url.clone());
+            vms.assertion_method.push(vm_url.clone());
+        } else if flags & pgp::FLAG_AUTHENTICATE != 0 {
+            vms.authentication.push(vm_url.clone());
+        }
+        if flags & (pgp::FLAG_ENCRYPT_COMMUNICATIONS | pgp::FLAG_ENCRYPT_STORAGE) != 0 {
+            vms.key_agreement.push(vm_url);
+        }
+    }
+    Ok(vms)
 }
 
 fn pk_to_vm_ed25519(
@@ -178,10 +288,18 @@ fn parse_pubkeys(
     did: &str,
     type_: DIDWebKeyType,
     bytes: Vec<u8>,
-) -> Result<(Vec<VerificationMethodMap>, Vec<DIDURL>), String> {
+) -> Result<VerificationMethods, String> {
     match type_ {
         DIDWebKeyType::Gpg => parse_pubkeys_gpg(did, bytes),
-        DIDWebKeyType::Ssh => parse_pubkeys_ssh(did, bytes),
+        DIDWebKeyType::Ssh => {
+            let (vm_maps, vm_urls) = parse_pubkeys_ssh(did, bytes)?;
+            Ok(VerificationMethods {
+                vm_maps,
+                authentication: vm_urls.clone(),
+                assertion_method: vm_urls,
+                key_agreement: Vec::new(),
+            })
+        }
     }
 }
 
@@ -282,28 +400,38 @@ impl DIDResolver for DIDWebKey {
                 )
             }
         };
-        let (vm_maps, vm_urls): (Vec<VerificationMethod>, Vec<VerificationMethod>) =
-            match parse_pubkeys(did, type_, bytes) {
-                Ok((maps, urls)) => (
-                    maps.into_iter().map(VerificationMethod::Map).collect(),
-                    urls.into_iter().map(VerificationMethod::DIDURL).collect(),
-                ),
-                Err(err) => {
-                    return (
-                        ResolutionMetadata::from_error(&format!("Error parsing keys: {}", err)),
-                        None,
-                        None,
-                    )
-                }
-            };
+        let vms = match parse_pubkeys(did, type_, bytes) {
+            Ok(vms) => vms,
+            Err(err) => {
+                return (
+                    ResolutionMetadata::from_error(&format!("Error parsing keys: {}", err)),
+                    None,
+                    None,
+                )
+            }
+        };
+        let vm_refs = |urls: Vec<DIDURL>| {
+            Some(
+                urls.into_iter()
+                    .map(VerificationMethod::DIDURL)
+                    .collect::<Vec<_>>(),
+            )
+            .filter(|refs| !refs.is_empty())
+        };
         let doc = Document {
             context: ssi::did::Contexts::One(ssi::did::Context::URI(
                 ssi::did::DEFAULT_CONTEXT.to_string(),
             )),
             id: did.to_string(),
-            verification_method: Some(vm_maps),
-            authentication: Some(vm_urls.clone()),
-            assertion_method: Some(vm_urls),
+            verification_method: Some(
+                vms.vm_maps
+                    .into_iter()
+                    .map(VerificationMethod::Map)
+                    .collect(),
+            ),
+            authentication: vm_refs(vms.authentication),
+            assertion_method: vm_refs(vms.assertion_method),
+            key_agreement: vm_refs(vms.key_agreement),
             ..Default::default()
         };
         // TODO: set document created/updated metadata from HTTP headers?
@@ -351,6 +479,8 @@ mod tests {
     // TODO: use JWK fingerprint
     const DID_URL: &str = "https://localhost/user.keys";
     const PUBKEYS: &str = include_str!("../tests/ssh_keys");
+    const GPG_DID_URL: &str = "https://localhost/user.gpg";
+    const GPG_PUBKEYS: &str = include_str!("../tests/gpg_keys");
     // localhost web server for serving did:web DID documents.
     // TODO: pass arguments here instead of using const
     fn web_server() -> Result<(String, impl FnOnce() -> Result<(), ()>), hyper::Error> {
@@ -363,8 +493,13 @@ mod tests {
                 let uri = req.uri();
                 // Skip leading slash
                 let proxied_url: String = uri.path().chars().skip(1).collect();
-                if proxied_url == DID_URL {
-                    let body = Body::from(PUBKEYS);
+                let pubkeys = match &proxied_url[..] {
+                    DID_URL => Some(PUBKEYS),
+                    GPG_DID_URL => Some(GPG_PUBKEYS),
+                    _ => None,
+                };
+                if let Some(pubkeys) = pubkeys {
+                    let body = Body::from(pubkeys);
                     let mut response = Response::new(body);
                     response
                         .headers_mut()
@@ -460,4 +595,135 @@ mod tests {
         });
         shutdown().ok();
     }
+
+    #[tokio::test]
+    async fn from_did_webkey_gpg() {
+        let (url, shutdown) = web_server().unwrap();
+        PROXY.with(|proxy| {
+            proxy.replace(Some(url));
+        });
+        let did = "did:webkey:gpg:localhost:user.gpg";
+        let (res_meta, doc_opt, _doc_meta) = DIDWebKey
+            .resolve(did, &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, None);
+        let doc = doc_opt.unwrap();
+        let ids = |vms: Option<Vec<VerificationMethod>>| {
+            let mut ids: Vec<String> = vms
+                .unwrap_or_default()
+                .iter()
+                .map(|vm| vm.get_id(did))
+                .collect();
+            ids.sort();
+            ids
+        };
+        let vm_id = |fingerprint: &str| format!("{}#{}", did, fingerprint);
+        // Ed25519 primary key, flagged for signing and certification
+        let ed25519 = vm_id("DE0EBFAC91E1624B34AC74BF79EE321BCE7A6278");
+        // Curve25519 subkey, flagged for encryption
+        let x25519 = vm_id("2065E2BBAE596387A0B7732390DB12FEC2F08209");
+        // Ed25519 subkey, flagged for authentication
+        let ed25519_auth = vm_id("CF67A6B294F886D665FABF3B1789F9B9A2312C28");
+        // RSA and P-256 primary keys, flagged for signing and certification
+        let rsa = vm_id("F0AF24FA80F797C7D22FB07ED75D1DBDBDD07E46");
+        let p256 = vm_id("EAAB19716A42697D0CCFF9E3A33B33FBC6456463");
+        // secp256k1 primary key, flagged for signing and certification. Its Ed25519 signing
+        // subkey has expired and its Curve25519 encryption subkey is revoked.
+        let secp256k1 = vm_id("F7EB3B98534A4778C17931E21E9689BF7BD5039F");
+
+        let mut all = vec![
+            ed25519.clone(),
+            x25519.clone(),
+            ed25519_auth.clone(),
+            rsa.clone(),
+            p256.clone(),
+            secp256k1.clone(),
+        ];
+        all.sort();
+        assert_eq!(ids(doc.verification_method.clone()), all);
+        let mut signing = vec![
+            ed25519.clone(),
+            rsa.clone(),
+            p256.clone(),
+            secp256k1.clone(),
+        ];
+        signing.sort();
+        assert_eq!(ids(doc.assertion_method.clone()), signing);
+        signing.push(ed25519_auth);
+        signing.sort();
+        assert_eq!(ids(doc.authentication.clone()), signing);
+        assert_eq!(ids(doc.key_agreement.clone()), vec![x25519.clone()]);
+
+        let vm_value = |id: &str| {
+            let vm = doc
+                .verification_method
+                .iter()
+                .flatten()
+                .find(|vm| vm.get_id(did) == id)
+                .unwrap();
+            serde_json::to_value(vm).unwrap()
+        };
+        assert_eq!(
+            vm_value(&ed25519),
+            json!({
+              "id": ed25519,
+              "type": "Ed25519VerificationKey2018",
+              "controller": did,
+              "publicKeyJwk": {
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": "_mnUcf5hRUMY5BkVX4s6Mvz0tCV4ifyU-idrs8CN9Ic"
+              }
+            })
+        );
+        assert_eq!(
+            vm_value(&x25519),
+            json!({
+              "id": x25519,
+              "type": "X25519KeyAgreementKey2019",
+              "controller": did,
+              "publicKeyJwk": {
+                "kty": "OKP",
+                "crv": "X25519",
+                "x": "a9ONXUD4KQUsURj4vyvDhg7MC9augWjChWAGfqcWZkY"
+              }
+            })
+        );
+        assert_eq!(
+            vm_value(&p256),
+            json!({
+              "id": p256,
+              "type": "EcdsaSecp256r1VerificationKey2019",
+              "controller": did,
+              "publicKeyJwk": {
+                "kty": "EC",
+                "crv": "P-256",
+                "x": "JkxrmA8z7TQxZU6g-DaDhDGQaoMK1TMgh8qVABLn-tk",
+                "y": "IVlngpFOXR60HG0SFThl8BWRnSW25YMPH6vPDo3VnX4"
+              }
+            })
+        );
+        assert_eq!(
+            vm_value(&secp256k1),
+            json!({
+              "id": secp256k1,
+              "type": "EcdsaSecp256k1VerificationKey2019",
+              "controller": did,
+              "publicKeyJwk": {
+                "kty": "EC",
+                "crv": "secp256k1",
+                "x": "K3N1LtCD3Tnja2lkTOG4o25ZlrFkg8WczMvuwlsYhSI",
+                "y": "MuhLotg_t2YcyyiRcPPLM_ELXxptRrIa2sORh3y0Glw"
+              }
+            })
+        );
+        assert_eq!(
+            vm_value(&rsa)["publicKeyJwk"]["n"],
+            "0ddCkXYmJyOvPcFCPCRd1S-5OBDkNN92-nNeo_QMBEgKnqgmkSCUkAh1e7TPGR6Nk7qYNJyS-9z4z1wAzwGLPPBLpFp04AvHtHHEwa_fLCgIGb3MxxxV6jpQ8QzFJUkx7Xl0yx73X9etnT_QS6B2HYdBML_C7cU_TzaEIqkqmF7wZI5kN4QZoYhtrNEYLCDjNSl_18-Ef9-a_YOQBHbHZkiUD7_Msq024971twdf2t9nJYm94CaCTG5MilrXpA2eJwS-ajO6lIrmPe60mKTKO7KGXUKujj4dnun2xBSapr4S2ae5nzFyVbIGYS__eCwJeF-qvl7agrsFgQOc7Ned2w"
+        );
+        PROXY.with(|proxy| {
+            proxy.replace(None);
+        });
+        shutdown().ok();
+    }
 }
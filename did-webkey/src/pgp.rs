@@ -0,0 +1,509 @@
+//! Parsing of ASCII-armored OpenPGP public keys ([RFC 4880][]), for `did:webkey:gpg`.
+//!
+//! Only version 4 keys are read. Self-signatures are used for key flags, expiration and
+//! revocation, but are not cryptographically verified: as with SSH key lists, the keys are
+//! trusted because they are served from the web origin of the DID.
+//!
+//! [RFC 4880]: https://www.rfc-editor.org/rfc/rfc4880
+
+use sha1::{Digest, Sha1};
+use std::convert::TryFrom;
+
+const ARMOR_BEGIN: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----";
+const ARMOR_END: &str = "-----END PGP PUBLIC KEY BLOCK-----";
+
+const TAG_SIGNATURE: u8 = 2;
+const TAG_PUBLIC_KEY: u8 = 6;
+const TAG_PUBLIC_SUBKEY: u8 = 14;
+
+const SIG_SUBKEY_BINDING: u8 = 0x18;
+const SIG_DIRECT_KEY: u8 = 0x1f;
+const SIG_KEY_REVOCATION: u8 = 0x20;
+const SIG_SUBKEY_REVOCATION: u8 = 0x28;
+
+const SUBPACKET_CREATION_TIME: u8 = 2;
+const SUBPACKET_KEY_EXPIRATION_TIME: u8 = 9;
+const SUBPACKET_ISSUER: u8 = 16;
+const SUBPACKET_KEY_FLAGS: u8 = 27;
+const SUBPACKET_ISSUER_FINGERPRINT: u8 = 33;
+
+/// Key flag: this key may be used to sign data
+pub const FLAG_SIGN: u8 = 0x02;
+/// Key flag: this key may be used to encrypt communications
+pub const FLAG_ENCRYPT_COMMUNICATIONS: u8 = 0x04;
+/// Key flag: this key may be used to encrypt storage
+pub const FLAG_ENCRYPT_STORAGE: u8 = 0x08;
+/// Key flag: this key may be used for authentication
+pub const FLAG_AUTHENTICATE: u8 = 0x20;
+
+/// Public key algorithm and key material of an OpenPGP key
+#[derive(Debug, Clone, PartialEq)]
+pub enum PublicKeyMaterial {
+    Rsa {
+        n: Vec<u8>,
+        e: Vec<u8>,
+    },
+    Ecdsa {
+        curve_oid: Vec<u8>,
+        point: Vec<u8>,
+    },
+    EdDsa {
+        curve_oid: Vec<u8>,
+        point: Vec<u8>,
+    },
+    Ecdh {
+        curve_oid: Vec<u8>,
+        point: Vec<u8>,
+    },
+    /// Any other public key algorithm
+    Other,
+}
+
+/// A valid (bound, unexpired and unrevoked) primary key or subkey
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicKey {
+    /// Uppercase hex-encoded version 4 fingerprint
+    pub fingerprint: String,
+    pub material: PublicKeyMaterial,
+    /// Key flags from the latest self-signature, if it has any
+    pub flags: Option<u8>,
+}
+
+fn truncated() -> String {
+    "Truncated OpenPGP packet".to_string()
+}
+
+/// Cursor over the big-endian fields of a packet
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < len {
+            return Err(truncated());
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Read a multiprecision integer.
+    fn mpi(&mut self) -> Result<&'a [u8], String> {
+        let bits = self.u16()? as usize;
+        let bytes = (bits + 7) >> 3;
+        self.take(bytes)
+    }
+
+    /// Read a length-prefixed curve OID.
+    fn oid(&mut self) -> Result<&'a [u8], String> {
+        match self.u8()? {
+            0 | 0xff => Err("Invalid OpenPGP curve OID".to_string()),
+            len => self.take(len as usize),
+        }
+    }
+}
+
+/// CRC-24 checksum of ASCII armor
+fn crc24(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xb704ce;
+    for byte in data {
+        crc ^= (*byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= 0x1864cfb;
+            }
+        }
+    }
+    crc & 0xffffff
+}
+
+/// Decode the concatenated contents of the public key blocks in an ASCII-armored text.
+fn dearmor(text: &str) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    let mut lines = text.lines().map(str::trim);
+    let mut found = false;
+    while let Some(line) = lines.next() {
+        if line != ARMOR_BEGIN {
+            continue;
+        }
+        found = true;
+        let mut body = String::new();
+        let mut checksum = None;
+        let mut in_headers = true;
+        loop {
+            let line = lines
+                .next()
+                .ok_or_else(|| "Unterminated OpenPGP armor".to_string())?;
+            if line == ARMOR_END {
+                break;
+            }
+            if in_headers {
+                in_headers = false;
+                // Armor headers, such as "Comment: ...", end with an empty line.
+                if line.contains(':') {
+                    in_headers = true;
+                    continue;
+                }
+                if line.is_empty() {
+                    continue;
+                }
+            }
+            match line.strip_prefix('=') {
+                Some(crc) if line.len() == 5 => checksum = Some(crc.to_string()),
+                _ => body.push_str(line),
+            }
+        }
+        let block =
+            base64::decode(&body).map_err(|e| format!("Unable to decode OpenPGP armor: {}", e))?;
+        if let Some(checksum) = checksum {
+            let crc = crc24(&block).to_be_bytes();
+            if base64::encode(&crc[1..]) != checksum {
+                return Err("OpenPGP armor checksum mismatch".to_string());
+            }
+        }
+        data.extend_from_slice(&block);
+    }
+    if !found {
+        return Err("Expected ASCII-armored OpenPGP public key block".to_string());
+    }
+    Ok(data)
+}
+
+/// Split data into packets, as pairs of packet tag and body.
+fn packets(mut data: &[u8]) -> Result<Vec<(u8, &[u8])>, String> {
+    let mut packets = Vec::new();
+    while !data.is_empty() {
+        let mut reader = Reader(data);
+        let ctb = reader.u8()?;
+        if ctb & 0x80 == 0 {
+            return Err("Invalid OpenPGP packet header".to_string());
+        }
+        let (tag, len) = if ctb & 0x40 != 0 {
+            // New format
+            let len = match reader.u8()? {
+                len @ 0..=191 => len as usize,
+                len @ 192..=223 => ((len as usize - 192) << 8) + reader.u8()? as usize + 192,
+                255 => reader.u32()? as usize,
+                _ => return Err("Unexpected partial length OpenPGP packet".to_string()),
+            };
+            (ctb & 0x3f, len)
+        } else {
+            // Old format
+            let len = match ctb & 0x03 {
+                0 => reader.u8()? as usize,
+                1 => reader.u16()? as usize,
+                2 => reader.u32()? as usize,
+                _ => reader.0.len(),
+            };
+            ((ctb >> 2) & 0x0f, len)
+        };
+        packets.push((tag, reader.take(len)?));
+        data = reader.0;
+    }
+    Ok(packets)
+}
+
+/// Split a signature subpacket area into subpackets, as pairs of type and body.
+fn subpackets(mut data: &[u8]) -> Result<Vec<(u8, &[u8])>, String> {
+    let mut subpackets = Vec::new();
+    while !data.is_empty() {
+        let mut reader = Reader(data);
+        let len = match reader.u8()? {
+            len @ 0..=191 => len as usize,
+            len @ 192..=254 => ((len as usize - 192) << 8) + reader.u8()? as usize + 192,
+            255 => reader.u32()? as usize,
+        };
+        let body = reader.take(len)?;
+        let (type_, value) = body.split_first().ok_or_else(truncated)?;
+        // Ignore the critical bit.
+        subpackets.push((type_ & 0x7f, value));
+        data = reader.0;
+    }
+    Ok(subpackets)
+}
+
+/// Version 4 key packet
+struct KeyPacket {
+    created: u32,
+    material: PublicKeyMaterial,
+    fingerprint: Vec<u8>,
+}
+
+impl KeyPacket {
+    /// Parse a public key or subkey packet body, or return `None` for key versions other than 4.
+    fn parse(body: &[u8]) -> Result<Option<Self>, String> {
+        let mut reader = Reader(body);
+        if reader.u8()? != 4 {
+            return Ok(None);
+        }
+        let created = reader.u32()?;
+        let material = match reader.u8()? {
+            // RSA (Encrypt or Sign), RSA Encrypt-Only, RSA Sign-Only
+            1..=3 => {
+                let n = reader.mpi()?.to_vec();
+                let e = reader.mpi()?.to_vec();
+                PublicKeyMaterial::Rsa { n, e }
+            }
+            18 => {
+                let curve_oid = reader.oid()?.to_vec();
+                let point = reader.mpi()?.to_vec();
+                PublicKeyMaterial::Ecdh { curve_oid, point }
+            }
+            19 => {
+                let curve_oid = reader.oid()?.to_vec();
+                let point = reader.mpi()?.to_vec();
+                PublicKeyMaterial::Ecdsa { curve_oid, point }
+            }
+            22 => {
+                let curve_oid = reader.oid()?.to_vec();
+                let point = reader.mpi()?.to_vec();
+                PublicKeyMaterial::EdDsa { curve_oid, point }
+            }
+            _ => PublicKeyMaterial::Other,
+        };
+        let len = u16::try_from(body.len()).map_err(|_| "OpenPGP key too long".to_string())?;
+        let mut hasher = Sha1::new();
+        hasher.update([0x99]);
+        hasher.update(len.to_be_bytes());
+        hasher.update(body);
+        Ok(Some(Self {
+            created,
+            material,
+            fingerprint: hasher.finalize().to_vec(),
+        }))
+    }
+
+    fn key_id(&self) -> &[u8] {
+        &self.fingerprint[12..]
+    }
+
+    fn fingerprint_hex(&self) -> String {
+        self.fingerprint
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect()
+    }
+}
+
+/// Version 4 signature packet, with the subpackets used to validate keys
+#[derive(Default)]
+struct Signature {
+    type_: u8,
+    created: u32,
+    key_expiration: Option<u32>,
+    key_flags: Option<u8>,
+    issuer_key_id: Option<Vec<u8>>,
+    issuer_fingerprint: Option<Vec<u8>>,
+}
+
+impl Signature {
+    /// Parse a signature packet body, or return `None` for signature versions other than 4.
+    fn parse(body: &[u8]) -> Result<Option<Self>, String> {
+        let mut reader = Reader(body);
+        if reader.u8()? != 4 {
+            return Ok(None);
+        }
+        let mut signature = Signature {
+            type_: reader.u8()?,
+            ..Default::default()
+        };
+        // Public key and hash algorithms
+        reader.take(2)?;
+        let hashed_len = reader.u16()? as usize;
+        let hashed = reader.take(hashed_len)?;
+        let unhashed_len = reader.u16()? as usize;
+        let unhashed = reader.take(unhashed_len)?;
+        let hashed = subpackets(hashed)?;
+        for &(type_, value) in &hashed {
+            match type_ {
+                SUBPACKET_CREATION_TIME => signature.created = Reader(value).u32()?,
+                SUBPACKET_KEY_EXPIRATION_TIME => {
+                    signature.key_expiration = Some(Reader(value).u32()?)
+                }
+                SUBPACKET_KEY_FLAGS => signature.key_flags = Some(Reader(value).u8().unwrap_or(0)),
+                _ => {}
+            }
+        }
+        // The issuer is not protected by the signature, and may be in either area.
+        for &(type_, value) in hashed.iter().chain(&subpackets(unhashed)?) {
+            match type_ {
+                SUBPACKET_ISSUER => signature.issuer_key_id = Some(value.to_vec()),
+                SUBPACKET_ISSUER_FINGERPRINT => {
+                    signature.issuer_fingerprint = value.get(1..).map(<[u8]>::to_vec)
+                }
+                _ => {}
+            }
+        }
+        Ok(Some(signature))
+    }
+
+    /// Check if the signature may have been made by a key. Signatures without an issuer are
+    /// assumed to be self-signatures.
+    fn is_issued_by(&self, key: &KeyPacket) -> bool {
+        match (&self.issuer_fingerprint, &self.issuer_key_id) {
+            (Some(fingerprint), _) => fingerprint[..] == key.fingerprint[..],
+            (None, Some(key_id)) => key_id[..] == *key.key_id(),
+            (None, None) => true,
+        }
+    }
+}
+
+/// A key and the signatures following it
+struct Component {
+    key: Option<KeyPacket>,
+    signatures: Vec<Signature>,
+}
+
+/// Get the latest binding self-signature of a key, or `None` if the key has no binding
+/// signature, has been revoked or has expired.
+fn binding_signature<'a>(
+    component: &'a Component,
+    key: &KeyPacket,
+    primary: &KeyPacket,
+    is_binding: fn(u8) -> bool,
+    revocation: u8,
+    now: i64,
+) -> Option<&'a Signature> {
+    let mut latest: Option<&Signature> = None;
+    for signature in &component.signatures {
+        if !signature.is_issued_by(primary) {
+            continue;
+        }
+        if signature.type_ == revocation {
+            return None;
+        }
+        if is_binding(signature.type_)
+            && !matches!(latest, Some(latest) if latest.created > signature.created)
+        {
+            latest = Some(signature);
+        }
+    }
+    let signature = latest?;
+    match signature.key_expiration {
+        Some(expiration) if expiration != 0 && key.created as i64 + expiration as i64 <= now => {
+            None
+        }
+        _ => Some(signature),
+    }
+}
+
+/// Get a valid key, skipping RSA keys shorter than 2048 bits.
+fn valid_key(key: &KeyPacket, signature: &Signature) -> Option<PublicKey> {
+    if let PublicKeyMaterial::Rsa { ref n, .. } = key.material {
+        if n.len() < 256 {
+            return None;
+        }
+    }
+    Some(PublicKey {
+        fingerprint: key.fingerprint_hex(),
+        material: key.material.clone(),
+        flags: signature.key_flags,
+    })
+}
+
+/// Parse the valid primary keys and subkeys of the certificates in ASCII-armored OpenPGP
+/// public key blocks, at time `now` (seconds since the Unix epoch).
+pub fn parse_public_keys(text: &str, now: i64) -> Result<Vec<PublicKey>, String> {
+    let data = dearmor(text)?;
+    // Certificates, as primary key followed by subkeys
+    let mut certs: Vec<Vec<Component>> = Vec::new();
+    for (tag, body) in packets(&data)? {
+        match tag {
+            TAG_PUBLIC_KEY => certs.push(vec![Component {
+                key: KeyPacket::parse(body)?,
+                signatures: Vec::new(),
+            }]),
+            TAG_PUBLIC_SUBKEY => certs
+                .last_mut()
+                .ok_or_else(|| "OpenPGP subkey without primary key".to_string())?
+                .push(Component {
+                    key: KeyPacket::parse(body)?,
+                    signatures: Vec::new(),
+                }),
+            // Certifications of user ids and attributes follow them, and are kept with the
+            // primary key: signature types tell them apart from subkey signatures.
+            TAG_SIGNATURE => {
+                let component = certs
+                    .last_mut()
+                    .and_then(|cert| cert.last_mut())
+                    .ok_or_else(|| "OpenPGP signature without key".to_string())?;
+                if let Some(signature) = Signature::parse(body)? {
+                    component.signatures.push(signature);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut keys = Vec::new();
+    for cert in &certs {
+        let (primary_component, subkeys) = match cert.split_first() {
+            Some(split) => split,
+            None => continue,
+        };
+        let primary = match primary_component.key {
+            Some(ref key) => key,
+            None => continue,
+        };
+        let is_primary_binding =
+            |type_: u8| (0x10..=0x13).contains(&type_) || type_ == SIG_DIRECT_KEY;
+        let signature = match binding_signature(
+            primary_component,
+            primary,
+            primary,
+            is_primary_binding,
+            SIG_KEY_REVOCATION,
+            now,
+        ) {
+            Some(signature) => signature,
+            None => continue,
+        };
+        keys.extend(valid_key(primary, signature));
+        for component in subkeys {
+            let subkey = match component.key {
+                Some(ref key) => key,
+                None => continue,
+            };
+            if let Some(signature) = binding_signature(
+                component,
+                subkey,
+                primary,
+                |type_| type_ == SIG_SUBKEY_BINDING,
+                SIG_SUBKEY_REVOCATION,
+                now,
+            ) {
+                keys.extend(valid_key(subkey, signature));
+            }
+        }
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn armor_checksum() {
+        // Armor with no keys, as returned for users without OpenPGP keys
+        let empty = "-----BEGIN PGP PUBLIC KEY BLOCK-----\nNote: no keys\n\n=twTO\n-----END PGP PUBLIC KEY BLOCK-----\n";
+        assert_eq!(parse_public_keys(empty, 0).unwrap(), vec![]);
+        let bad_checksum = empty.replace("=twTO", "=twTP");
+        assert!(parse_public_keys(&bad_checksum, 0).is_err());
+        assert!(parse_public_keys("ssh-ed25519 AAAA", 0).is_err());
+    }
+}
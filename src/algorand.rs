@@ -0,0 +1,122 @@
+//! [Algorand addresses](https://developer.algorand.org/docs/get-details/accounts/#transformation-public-key-to-algorand-address)
+//!
+//! An address is the base32 encoding (without padding) of an Ed25519 public key followed by the
+//! last four bytes of its SHA-512/256 hash.
+
+use crate::jwk::{Params, JWK};
+use sha2_old::{Digest, Sha512Trunc256};
+use thiserror::Error;
+
+const PUBLIC_KEY_LENGTH: usize = 32;
+const CHECKSUM_LENGTH: usize = 4;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+#[derive(Error, Debug)]
+pub enum AlgorandAddressError {
+    #[error("Unexpected address length: {0}")]
+    Length(usize),
+    #[error("Unexpected character in address: {0}")]
+    Char(char),
+    #[error("Invalid address checksum")]
+    Checksum,
+    #[error("Expected Ed25519 key")]
+    KeyType,
+}
+
+fn checksum(public_key: &[u8]) -> Vec<u8> {
+    let hash = Sha512Trunc256::digest(public_key);
+    hash[hash.len() - CHECKSUM_LENGTH..].to_vec()
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() * 8 + 4) / 5);
+    let mut buffer: u16 = 0;
+    let mut bits = 0;
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(string: &str) -> Result<Vec<u8>, AlgorandAddressError> {
+    let mut out = Vec::with_capacity(string.len() * 5 / 8);
+    let mut buffer: u16 = 0;
+    let mut bits = 0;
+    for c in string.chars() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|a| *a as char == c)
+            .ok_or(AlgorandAddressError::Char(c))?;
+        buffer = (buffer << 5) | value as u16;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+/// Encode an Ed25519 public key as an Algorand address.
+pub fn encode_address(public_key: &[u8]) -> Result<String, AlgorandAddressError> {
+    if public_key.len() != PUBLIC_KEY_LENGTH {
+        return Err(AlgorandAddressError::Length(public_key.len()));
+    }
+    let mut data = public_key.to_vec();
+    data.extend_from_slice(&checksum(public_key));
+    Ok(base32_encode(&data))
+}
+
+/// Decode an Algorand address into its Ed25519 public key.
+pub fn decode_address(address: &str) -> Result<Vec<u8>, AlgorandAddressError> {
+    let data = base32_decode(address)?;
+    if data.len() != PUBLIC_KEY_LENGTH + CHECKSUM_LENGTH {
+        return Err(AlgorandAddressError::Length(data.len()));
+    }
+    let (public_key, checksum_bytes) = data.split_at(PUBLIC_KEY_LENGTH);
+    if checksum(public_key) != checksum_bytes {
+        return Err(AlgorandAddressError::Checksum);
+    }
+    Ok(public_key.to_vec())
+}
+
+/// Get the Algorand address of an Ed25519 public key.
+pub fn encode_public_key(jwk: &JWK) -> Result<String, AlgorandAddressError> {
+    match jwk.params {
+        Params::OKP(ref params) if params.curve == "Ed25519" => {
+            encode_address(&params.public_key.0)
+        }
+        _ => Err(AlgorandAddressError::KeyType),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode() {
+        let public_key =
+            hex::decode("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d")
+                .unwrap();
+        let address = "2Q2ZHRYV7XJRYYIUDK6QJKM722BCZBKYQVGM3Y42K2COPJLNUJ6QT5ULYA";
+        assert_eq!(encode_address(&public_key).unwrap(), address);
+        assert_eq!(decode_address(address).unwrap(), public_key);
+        assert!(matches!(
+            decode_address("3Q2ZHRYV7XJRYYIUDK6QJKM722BCZBKYQVGM3Y42K2COPJLNUJ6QT5ULYA"),
+            Err(AlgorandAddressError::Checksum)
+        ));
+        assert!(matches!(
+            decode_address("2q2ZHRYV7XJRYYIUDK6QJKM722BCZBKYQVGM3Y42K2COPJLNUJ6QT5ULYA"),
+            Err(AlgorandAddressError::Char('q'))
+        ));
+    }
+}
@@ -0,0 +1,119 @@
+//! Signed messages ("Bitcoin Signed Message"), as produced by Bitcoin and Dogecoin wallets.
+//!
+//! Signatures are recoverable secp256k1 signatures with a header byte indicating the recovery id
+//! and address type, as specified in [BIP-137](https://github.com/bitcoin/bips/blob/master/bip-0137.mediawiki).
+//! Only signatures for P2PKH addresses of compressed public keys are supported.
+
+use std::convert::TryFrom;
+
+use crate::error::Error;
+use crate::hash::sha256;
+use crate::jwk::{ECParams, Params, JWK};
+use crate::passthrough_digest::PassthroughDigest;
+
+pub const BITCOIN_MESSAGE_MAGIC: &str = "Bitcoin Signed Message:\n";
+pub const DOGECOIN_MESSAGE_MAGIC: &str = "Dogecoin Signed Message:\n";
+
+/// Header byte of signatures for P2PKH addresses of compressed public keys, without recovery id
+const HEADER_P2PKH_COMPRESSED: u8 = 31;
+
+fn push_varint(data: &mut Vec<u8>, n: usize) {
+    if n < 0xfd {
+        data.push(n as u8);
+    } else if n <= 0xffff {
+        data.push(0xfd);
+        data.extend_from_slice(&(n as u16).to_le_bytes());
+    } else if n <= 0xffff_ffff {
+        data.push(0xfe);
+        data.extend_from_slice(&(n as u32).to_le_bytes());
+    } else {
+        data.push(0xff);
+        data.extend_from_slice(&(n as u64).to_le_bytes());
+    }
+}
+
+/// Hash a message for signing, with the given magic prefix: the double SHA-256 of the
+/// length-prefixed magic and message.
+pub fn hash_message(magic: &str, msg: &str) -> Result<[u8; 32], Error> {
+    let mut data = Vec::with_capacity(magic.len() + msg.len() + 10);
+    push_varint(&mut data, magic.len());
+    data.extend_from_slice(magic.as_bytes());
+    push_varint(&mut data, msg.len());
+    data.extend_from_slice(msg.as_bytes());
+    sha256(&sha256(&data)?)
+}
+
+/// Sign a message with a secp256k1 key, returning the 65-byte signature (header byte, `r` and
+/// `s`).
+pub fn sign_message(magic: &str, msg: &str, key: &JWK) -> Result<Vec<u8>, Error> {
+    use k256::ecdsa::signature::{digest::Digest, DigestSigner};
+    let ec_params = match key.params {
+        Params::EC(ref params) => params,
+        _ => return Err(Error::KeyTypeNotImplemented),
+    };
+    let secret_key = k256::SecretKey::try_from(ec_params)?;
+    let signing_key = k256::ecdsa::SigningKey::from(secret_key);
+    let hash = hash_message(magic, msg)?;
+    let digest = Digest::chain(<PassthroughDigest as Digest>::new(), hash);
+    let sig: k256::ecdsa::recoverable::Signature = signing_key.try_sign_digest(digest)?;
+    let sig_bytes = sig.as_ref();
+    let mut bytes = Vec::with_capacity(65);
+    bytes.push(HEADER_P2PKH_COMPRESSED + sig_bytes[64]);
+    bytes.extend_from_slice(&sig_bytes[..64]);
+    Ok(bytes)
+}
+
+/// Recover the public key from a 65-byte message signature.
+pub fn recover_message(magic: &str, msg: &str, signature: &[u8]) -> Result<JWK, Error> {
+    if signature.len() != 65 {
+        return Err(Error::UnexpectedSignatureLength(65, signature.len()));
+    }
+    let rec_id = match signature[0] {
+        header @ 31..=34 => header - HEADER_P2PKH_COMPRESSED,
+        // Uncompressed public key, or SegWit address
+        27..=30 | 35..=42 => return Err(Error::UnsupportedKeyType),
+        _ => return Err(Error::InvalidSignature),
+    };
+    let rec_id = k256::ecdsa::recoverable::Id::try_from(rec_id)?;
+    let sig = k256::ecdsa::Signature::try_from(&signature[1..])?;
+    let sig = k256::ecdsa::recoverable::Signature::new(&sig, rec_id)?;
+    let hash = hash_message(magic, msg)?;
+    let digest = k256::elliptic_curve::FieldBytes::<k256::Secp256k1>::from_slice(&hash);
+    let recovered_key = sig.recover_verify_key_from_digest_bytes(digest)?;
+    let jwk = JWK::from(Params::EC(ECParams::try_from(
+        &k256::PublicKey::from_sec1_bytes(&recovered_key.to_bytes())?,
+    )?));
+    Ok(jwk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_recover() {
+        let key = JWK::generate_secp256k1().unwrap();
+        let msg = "Hello, Bitcoin!";
+        let sig = sign_message(BITCOIN_MESSAGE_MAGIC, msg, &key).unwrap();
+        assert!((31..=34).contains(&sig[0]));
+        let recovered = recover_message(BITCOIN_MESSAGE_MAGIC, msg, &sig).unwrap();
+        assert!(recovered.equals_public(&key));
+        assert!(!matches!(
+            recover_message(DOGECOIN_MESSAGE_MAGIC, msg, &sig),
+            Ok(recovered) if recovered.equals_public(&key)
+        ));
+        assert!(!matches!(
+            recover_message(BITCOIN_MESSAGE_MAGIC, "Hello", &sig),
+            Ok(recovered) if recovered.equals_public(&key)
+        ));
+    }
+
+    #[test]
+    fn hash() {
+        let hash = hash_message(BITCOIN_MESSAGE_MAGIC, "").unwrap();
+        assert_eq!(
+            hex::encode(hash),
+            "80e795d4a4caadd7047af389d9f7f220562feb6196032e2131e10563352c4bcc"
+        );
+    }
+}
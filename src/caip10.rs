@@ -74,6 +74,37 @@ impl BlockchainAccountId {
                 crate::ripemd::hash_public_key(jwk, 0x1e)
                     .map_err(|e| BlockchainAccountIdVerifyError::HashError(e.to_string()))
             }
+            // Cosmos: the address prefix depends on the chain.
+            #[cfg(all(feature = "ripemd160", feature = "bech32"))]
+            ("cosmos", _net) => {
+                let hrp = match self.account_address.rfind('1') {
+                    Some(i) => &self.account_address[..i],
+                    None => {
+                        return Err(BlockchainAccountIdVerifyError::HashError(
+                            "Expected Bech32 address".to_string(),
+                        ))
+                    }
+                };
+                crate::ripemd::hash_public_key_bech32(jwk, hrp)
+                    .map_err(|e| BlockchainAccountIdVerifyError::HashError(e.to_string()))
+            }
+            // Polkadot and Kusama (Ed25519 accounts only)
+            ("polkadot", net) => {
+                let address_type = match net {
+                    "91b171bb158e2d3848fa23a9f1c25182" => crate::ss58::POLKADOT,
+                    "b0a8d493285c2df73290dfb7e61f870f" => crate::ss58::KUSAMA,
+                    _ => match crate::ss58::decode(&self.account_address) {
+                        Ok((address_type, _)) => address_type,
+                        Err(e) => {
+                            return Err(BlockchainAccountIdVerifyError::HashError(e.to_string()))
+                        }
+                    },
+                };
+                crate::ss58::encode_public_key(jwk, address_type)
+                    .map_err(|e| BlockchainAccountIdVerifyError::HashError(e.to_string()))
+            }
+            ("algorand", _net) => crate::algorand::encode_public_key(jwk)
+                .map_err(|e| BlockchainAccountIdVerifyError::HashError(e.to_string())),
             _ => Err(BlockchainAccountIdVerifyError::UnknownChainId(
                 self.chain_id.to_string(),
             )),
@@ -193,5 +224,22 @@ mod tests {
         )
         .unwrap();
         account_id.verify(&jwk).unwrap();
+
+        let account_id = BlockchainAccountId::from_str(
+            "polkadot:91b171bb158e2d3848fa23a9f1c25182:1dTEPeJBU76DFJsEFq6aHzkD7frp1LB2BUAg6bnESv1KtLg",
+        )
+        .unwrap();
+        account_id.verify(&jwk).unwrap();
+        // Kusama address of the same key
+        let account_id = BlockchainAccountId::from_str(
+            "polkadot:b0a8d493285c2df73290dfb7e61f870f:DCmkNj6x3rYXN7o3Kb9L6XbW5xSvNbDQ4aRuTtPAA6ytDQF",
+        )
+        .unwrap();
+        account_id.verify(&jwk).unwrap();
+        let account_id = BlockchainAccountId::from_str(
+            "algorand:wGHE2Pwdvd7S12BL5FaOP20EGYesN73k:DPGSFMSK577HCOXWU3DC2KS6HBZGBGK6BQUIZ3GLTF4YSPVJBMGJ2F44EE",
+        )
+        .unwrap();
+        account_id.verify(&jwk).unwrap();
     }
 }
@@ -1,14 +1,17 @@
 use crate::caip10::BlockchainAccountId;
 use std::collections::BTreeMap as Map;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::fmt;
+use std::iter::FromIterator;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use crate::did_resolve::{
     Content, ContentMetadata, DIDResolver, DereferencingInputMetadata, DereferencingMetadata,
     DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata, ERROR_INVALID_DID,
-    ERROR_METHOD_NOT_SUPPORTED, TYPE_DID_LD_JSON,
+    ERROR_INVALID_DID_DOCUMENT, ERROR_METHOD_NOT_SUPPORTED, TYPE_DID_CBOR, TYPE_DID_JSON,
+    TYPE_DID_LD_JSON, TYPE_JSON,
 };
 use crate::error::Error;
 use crate::jwk::JWK;
@@ -256,10 +259,39 @@ pub trait DIDMethod: DIDResolver {
     /// <https://w3c.github.io/did-core/#method-schemes>
     fn name(&self) -> &'static str;
 
-    // TODO: allow returning errors
     /// Generate a DID from some source
-    fn generate(&self, _source: &Source) -> Option<String> {
-        None
+    fn generate(&self, _source: &Source) -> Result<String, GenerateError> {
+        Err(GenerateError::NotImplemented)
+    }
+
+    /// Create a DID. Returns a transaction to be submitted with
+    /// [`submit_transaction`](DIDMethod::submit_transaction) or published by the caller.
+    fn create(&self, _create: DIDCreate) -> Result<DIDMethodTransaction, DIDMethodError> {
+        Err(DIDMethodError::NotImplemented("Create operation"))
+    }
+
+    /// Update a DID document. Returns a transaction to be submitted or published.
+    fn update(&self, _update: DIDUpdate) -> Result<DIDMethodTransaction, DIDMethodError> {
+        Err(DIDMethodError::NotImplemented("Update operation"))
+    }
+
+    /// Recover a DID, replacing its keys. Returns a transaction to be submitted or published.
+    fn recover(&self, _recover: DIDRecover) -> Result<DIDMethodTransaction, DIDMethodError> {
+        Err(DIDMethodError::NotImplemented("Recover operation"))
+    }
+
+    /// Deactivate a DID. Returns a transaction to be submitted or published.
+    fn deactivate(
+        &self,
+        _deactivate: DIDDeactivate,
+    ) -> Result<DIDMethodTransaction, DIDMethodError> {
+        Err(DIDMethodError::NotImplemented("Deactivate operation"))
+    }
+
+    /// Submit a DID method transaction, e.g. to a ledger or registry. Returns a method-specific
+    /// result.
+    async fn submit_transaction(&self, _tx: DIDMethodTransaction) -> Result<Value, DIDMethodError> {
+        Err(DIDMethodError::NotImplemented("Transaction submission"))
     }
 
     /// Upcast the DID method as a DID resolver.
@@ -270,6 +302,152 @@ pub trait DIDMethod: DIDResolver {
     fn to_resolver(&self) -> &dyn DIDResolver;
 }
 
+/// Error from a DID method operation
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum DIDMethodError {
+    #[error("Not implemented for DID method: {0}")]
+    NotImplemented(&'static str),
+    #[error("Option '{option}' not supported for DID operation '{operation}'")]
+    OptionNotSupported {
+        operation: &'static str,
+        option: String,
+    },
+    #[error("Missing {0} key")]
+    MissingKey(&'static str),
+    #[error("Missing option: {0}")]
+    MissingOption(&'static str),
+    #[error("Invalid DID: {0}")]
+    InvalidDID(String),
+    #[error("{0}")]
+    Error(#[from] Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+/// Error from generating a DID with [`DIDMethod::generate`]
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum GenerateError {
+    #[error("DID generation not implemented for DID method")]
+    NotImplemented,
+    #[error("Unsupported source for DID generation")]
+    UnsupportedSource,
+    #[error("Missing DID method name in pattern")]
+    MissingMethodName,
+    #[error("DID method not supported: {0}")]
+    MethodNotSupported(String),
+    #[error("Unsupported pattern: {0}")]
+    UnsupportedPattern(String),
+    #[error("Unsupported key type")]
+    UnsupportedKeyType,
+    #[error("Unsupported curve: {0}")]
+    UnsupportedCurve(String),
+    #[error("{0}")]
+    Error(#[from] Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+/// A transaction produced by a DID method operation, to be submitted with
+/// [`DIDMethod::submit_transaction`] or published as the DID method requires.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DIDMethodTransaction {
+    /// Name of the DID method that can handle the transaction
+    pub did_method: String,
+    /// Method-specific transaction data
+    #[serde(flatten)]
+    pub value: Map<String, Value>,
+}
+
+/// An operation on the contents of a DID document, for [`DIDUpdate`]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", content = "value")]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum DIDDocumentOperation {
+    /// Replace the DID document
+    SetDidDocument(Document),
+    /// Add or replace top-level properties of the DID document
+    AddToDidDocument(Map<String, Value>),
+    /// Remove top-level properties of the DID document
+    RemoveFromDidDocument(Vec<String>),
+    /// Add a verification method, with the given verification relationships
+    AddVerificationMethod {
+        vmm: VerificationMethodMap,
+        purposes: Vec<VerificationRelationship>,
+    },
+    /// Remove a verification method
+    RemoveVerificationMethod(DIDURL),
+    /// Add a service
+    AddService(Service),
+    /// Remove a service
+    RemoveService(DIDURL),
+}
+
+/// Input for a DID create operation
+#[derive(Debug, Clone, Default)]
+pub struct DIDCreate {
+    pub update_key: Option<JWK>,
+    pub recovery_key: Option<JWK>,
+    pub verification_key: Option<JWK>,
+    /// Method-specific options
+    pub options: Map<String, Value>,
+}
+
+/// Input for a DID update operation
+#[derive(Debug, Clone)]
+pub struct DIDUpdate {
+    pub did: String,
+    pub update_key: Option<JWK>,
+    pub new_update_key: Option<JWK>,
+    pub operation: DIDDocumentOperation,
+    /// Method-specific options
+    pub options: Map<String, Value>,
+}
+
+/// Input for a DID recover operation
+#[derive(Debug, Clone, Default)]
+pub struct DIDRecover {
+    pub did: String,
+    pub recovery_key: Option<JWK>,
+    pub new_update_key: Option<JWK>,
+    pub new_recovery_key: Option<JWK>,
+    pub new_verification_key: Option<JWK>,
+    /// Method-specific options
+    pub options: Map<String, Value>,
+}
+
+/// Input for a DID deactivate operation
+#[derive(Debug, Clone, Default)]
+pub struct DIDDeactivate {
+    pub did: String,
+    pub key: Option<JWK>,
+    /// Method-specific options
+    pub options: Map<String, Value>,
+}
+
+impl DIDMethodError {
+    /// Error for the first unsupported option of an operation, if any.
+    pub fn check_options(
+        operation: &'static str,
+        options: &Map<String, Value>,
+        supported: &[&str],
+    ) -> Result<(), Self> {
+        match options
+            .keys()
+            .find(|key| !supported.contains(&key.as_str()))
+        {
+            Some(option) => Err(Self::OptionNotSupported {
+                operation,
+                option: option.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
 /// A collection of DID methods
 #[derive(Clone, Default)]
 pub struct DIDMethods<'a> {
@@ -294,16 +472,7 @@ impl<'a> DIDMethods<'a> {
 
     /// Get DID method to handle a given DID
     pub fn get_method(&self, did: &str) -> Result<&&'a dyn DIDMethod, &'static str> {
-        let mut parts = did.split(':');
-        if parts.next() != Some("did") {
-            return Err(ERROR_INVALID_DID);
-        };
-        let method_name = match parts.next() {
-            Some(method_name) => method_name,
-            None => {
-                return Err(ERROR_INVALID_DID);
-            }
-        };
+        let method_name = did_method_name(did)?;
         let method = match self.methods.get(method_name) {
             Some(method) => method,
             None => {
@@ -314,27 +483,253 @@ impl<'a> DIDMethods<'a> {
     }
 
     /// Generate a DID given some input
-    pub fn generate(&self, source: &Source) -> Option<String> {
-        let (jwk, pattern) = match source {
-            Source::Key(_) => {
-                // Need name/pattern to select DID method
-                return None;
+    pub fn generate(&self, source: &Source) -> Result<String, GenerateError> {
+        let (method_name, source) = select_generate_method(source)?;
+        let method = self
+            .methods
+            .get(method_name)
+            .ok_or_else(|| GenerateError::MethodNotSupported(method_name.to_string()))?;
+        method.generate(&source)
+    }
+}
+
+// Get the method name of a DID.
+fn did_method_name(did: &str) -> Result<&str, &'static str> {
+    let mut parts = did.split(':');
+    if parts.next() != Some("did") {
+        return Err(ERROR_INVALID_DID);
+    };
+    match parts.next() {
+        Some(method_name) => Ok(method_name),
+        None => Err(ERROR_INVALID_DID),
+    }
+}
+
+// Get the DID method name from a source's pattern, and the source to pass to that DID method.
+fn select_generate_method<'s>(source: &Source<'s>) -> Result<(&'s str, Source<'s>), GenerateError> {
+    let (jwk, pattern) = match source {
+        Source::Key(_) => {
+            // Need name/pattern to select DID method
+            return Err(GenerateError::MissingMethodName);
+        }
+        Source::KeyAndPattern(jwk, pattern) => (*jwk, *pattern),
+    };
+    let mut parts = pattern.splitn(2, ':');
+    let method_name = parts.next().unwrap();
+    let source = match parts.next() {
+        Some(method_pattern) => Source::KeyAndPattern(jwk, method_pattern),
+        None => Source::Key(jwk),
+    };
+    Ok((method_name, source))
+}
+
+/// A DID method owned by a [`DIDMethodRegistry`]
+pub type SharedDIDMethod = Arc<dyn DIDMethod + Send + Sync>;
+
+/// Configuration for a [`DIDMethodRegistry`]: the names of the DID methods to enable.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DIDMethodRegistryConfig {
+    pub methods: Vec<String>,
+}
+
+impl FromStr for DIDMethodRegistryConfig {
+    type Err = std::convert::Infallible;
+    /// Parse a comma-separated list of DID method names, e.g. `key,web,pkh`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let methods = s
+            .split(',')
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_string())
+            .collect();
+        Ok(Self { methods })
+    }
+}
+
+/// Error building a [`DIDMethodRegistry`] from a [`DIDMethodRegistryConfig`]
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum RegistryConfigError {
+    #[error("Unknown DID method: {0}")]
+    UnknownMethod(String),
+    #[error("DID method configured more than once: {0}")]
+    DuplicateMethod(String),
+}
+
+/// A collection of DID methods, owned rather than borrowed as in [`DIDMethods`]. Cloning the
+/// registry is cheap, so it can be shared across tasks.
+#[derive(Clone, Default)]
+pub struct DIDMethodRegistry {
+    methods: Arc<HashMap<String, SharedDIDMethod>>,
+}
+
+impl DIDMethodRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a registry from configuration, taking each configured DID method from the DID
+    /// methods available to the application. Fails if a configured method is not available or is
+    /// listed more than once.
+    pub fn from_config(
+        config: &DIDMethodRegistryConfig,
+        available: &[SharedDIDMethod],
+    ) -> Result<Self, RegistryConfigError> {
+        let mut registry = Self::new();
+        for name in &config.methods {
+            let method = available
+                .iter()
+                .find(|method| method.name() == name)
+                .ok_or_else(|| RegistryConfigError::UnknownMethod(name.to_string()))?;
+            if registry.insert(method.clone()).is_some() {
+                return Err(RegistryConfigError::DuplicateMethod(name.to_string()));
             }
-            Source::KeyAndPattern(jwk, pattern) => (jwk, pattern),
+        }
+        Ok(registry)
+    }
+
+    /// Add a DID method to the registry. Returns the previous one set for the given method name,
+    /// if any.
+    pub fn insert(&mut self, method: SharedDIDMethod) -> Option<SharedDIDMethod> {
+        let name = method.name().to_string();
+        Arc::make_mut(&mut self.methods).insert(name, method)
+    }
+
+    /// Remove a DID method from the registry.
+    pub fn remove(&mut self, method_name: &str) -> Option<SharedDIDMethod> {
+        Arc::make_mut(&mut self.methods).remove(method_name)
+    }
+
+    /// Get a DID method from the registry.
+    pub fn get(&self, method_name: &str) -> Option<&SharedDIDMethod> {
+        self.methods.get(method_name)
+    }
+
+    /// Get the names of the DID methods in the registry, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.methods.keys().map(|name| name.as_str())
+    }
+
+    /// Get DID method to handle a given DID
+    pub fn get_method(&self, did: &str) -> Result<&SharedDIDMethod, &'static str> {
+        let method_name = did_method_name(did)?;
+        self.methods
+            .get(method_name)
+            .ok_or(ERROR_METHOD_NOT_SUPPORTED)
+    }
+
+    /// Generate a DID given some input. The source must include a pattern starting with the DID
+    /// method name, as for [`DIDMethods::generate`].
+    pub fn generate(&self, source: &Source) -> Result<String, GenerateError> {
+        let (method_name, source) = select_generate_method(source)?;
+        let method = self
+            .methods
+            .get(method_name)
+            .ok_or_else(|| GenerateError::MethodNotSupported(method_name.to_string()))?;
+        method.generate(&source)
+    }
+
+    /// Borrow the registry as a [`DIDMethods`].
+    pub fn to_did_methods(&self) -> DIDMethods<'_> {
+        let mut methods = DIDMethods::default();
+        for method in self.methods.values() {
+            methods.insert(method.as_ref());
+        }
+        methods
+    }
+
+    pub fn to_resolver(&self) -> &dyn DIDResolver {
+        self
+    }
+}
+
+impl FromIterator<SharedDIDMethod> for DIDMethodRegistry {
+    fn from_iter<I: IntoIterator<Item = SharedDIDMethod>>(iter: I) -> Self {
+        let mut registry = Self::default();
+        registry.extend(iter);
+        registry
+    }
+}
+
+impl Extend<SharedDIDMethod> for DIDMethodRegistry {
+    fn extend<I: IntoIterator<Item = SharedDIDMethod>>(&mut self, iter: I) {
+        for method in iter {
+            self.insert(method);
+        }
+    }
+}
+
+/// Reject a resolved DID document that does not conform to DID Core, according to
+/// [`Document::validate`].
+fn validate_resolved(
+    (res_meta, doc, doc_meta): (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ),
+) -> (
+    ResolutionMetadata,
+    Option<Document>,
+    Option<DocumentMetadata>,
+) {
+    match doc {
+        Some(ref doc) if doc.validate().is_err() => (
+            ResolutionMetadata::from_error(ERROR_INVALID_DID_DOCUMENT),
+            None,
+            None,
+        ),
+        _ => (res_meta, doc, doc_meta),
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl DIDResolver for DIDMethodRegistry {
+    async fn resolve(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        let method = match self.get_method(did) {
+            Ok(method) => method,
+            Err(err) => return (ResolutionMetadata::from_error(err), None, None),
         };
-        let mut parts = pattern.splitn(2, ':');
-        let method_name = parts.next().unwrap();
-        let method = match self.methods.get(method_name) {
-            Some(method) => method,
-            None => return None,
+        validate_resolved(method.resolve(did, input_metadata).await)
+    }
+
+    async fn resolve_representation(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (ResolutionMetadata, Vec<u8>, Option<DocumentMetadata>) {
+        let method = match self.get_method(did) {
+            Ok(method) => method,
+            Err(err) => return (ResolutionMetadata::from_error(err), Vec::new(), None),
         };
-        if let Some(method_pattern) = parts.next() {
-            let source = Source::KeyAndPattern(jwk, method_pattern);
-            method.generate(&source)
-        } else {
-            let source = Source::Key(jwk);
-            method.generate(&source)
-        }
+        method.resolve_representation(did, input_metadata).await
+    }
+
+    async fn dereference(
+        &self,
+        did_url: &PrimaryDIDURL,
+        input_metadata: &DereferencingInputMetadata,
+    ) -> Option<(DereferencingMetadata, Content, ContentMetadata)> {
+        let method = match self.get_method(&did_url.did) {
+            Ok(method) => method,
+            Err(err) => {
+                return Some((
+                    DereferencingMetadata::from_error(err),
+                    Content::Null,
+                    ContentMetadata::default(),
+                ))
+            }
+        };
+        method.dereference(did_url, input_metadata).await
     }
 }
 
@@ -354,7 +749,7 @@ impl<'a> DIDResolver for DIDMethods<'a> {
             Ok(method) => method,
             Err(err) => return (ResolutionMetadata::from_error(err), None, None),
         };
-        method.resolve(did, input_metadata).await
+        validate_resolved(method.resolve(did, input_metadata).await)
     }
 
     async fn resolve_representation(
@@ -469,6 +864,24 @@ impl VerificationMethodMap {
         }
     }
 
+    /// Check that the verification method has exactly one verification material property, or
+    /// otherwise identifies a blockchain account.
+    /// <https://www.w3.org/TR/did-core/#verification-material>
+    pub fn validate_verification_material(&self) -> Result<(), Error> {
+        let mut count = self.public_key_jwk.iter().count() + self.public_key_base58.iter().count();
+        if let Some(ref property_set) = self.property_set {
+            count += VERIFICATION_MATERIAL_PROPERTIES
+                .iter()
+                .filter(|name| property_set.contains_key(**name))
+                .count();
+        }
+        match count {
+            0 if self.blockchain_account_id.is_none() => Err(Error::MissingKey),
+            0 | 1 => Ok(()),
+            _ => Err(Error::MultipleKeyMaterial),
+        }
+    }
+
     /// Get the verification material as a JWK, from the publicKeyJwk property, or converting from other
     /// public key properties as needed.
     pub fn get_jwk(&self) -> Result<JWK, Error> {
@@ -501,7 +914,10 @@ impl VerificationMethodMap {
                 let pk_hex = pk_hex.strip_prefix("0x").unwrap_or(pk_hex);
                 hex::decode(pk_hex)?
             }
-            (None, None, None, Some(pk_mb)) => multibase::decode(pk_mb)?.1,
+            (None, None, None, Some(pk_mb)) => match multibase::decode(pk_mb)? {
+                (multibase::Base::Base58Btc, pk_bytes) => pk_bytes,
+                _ => return Err(Error::ExpectedMultibaseZ),
+            },
             (None, None, None, None) => return Err(Error::MissingKey),
             _ => {
                 // https://w3c.github.io/did-core/#verification-material
@@ -555,6 +971,66 @@ impl VerificationMethodMap {
     }
 }
 
+/// Verification material properties, other than `publicKeyJwk` and `publicKeyBase58`
+const VERIFICATION_MATERIAL_PROPERTIES: [&str; 4] = [
+    "publicKeyMultibase",
+    "publicKeyHex",
+    "publicKeyPem",
+    "publicKeyBase64",
+];
+
+/// Check that a string is a DID, according to the [DID Syntax](https://www.w3.org/TR/did-core/#did-syntax).
+pub fn validate_did(did: &str) -> Result<(), Error> {
+    let invalid = || Error::InvalidDID(did.to_string());
+    let mut parts = did.splitn(3, ':');
+    if parts.next() != Some("did") {
+        return Err(invalid());
+    }
+    let method_name = parts.next().ok_or_else(invalid)?;
+    let method_specific_id = parts.next().ok_or_else(invalid)?;
+    // method-name = 1*method-char
+    // method-char = %x61-7A / DIGIT
+    if method_name.is_empty()
+        || !method_name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    // method-specific-id = *( *idchar ":" ) 1*idchar
+    // idchar = ALPHA / DIGIT / "." / "-" / "_" / pct-encoded
+    if method_specific_id.is_empty() || method_specific_id.ends_with(':') {
+        return Err(invalid());
+    }
+    let bytes = method_specific_id.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let pct_encoded = bytes
+                    .get(i + 1..i + 3)
+                    .map_or(false, |hex| hex.iter().all(u8::is_ascii_hexdigit));
+                if !pct_encoded {
+                    return Err(invalid());
+                }
+                i += 3;
+                continue;
+            }
+            b if b.is_ascii_alphanumeric() || b".-_:".contains(&b) => {}
+            _ => return Err(invalid()),
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+/// Check that a string is a DID URL whose DID is valid.
+fn validate_did_url(did_url: &str) -> Result<DIDURL, Error> {
+    let did_url = DIDURL::from_str(did_url)?;
+    validate_did(&did_url.did)?;
+    Ok(did_url)
+}
+
 impl FromStr for DIDURL {
     type Err = Error;
     fn from_str(didurl: &str) -> Result<Self, Self::Err> {
@@ -946,12 +1422,133 @@ impl Document {
         Ok(vm_ids)
     }
 
+    /// Check that the DID document conforms to [DID Core](https://www.w3.org/TR/did-core/):
+    /// - `id` and `controller` values are DIDs.
+    /// - Verification methods have unique DID URLs as ids, DIDs as controllers, and exactly one
+    ///   verification material property (or a `blockchainAccountId`).
+    /// - Verification relationships referring to verification methods of this DID refer to
+    ///   verification methods in this DID document.
+    /// - Services have unique ids and valid service endpoints.
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_did(&self.id)?;
+        for controller in self.controller.iter().flatten() {
+            validate_did(controller)?;
+        }
+        let relationships = [
+            &self.authentication,
+            &self.assertion_method,
+            &self.key_agreement,
+            &self.capability_invocation,
+            &self.capability_delegation,
+        ];
+        let mut ids = HashSet::new();
+        for vm in [&self.verification_method, &self.public_key]
+            .iter()
+            .chain(relationships.iter())
+            .flat_map(|vms| vms.iter().flatten())
+        {
+            if let VerificationMethod::Map(vmm) = vm {
+                let id = vmm.get_id(&self.id);
+                validate_did_url(&id)?;
+                validate_did(&vmm.controller)?;
+                vmm.validate_verification_material()?;
+                if !ids.insert(id.clone()) {
+                    return Err(Error::DuplicateId(id));
+                }
+            }
+        }
+        for vm in relationships.iter().flat_map(|vms| vms.iter().flatten()) {
+            let id = match vm {
+                VerificationMethod::Map(_) => continue,
+                VerificationMethod::DIDURL(_) | VerificationMethod::RelativeDIDURL(_) => {
+                    vm.get_id(&self.id)
+                }
+            };
+            let did_url = validate_did_url(&id)?;
+            if did_url.did == self.id && !ids.contains(&id) {
+                return Err(Error::ResourceNotFound(id));
+            }
+        }
+        for service in self.service.iter().flatten() {
+            let id = if service.id.starts_with('#') {
+                self.id.to_string() + &service.id
+            } else {
+                service.id.to_string()
+            };
+            if !id.contains(':') {
+                return Err(Error::URI);
+            }
+            let service_endpoints = match service.service_endpoint {
+                Some(ref service_endpoints) if !service_endpoints.is_empty() => service_endpoints,
+                _ => return Err(Error::InvalidServiceEndpoint(id)),
+            };
+            for service_endpoint in service_endpoints {
+                let valid = match service_endpoint {
+                    ServiceEndpoint::URI(uri) => uri.contains(':'),
+                    ServiceEndpoint::Map(map) => map.is_object(),
+                };
+                if !valid {
+                    return Err(Error::InvalidServiceEndpoint(id));
+                }
+            }
+            if !ids.insert(id.clone()) {
+                return Err(Error::DuplicateId(id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Produce a representation of the DID document.
+    ///
+    /// Representation-specific entries (`@context`) are only included in the JSON-LD
+    /// representation ([`TYPE_DID_LD_JSON`]), not in the plain JSON ([`TYPE_DID_JSON`]) or CBOR
+    /// ([`TYPE_DID_CBOR`]) representations.
+    /// <https://www.w3.org/TR/did-core/#production-and-consumption>
     pub fn to_representation(&self, content_type: &str) -> Result<Vec<u8>, Error> {
         match content_type {
             TYPE_DID_LD_JSON => Ok(serde_json::to_vec(self)?),
+            TYPE_DID_JSON => Ok(serde_json::to_vec(&self.to_data_model()?)?),
+            TYPE_DID_CBOR => {
+                let mut representation = Vec::new();
+                ciborium::ser::into_writer(&self.to_data_model()?, &mut representation)?;
+                Ok(representation)
+            }
             _ => Err(Error::RepresentationNotSupported),
         }
     }
+
+    /// Consume a representation of a DID document.
+    ///
+    /// Representations without `@context` are given the default DID context.
+    pub fn from_representation(representation: &[u8], content_type: &str) -> Result<Self, Error> {
+        let value: Value = match content_type {
+            TYPE_DID_LD_JSON | TYPE_DID_JSON | TYPE_JSON => serde_json::from_slice(representation)?,
+            TYPE_DID_CBOR => ciborium::de::from_reader(representation)?,
+            _ => return Err(Error::RepresentationNotSupported),
+        };
+        Self::from_data_model(value)
+    }
+
+    // Get the properties of the DID document, without representation-specific entries.
+    fn to_data_model(&self) -> Result<Value, Error> {
+        let mut value = serde_json::to_value(self)?;
+        if let Value::Object(ref mut object) = value {
+            object.remove("@context");
+        }
+        Ok(value)
+    }
+
+    fn from_data_model(mut value: Value) -> Result<Self, Error> {
+        if let Value::Object(ref mut object) = value {
+            if !object.contains_key("@context") {
+                object.insert(
+                    "@context".to_string(),
+                    Value::String(DEFAULT_CONTEXT.to_string()),
+                );
+            }
+        }
+        Ok(serde_json::from_value(value)?)
+    }
 }
 
 pub mod example {
@@ -1174,4 +1771,254 @@ mod tests {
         let jwk = vmm_ed.get_jwk().unwrap();
         assert_eq!(jwk, pk_jwk);
     }
+
+    #[test]
+    fn vmm_multibase_to_jwk() {
+        let jwk = JWK::generate_ed25519().unwrap().to_public();
+        let pk_bytes = match jwk.params {
+            crate::jwk::Params::OKP(ref params) => params.public_key.0.clone(),
+            _ => unreachable!(),
+        };
+        let vmm_multibase = |base| {
+            let mut property_set = Map::new();
+            property_set.insert(
+                "publicKeyMultibase".to_string(),
+                Value::String(multibase::encode(base, &pk_bytes)),
+            );
+            VerificationMethodMap {
+                id: String::from("did:example:foo#key-multibase"),
+                type_: String::from("Ed25519VerificationKey2018"),
+                controller: String::from("did:example:foo"),
+                property_set: Some(property_set),
+                ..Default::default()
+            }
+        };
+        let vmm = vmm_multibase(multibase::Base::Base58Btc);
+        assert_eq!(vmm.get_jwk().unwrap(), jwk);
+        let vmm = vmm_multibase(multibase::Base::Base64Url);
+        assert!(matches!(vmm.get_jwk(), Err(Error::ExpectedMultibaseZ)));
+    }
+
+    #[async_std::test]
+    async fn did_method_registry() {
+        use crate::did::example::DIDExample;
+        let registry: DIDMethodRegistry = vec![Arc::new(DIDExample) as SharedDIDMethod]
+            .into_iter()
+            .collect();
+        assert_eq!(registry.names().collect::<Vec<_>>(), vec!["example"]);
+
+        // Clones can be moved into other tasks.
+        let registry_clone = registry.clone();
+        let (res_meta, doc, _) = async_std::task::spawn(async move {
+            registry_clone
+                .resolve("did:example:foo", &ResolutionInputMetadata::default())
+                .await
+        })
+        .await;
+        assert_eq!(res_meta.error, None);
+        assert_eq!(doc.unwrap().id, "did:example:foo");
+
+        let (res_meta, _, _) = registry
+            .resolve("did:other:foo", &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, Some(ERROR_METHOD_NOT_SUPPORTED.to_string()));
+
+        let did_methods = registry.to_did_methods();
+        assert!(did_methods.get("example").is_some());
+
+        let jwk = JWK::generate_ed25519().unwrap();
+        assert!(matches!(
+            registry.generate(&Source::Key(&jwk)),
+            Err(GenerateError::MissingMethodName)
+        ));
+        assert!(matches!(
+            registry.generate(&Source::KeyAndPattern(&jwk, "other")),
+            Err(GenerateError::MethodNotSupported(_))
+        ));
+
+        let available = vec![Arc::new(DIDExample) as SharedDIDMethod];
+        let config: DIDMethodRegistryConfig =
+            serde_json::from_value(serde_json::json!({ "methods": ["example"] })).unwrap();
+        let registry = DIDMethodRegistry::from_config(&config, &available).unwrap();
+        assert_eq!(registry.names().collect::<Vec<_>>(), vec!["example"]);
+        let config: DIDMethodRegistryConfig = "example, other".parse().unwrap();
+        assert!(matches!(
+            DIDMethodRegistry::from_config(&config, &available),
+            Err(RegistryConfigError::UnknownMethod(name)) if name == "other"
+        ));
+        let config: DIDMethodRegistryConfig = "example,example".parse().unwrap();
+        assert!(matches!(
+            DIDMethodRegistry::from_config(&config, &available),
+            Err(RegistryConfigError::DuplicateMethod(_))
+        ));
+    }
+
+    #[test]
+    fn validate_did_syntax() {
+        validate_did("did:example:foo").unwrap();
+        validate_did("did:web:localhost%3A8080:user:alice").unwrap();
+        validate_did("did:ion:EiA_x-y.z").unwrap();
+        for did in &[
+            "did:example",
+            "did:example:",
+            "did:Example:foo",
+            "did:example:foo:",
+            "did:example:foo#key",
+            "did:example:%zz",
+            "example:foo",
+        ] {
+            assert!(validate_did(did).is_err(), "{}", did);
+        }
+    }
+
+    #[test]
+    fn validate_document() {
+        let doc: Document =
+            serde_json::from_str(include_str!("../tests/did-example-foo.json")).unwrap();
+        doc.validate().unwrap();
+
+        let mut bad_doc = doc.clone();
+        bad_doc.id = "did:example:bad id".to_string();
+        assert!(matches!(bad_doc.validate(), Err(Error::InvalidDID(_))));
+
+        // Resolvers of DID method registries reject non-conforming DID documents.
+        let (res_meta, doc_opt, _) = validate_resolved((
+            ResolutionMetadata::default(),
+            Some(bad_doc),
+            Some(DocumentMetadata::default()),
+        ));
+        assert_eq!(res_meta.error.as_deref(), Some(ERROR_INVALID_DID_DOCUMENT));
+        assert!(doc_opt.is_none());
+
+        let mut bad_doc = doc.clone();
+        let vms = bad_doc.verification_method.as_mut().unwrap();
+        vms.push(vms[0].clone());
+        assert!(matches!(bad_doc.validate(), Err(Error::DuplicateId(_))));
+
+        let mut bad_doc = doc.clone();
+        if let VerificationMethod::Map(ref mut vmm) =
+            bad_doc.verification_method.as_mut().unwrap()[2]
+        {
+            vmm.public_key_jwk = Some(JWK::generate_ed25519().unwrap().to_public());
+        }
+        assert!(matches!(
+            bad_doc.validate(),
+            Err(Error::MultipleKeyMaterial)
+        ));
+
+        let mut bad_doc = doc.clone();
+        bad_doc.authentication = Some(vec![VerificationMethod::RelativeDIDURL(
+            RelativeDIDURL::from_str("#key4").unwrap(),
+        )]);
+        assert!(matches!(
+            bad_doc.validate(),
+            Err(Error::ResourceNotFound(_))
+        ));
+
+        // Verification methods of other DIDs can be referenced.
+        let mut other_doc = doc.clone();
+        other_doc.authentication = Some(vec![VerificationMethod::DIDURL(
+            DIDURL::from_str("did:example:bar#key1").unwrap(),
+        )]);
+        other_doc.validate().unwrap();
+
+        let service = Service {
+            id: "#hub".to_string(),
+            type_: OneOrMany::One("Hub".to_string()),
+            service_endpoint: Some(OneOrMany::One(ServiceEndpoint::URI(
+                "https://hub.example.com/".to_string(),
+            ))),
+            property_set: None,
+        };
+        let mut service_doc = doc.clone();
+        service_doc.service = Some(vec![service.clone()]);
+        service_doc.validate().unwrap();
+
+        let mut bad_doc = doc.clone();
+        bad_doc.service = Some(vec![service.clone(), service.clone()]);
+        assert!(matches!(bad_doc.validate(), Err(Error::DuplicateId(_))));
+
+        let mut bad_doc = doc;
+        bad_doc.service = Some(vec![Service {
+            service_endpoint: None,
+            ..service
+        }]);
+        assert!(matches!(
+            bad_doc.validate(),
+            Err(Error::InvalidServiceEndpoint(_))
+        ));
+    }
+
+    #[test]
+    fn document_representations() {
+        let doc: Document =
+            serde_json::from_str(include_str!("../tests/did-example-foo.json")).unwrap();
+        let data_model = doc.to_data_model().unwrap();
+
+        let json = doc.to_representation(TYPE_DID_JSON).unwrap();
+        let value: Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value.get("@context"), None);
+        assert_eq!(value["id"], "did:example:foo");
+        let doc_json = Document::from_representation(&json, TYPE_DID_JSON).unwrap();
+        assert_eq!(doc_json.to_data_model().unwrap(), data_model);
+
+        let cbor = doc.to_representation(TYPE_DID_CBOR).unwrap();
+        let doc_cbor = Document::from_representation(&cbor, TYPE_DID_CBOR).unwrap();
+        assert_eq!(doc_cbor.to_data_model().unwrap(), data_model);
+
+        let json_ld = doc.to_representation(TYPE_DID_LD_JSON).unwrap();
+        let doc_json_ld = Document::from_representation(&json_ld, TYPE_DID_LD_JSON).unwrap();
+        assert_eq!(
+            serde_json::to_value(doc_json_ld).unwrap(),
+            serde_json::to_value(&doc).unwrap()
+        );
+
+        assert!(matches!(
+            doc.to_representation("text/html"),
+            Err(Error::RepresentationNotSupported)
+        ));
+    }
+
+    #[async_std::test]
+    async fn resolve_representation_accept() {
+        use crate::did::example::DIDExample;
+        use crate::did_resolve::ERROR_REPRESENTATION_NOT_SUPPORTED;
+        for content_type in &[TYPE_DID_LD_JSON, TYPE_DID_JSON, TYPE_DID_CBOR] {
+            let input_meta = ResolutionInputMetadata {
+                accept: Some(content_type.to_string()),
+                ..Default::default()
+            };
+            let (res_meta, representation, _) = DIDExample
+                .resolve_representation("did:example:foo", &input_meta)
+                .await;
+            assert_eq!(res_meta.error, None);
+            assert_eq!(res_meta.content_type.as_deref(), Some(*content_type));
+            let doc = Document::from_representation(&representation, content_type).unwrap();
+            assert_eq!(doc.id, "did:example:foo");
+        }
+        for accept in &[TYPE_JSON, "*/*"] {
+            let input_meta = ResolutionInputMetadata {
+                accept: Some(accept.to_string()),
+                ..Default::default()
+            };
+            let (res_meta, representation, _) = DIDExample
+                .resolve_representation("did:example:foo", &input_meta)
+                .await;
+            assert_eq!(res_meta.error, None);
+            assert_eq!(res_meta.content_type.as_deref(), Some(TYPE_DID_JSON));
+            let value: Value = serde_json::from_slice(&representation).unwrap();
+            assert!(value.get("@context").is_none());
+        }
+        let input_meta = ResolutionInputMetadata {
+            accept: Some("text/html".to_string()),
+            ..Default::default()
+        };
+        let (res_meta, _, _) = DIDExample
+            .resolve_representation("did:example:foo", &input_meta)
+            .await;
+        assert_eq!(
+            res_meta.error.as_deref(),
+            Some(ERROR_REPRESENTATION_NOT_SUPPORTED)
+        );
+    }
 }
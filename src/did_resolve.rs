@@ -1,5 +1,10 @@
 use async_trait::async_trait;
 use chrono::prelude::{DateTime, Utc};
+use chrono::Duration;
+use futures::future::join_all;
+#[cfg(not(target_arch = "wasm32"))]
+use futures::future::{BoxFuture, FutureExt, Shared};
+use futures::stream::{FuturesUnordered, StreamExt};
 #[cfg(feature = "http-did")]
 use hyper::{header, Client, Request, StatusCode, Uri};
 #[cfg(feature = "http-did")]
@@ -10,20 +15,21 @@ use serde_json::Value;
 use serde_urlencoded;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryFrom;
+use std::sync::{Mutex, MutexGuard};
 
 // https://w3c-ccg.github.io/did-resolution/
 use crate::did::{
-    DIDMethod, DIDParameters, Document, PrimaryDIDURL, Resource, ServiceEndpoint,
+    DIDMethod, DIDParameters, Document, PrimaryDIDURL, Resource, Service, ServiceEndpoint,
     VerificationMethod, VerificationMethodMap, VerificationRelationship, DIDURL,
 };
 use crate::error::Error;
 use crate::jsonld::DID_RESOLUTION_V1_CONTEXT;
-use crate::one_or_many::OneOrMany;
 
 pub const TYPE_JSON: &str = "application/json";
 pub const TYPE_LD_JSON: &str = "application/ld+json";
 pub const TYPE_DID_JSON: &str = "application/did+json";
 pub const TYPE_DID_LD_JSON: &str = "application/did+ld+json";
+pub const TYPE_DID_CBOR: &str = "application/did+cbor";
 pub const TYPE_URL: &str = "text/url";
 pub const ERROR_INVALID_DID: &str = "invalidDid";
 pub const ERROR_INVALID_DID_URL: &str = "invalidDidUrl";
@@ -31,6 +37,10 @@ pub const ERROR_UNAUTHORIZED: &str = "unauthorized";
 pub const ERROR_NOT_FOUND: &str = "notFound";
 pub const ERROR_METHOD_NOT_SUPPORTED: &str = "methodNotSupported";
 pub const ERROR_REPRESENTATION_NOT_SUPPORTED: &str = "representationNotSupported";
+/// Error for a DID resolver taking too long, e.g. in a [`CompositeResolver`] (not standard)
+pub const ERROR_TIMEOUT: &str = "timeout";
+/// Error for a resolved DID document not conforming to DID Core (not standard)
+pub const ERROR_INVALID_DID_DOCUMENT: &str = "invalidDidDocument";
 pub const TYPE_DID_RESOLUTION: &str =
     "application/ld+json;profile=\"https://w3id.org/did-resolution\";charset=utf-8";
 
@@ -83,6 +93,8 @@ pub struct DocumentMetadata {
     pub updated: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub deactivated: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
     #[serde(flatten)]
     pub property_set: Option<HashMap<String, Metadata>>,
 }
@@ -98,6 +110,9 @@ pub struct DereferencingInputMetadata {
     pub service_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub follow_redirect: Option<bool>,
+    /// Bypass caches of dereferencing results (not standard)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_cache: Option<bool>,
     #[serde(flatten)]
     pub property_set: Option<HashMap<String, Metadata>>,
 }
@@ -259,19 +274,29 @@ pub trait DIDResolver: Sync {
     ) -> (ResolutionMetadata, Vec<u8>, Option<DocumentMetadata>) {
         // Implement resolveRepresentation in terms of resolve.
         let (mut res_meta, doc, doc_meta) = self.resolve(did, input_metadata).await;
+        // Default to JSON-LD DID document
+        let content_type = input_metadata
+            .accept
+            .as_deref()
+            .map_or(TYPE_DID_LD_JSON, representation_type);
         let doc_representation = match doc {
             None => Vec::new(),
-            Some(doc) => match serde_json::to_vec_pretty(&doc) {
+            Some(doc) => match doc.to_representation(content_type) {
                 Ok(vec) => vec,
+                Err(Error::RepresentationNotSupported) => {
+                    return (
+                        ResolutionMetadata::from_error(ERROR_REPRESENTATION_NOT_SUPPORTED),
+                        Vec::new(),
+                        doc_meta,
+                    );
+                }
                 Err(err) => {
-                    res_meta.error =
-                        Some("Error serializing JSON: ".to_string() + &err.to_string());
+                    res_meta.error = Some(format!("Error producing DID document: {}", err));
                     Vec::new()
                 }
             },
         };
-        // Assume JSON-LD DID document
-        res_meta.content_type = Some(TYPE_DID_LD_JSON.to_string());
+        res_meta.content_type = Some(content_type.to_string());
         (res_meta, doc_representation, doc_meta)
     }
 
@@ -295,6 +320,54 @@ pub trait DIDResolver: Sync {
     }
 }
 
+/// Get the DID document representation type for an `accept` input metadata property. Requests
+/// for plain JSON or any media type get the `application/did+json` representation.
+fn representation_type(accept: &str) -> &str {
+    match accept {
+        TYPE_JSON | "*/*" => TYPE_DID_JSON,
+        _ => accept,
+    }
+}
+
+/// Convert a DID document representation to the representation requested in the `accept`
+/// resolution input metadata property, if any. Returns resolution metadata with the content type
+/// of the resulting representation, or an error.
+pub fn convert_representation(
+    representation: Vec<u8>,
+    content_type: &str,
+    input_metadata: &ResolutionInputMetadata,
+) -> (ResolutionMetadata, Vec<u8>) {
+    let accept = match input_metadata.accept.as_deref().map(representation_type) {
+        Some(accept) if accept != content_type => accept,
+        _ => {
+            let res_meta = ResolutionMetadata {
+                content_type: Some(content_type.to_string()),
+                ..Default::default()
+            };
+            return (res_meta, representation);
+        }
+    };
+    let result = Document::from_representation(&representation, content_type)
+        .and_then(|doc| doc.to_representation(accept));
+    match result {
+        Ok(representation) => {
+            let res_meta = ResolutionMetadata {
+                content_type: Some(accept.to_string()),
+                ..Default::default()
+            };
+            (res_meta, representation)
+        }
+        Err(Error::RepresentationNotSupported) => (
+            ResolutionMetadata::from_error(ERROR_REPRESENTATION_NOT_SUPPORTED),
+            Vec::new(),
+        ),
+        Err(err) => (
+            ResolutionMetadata::from_error(&format!("Unable to convert DID document: {}", err)),
+            Vec::new(),
+        ),
+    }
+}
+
 /// Dereference a DID URL
 ///
 /// <https://w3c.github.io/did-core/#did-url-dereferencing>
@@ -315,7 +388,7 @@ pub async fn dereference(
         }
     };
     // 1
-    let did_res_input_metadata: ResolutionInputMetadata = match did_url.query.as_ref() {
+    let mut did_res_input_metadata: ResolutionInputMetadata = match did_url.query.as_ref() {
         Some(query) => match serde_urlencoded::from_str(query) {
             Ok(meta) => meta,
             Err(error) => {
@@ -328,6 +401,9 @@ pub async fn dereference(
         },
         None => ResolutionInputMetadata::default(),
     };
+    if did_url_dereferencing_input_metadata.no_cache == Some(true) {
+        did_res_input_metadata.no_cache = Some(true);
+    }
 
     let (did_doc_res_meta, did_doc_opt, did_doc_meta_opt) = resolver
         .resolve(&did_url.did, &did_res_input_metadata)
@@ -386,14 +462,14 @@ async fn dereference_primary_resource(
     did_url_dereferencing_input_metadata: &DereferencingInputMetadata,
     res_meta: &ResolutionMetadata,
     did_doc: Document,
-    _did_doc_meta: &DocumentMetadata,
+    did_doc_meta: &DocumentMetadata,
 ) -> (DereferencingMetadata, Content, ContentMetadata) {
     let parameters: DIDParameters = match primary_did_url.query {
         Some(ref query) => match serde_urlencoded::from_str(query) {
             Ok(params) => params,
-            Err(err) => {
+            Err(_) => {
                 return (
-                    DereferencingMetadata::from(Error::from(err)),
+                    DereferencingMetadata::from_error(ERROR_INVALID_DID_URL),
                     Content::Null,
                     ContentMetadata::default(),
                 );
@@ -401,6 +477,15 @@ async fn dereference_primary_resource(
         },
         None => Default::default(),
     };
+    // Check that the DID document resolved is the version requested, in case the DID method
+    // does not support versionId or versionTime.
+    if let Err(error) = check_requested_version(&parameters, did_doc_meta) {
+        return (
+            DereferencingMetadata::from_error(error),
+            Content::Null,
+            ContentMetadata::default(),
+        );
+    }
     // 1
     if let Some(ref service) = parameters.service {
         // 1.1
@@ -408,35 +493,27 @@ async fn dereference_primary_resource(
             Some(service) => service,
             None => {
                 return (
-                    DereferencingMetadata::from_error("Service not found"),
+                    DereferencingMetadata::from_error(ERROR_NOT_FOUND),
                     Content::Null,
                     ContentMetadata::default(),
                 );
             }
         };
-        // 1.2, 1.2.1
-        // TODO: support these other cases?
-        let input_service_endpoint_url = match &service.service_endpoint {
-            None => {
-                return (
-                    DereferencingMetadata::from_error("Missing service endpoint"),
-                    Content::Null,
-                    ContentMetadata::default(),
-                );
-            }
-            Some(OneOrMany::One(ServiceEndpoint::URI(uri))) => uri,
-            Some(OneOrMany::One(ServiceEndpoint::Map(_))) => {
+        if let Some(ref service_type) = did_url_dereferencing_input_metadata.service_type {
+            if !service.type_.any(|type_| type_ == service_type) {
                 return (
-                    DereferencingMetadata::from_error("serviceEndpoint map not supported"),
+                    DereferencingMetadata::from_error(ERROR_NOT_FOUND),
                     Content::Null,
                     ContentMetadata::default(),
                 );
             }
-            Some(OneOrMany::Many(_)) => {
+        }
+        // 1.2, 1.2.1
+        let input_service_endpoint_url = match select_service_endpoint_url(service) {
+            Ok(url) => url,
+            Err(err) => {
                 return (
-                    DereferencingMetadata::from_error(
-                        "Multiple serviceEndpoint properties not supported",
-                    ),
+                    DereferencingMetadata::from_error(err),
                     Content::Null,
                     ContentMetadata::default(),
                 );
@@ -469,8 +546,24 @@ async fn dereference_primary_resource(
             ContentMetadata::default(),
         );
     }
+    // https://www.w3.org/TR/did-core/#did-parameters
+    // relativeRef identifies a resource at a service endpoint selected using service.
+    if parameters.relative_ref.is_some() {
+        return (
+            DereferencingMetadata::from_error(ERROR_INVALID_DID_URL),
+            Content::Null,
+            ContentMetadata::default(),
+        );
+    }
     // 2
-    if primary_did_url.path.is_none() && primary_did_url.query.is_none() {
+    // A query of only versionId and/or versionTime selects the version of the DID document,
+    // which was already passed to the resolve function.
+    let selects_version_only = parameters.hashlink.is_none()
+        && parameters
+            .property_set
+            .as_ref()
+            .map_or(true, |property_set| property_set.is_empty());
+    if primary_did_url.path.is_none() && (primary_did_url.query.is_none() || selects_version_only) {
         // 2.1
         // Add back contentType, since the resolve function does not include it, but we need
         // it to dereference the secondary resource.
@@ -482,7 +575,7 @@ async fn dereference_primary_resource(
         return (
             deref_meta,
             Content::DIDDocument(did_doc),
-            ContentMetadata::default(),
+            ContentMetadata::DIDDocument(did_doc_meta.clone()),
         );
     }
     // 3
@@ -558,7 +651,9 @@ async fn dereference_secondary_resource(
                     ..Default::default()
                 },
                 Content::Object(object),
-                ContentMetadata::default(),
+                // Keep the metadata of the DID document containing the object, e.g. so that
+                // verifiers can tell if the DID is deactivated.
+                content_meta,
             );
         }
         Content::URL(mut url) => {
@@ -596,6 +691,76 @@ async fn dereference_secondary_resource(
     }
 }
 
+/// Check if a resolved DID document is the version requested by the `versionId` and
+/// `versionTime` DID parameters, according to its metadata. Returns `methodNotSupported` if
+/// `versionId` is requested but the DID method does not report version ids, or `notFound` if
+/// the DID document is not the requested version.
+fn check_requested_version(
+    parameters: &DIDParameters,
+    doc_meta: &DocumentMetadata,
+) -> Result<(), &'static str> {
+    if let Some(ref version_id) = parameters.version_id {
+        match doc_meta.version_id {
+            None => return Err(ERROR_METHOD_NOT_SUPPORTED),
+            Some(ref doc_version_id) if doc_version_id != version_id => {
+                return Err(ERROR_NOT_FOUND)
+            }
+            Some(_) => {}
+        }
+    }
+    if let Some(version_time) = parameters.version_time {
+        let created_after = doc_meta.created.map_or(false, |time| time > version_time);
+        let updated_after = doc_meta.updated.map_or(false, |time| time > version_time);
+        if created_after || updated_after {
+            return Err(ERROR_NOT_FOUND);
+        }
+    }
+    Ok(())
+}
+
+/// Select the URL of a service endpoint to use for service endpoint construction: the first one
+/// given as a URI.
+fn select_service_endpoint_url(service: &Service) -> Result<&str, &'static str> {
+    let service_endpoints = match service.service_endpoint {
+        Some(ref service_endpoints) => service_endpoints,
+        None => return Err("Missing service endpoint"),
+    };
+    service_endpoints
+        .into_iter()
+        .find_map(|service_endpoint| match service_endpoint {
+            ServiceEndpoint::URI(uri) => Some(uri.as_str()),
+            ServiceEndpoint::Map(_) => None,
+        })
+        .ok_or("serviceEndpoint map not supported")
+}
+
+/// Names of DID parameters, which are not passed on to a service endpoint URL.
+const DID_PARAMETER_NAMES: [&str; 6] = [
+    "service",
+    "relativeRef",
+    "relative-ref",
+    "versionId",
+    "versionTime",
+    "hl",
+];
+
+/// Remove DID parameters from the query component of a DID URL.
+fn remove_did_parameters(query: &str) -> Option<String> {
+    let query = query
+        .split('&')
+        .filter(|param| {
+            let name = param.split('=').next().unwrap_or_default();
+            !DID_PARAMETER_NAMES.contains(&name)
+        })
+        .collect::<Vec<&str>>()
+        .join("&");
+    if query.is_empty() {
+        None
+    } else {
+        Some(query)
+    }
+}
+
 /// <https://w3c-ccg.github.io/did-resolution/#service-endpoint-construction>
 fn construct_service_endpoint(
     did_url: &DIDURL,
@@ -630,7 +795,7 @@ fn construct_service_endpoint(
         did_url_query = parts.next().map(|q| q.to_owned());
     } else {
         did_url_path = did_url.path_abempty.to_owned();
-        did_url_query = did_url.query.to_owned();
+        did_url_query = did_url.query.as_deref().and_then(remove_did_parameters);
         // TODO: do something with the DID URL query that is being ignored in favor of the
         // relativeRef query
     }
@@ -667,6 +832,12 @@ fn construct_service_endpoint(
     Ok(output_url)
 }
 
+/// Characters to percent-encode in a DID or DID URL in the path of a DID Resolution HTTP(S)
+/// request. The query string of the request is used for input metadata.
+#[cfg(feature = "http")]
+const DID_URL_ENCODE_SET: &percent_encoding::AsciiSet =
+    &percent_encoding::CONTROLS.add(b'%').add(b'?').add(b'#');
+
 #[cfg(feature = "http")]
 #[derive(Debug, Clone, Default)]
 pub struct HTTPDIDResolver {
@@ -745,7 +916,7 @@ impl DIDResolver for HTTPDIDResolver {
             }
         };
         let did_urlencoded =
-            percent_encoding::utf8_percent_encode(&did, percent_encoding::CONTROLS).to_string();
+            percent_encoding::utf8_percent_encode(&did, DID_URL_ENCODE_SET).to_string();
         let mut url = self.endpoint.clone() + &did_urlencoded;
         if !querystring.is_empty() {
             url.push('?');
@@ -897,11 +1068,9 @@ impl DIDResolver for HTTPDIDResolver {
                 ))
             }
         };
-        let did_url_urlencoded = percent_encoding::utf8_percent_encode(
-            &primary_did_url.to_string(),
-            percent_encoding::CONTROLS,
-        )
-        .to_string();
+        let did_url_urlencoded =
+            percent_encoding::utf8_percent_encode(&primary_did_url.to_string(), DID_URL_ENCODE_SET)
+                .to_string();
         let mut url = self.endpoint.clone() + &did_url_urlencoded;
         if !querystring.is_empty() {
             url.push('?');
@@ -1134,124 +1303,1012 @@ impl<'a> DIDResolver for SeriesResolver<'a> {
     }
 }
 
-// TODO: replace with Try trait implementation once stabilized.
-// <https://github.com/rust-lang/rust/issues/84277>
-pub(crate) async fn easy_resolve(did: &str, resolver: &dyn DIDResolver) -> Result<Document, Error> {
-    let (res_meta, doc_opt, _meta) = resolver
-        .resolve(did, &ResolutionInputMetadata::default())
-        .await;
-    if let Some(err) = res_meta.error {
-        return Err(Error::UnableToResolve(err));
-    }
-    let doc = doc_opt
-        .ok_or_else(|| Error::UnableToResolve(format!("Missing document for DID: {}", did)))?;
-    Ok(doc)
+/// How a [`CompositeResolver`] combines its resolvers
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum CompositeMode {
+    /// Try resolvers in order, until one supports the DID method and does not time out.
+    Series,
+    /// Try resolvers in parallel. The first successful result is used.
+    FirstSuccess,
+    /// Try resolvers in parallel. All resolvers must succeed and return the same DID document.
+    AllAgree,
 }
 
-/// Get the resolved verification method maps for a given DID (including its controllers,
-/// recursively) and verification relationship (proof purpose).
-pub async fn get_verification_methods(
-    did: &str,
-    verification_relationship: VerificationRelationship,
-    resolver: &dyn DIDResolver,
-) -> Result<HashMap<String, VerificationMethodMap>, Error> {
-    // Resolve DIDs, recursing through DID controllers.
-    let mut did_docs: HashMap<String, Document> = HashMap::new();
-    let mut dids_to_resolve = vec![did.to_string()];
-    while let Some(did) = dids_to_resolve.pop() {
-        if !did_docs.contains_key(&did) {
-            let doc = easy_resolve(&did, resolver).await?;
-            for controller in doc.controller.iter().flatten() {
-                dids_to_resolve.push(controller.clone());
-            }
-            if did_docs.len() > MAX_CONTROLLERS {
-                return Err(Error::ControllerLimit);
-            }
-            did_docs.insert(did, doc);
+/// A resolver used by a [`CompositeResolver`]
+pub struct ResolverBackend<'a> {
+    /// Name of the resolver, reported in the `resolver` DID resolution metadata property.
+    pub name: String,
+    pub resolver: &'a dyn DIDResolver,
+    /// Maximum time to wait for the resolver to resolve a DID
+    pub timeout: Option<std::time::Duration>,
+    /// DID method names that the resolver is used for. If `None`, the resolver is used for all
+    /// DID methods.
+    pub methods: Option<Vec<String>>,
+}
+
+impl<'a> ResolverBackend<'a> {
+    pub fn new(name: &str, resolver: &'a dyn DIDResolver) -> Self {
+        Self {
+            name: name.to_string(),
+            resolver,
+            timeout: None,
+            methods: None,
         }
     }
-    let mut vm_ids_for_purpose = HashSet::new();
-    let mut vmms_by_id: HashMap<String, VerificationMethodMap> = HashMap::new();
-    for (_did, doc) in did_docs {
-        // Find verification method ids for this proof purpose.
-        let vm_ids = doc
-            .get_verification_method_ids(verification_relationship.clone())
-            .map_err(|e| {
-                Error::UnableToResolve(format!("Unable to get verification method ids: {:?}", e))
-            })?;
-        for id in vm_ids {
-            vm_ids_for_purpose.insert(id);
-        }
-        // Find all verification method maps defined in the resolved DID document.
-        for vm in doc
-            .verification_method
-            .into_iter()
-            .chain(doc.public_key.into_iter())
-            .chain(doc.authentication.into_iter())
-            .chain(doc.assertion_method.into_iter())
-            .chain(doc.key_agreement.into_iter())
-            .chain(doc.capability_invocation.into_iter())
-            .chain(doc.capability_delegation.into_iter())
-            .flatten()
-        {
-            if let VerificationMethod::Map(mut vmm) = vm {
-                let vm_id = vmm.get_id(&doc.id);
-                crate::did::merge_context(&mut vmm.context, &doc.context);
-                vmms_by_id.insert(vm_id, vmm);
-            }
+
+    fn supports_did(&self, did: &str) -> bool {
+        match self.methods {
+            None => true,
+            Some(ref methods) => match did.split(':').nth(1) {
+                Some(method) => methods.iter().any(|m| m == method),
+                None => false,
+            },
         }
     }
-    let mut vmms = HashMap::new();
-    for id in vm_ids_for_purpose {
-        let vmm = if let Some(vmm) = vmms_by_id.remove(&id) {
-            vmm
-        } else {
-            crate::ldp::resolve_vm(&id, resolver).await?
+
+    async fn resolve(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> ResolutionTuple {
+        let resolution = self.resolver.resolve(did, input_metadata);
+        let (mut res_meta, doc_opt, doc_meta_opt) = match self.timeout {
+            None => resolution.await,
+            Some(timeout) => match async_std::future::timeout(timeout, resolution).await {
+                Ok(result) => result,
+                Err(_) => (ResolutionMetadata::from_error(ERROR_TIMEOUT), None, None),
+            },
         };
-        vmms.insert(id, vmm);
+        res_meta
+            .property_set
+            .get_or_insert_with(HashMap::new)
+            .insert("resolver".to_string(), Metadata::String(self.name.clone()));
+        (res_meta, doc_opt, doc_meta_opt)
+    }
+
+    async fn dereference(
+        &self,
+        primary_did_url: &PrimaryDIDURL,
+        input_metadata: &DereferencingInputMetadata,
+    ) -> DereferencingResult {
+        let dereferencing = self.resolver.dereference(primary_did_url, input_metadata);
+        let (mut deref_meta, content, content_meta) = match self.timeout {
+            None => dereferencing.await?,
+            Some(timeout) => match async_std::future::timeout(timeout, dereferencing).await {
+                Ok(result) => result?,
+                Err(_) => (
+                    DereferencingMetadata::from_error(ERROR_TIMEOUT),
+                    Content::Null,
+                    ContentMetadata::default(),
+                ),
+            },
+        };
+        deref_meta
+            .property_set
+            .get_or_insert_with(HashMap::new)
+            .insert("resolver".to_string(), Metadata::String(self.name.clone()));
+        Some((deref_meta, content, content_meta))
     }
-    // TODO: verify VM controller properties.
-    Ok(vmms)
 }
 
-#[cfg(test)]
-mod tests {
-    #[cfg(feature = "http-did")]
-    use hyper::{Body, Response, Server};
-    // use std::future::Future;
+/// Compose multiple DID resolvers, with per-resolver timeouts and DID method routing, in series
+/// or in parallel. See [`CompositeMode`].
+///
+/// The name of the resolver that produced a result is returned in the `resolver` DID resolution
+/// metadata property; in [`CompositeMode::AllAgree`] mode, the names of all the resolvers used are
+/// returned in the `resolvers` property.
+pub struct CompositeResolver<'a> {
+    pub backends: Vec<ResolverBackend<'a>>,
+    pub mode: CompositeMode,
+}
 
-    use super::*;
+impl<'a> CompositeResolver<'a> {
+    pub fn new(mode: CompositeMode) -> Self {
+        Self {
+            backends: Vec::new(),
+            mode,
+        }
+    }
 
-    struct ExampleResolver {}
+    /// Add a resolver backend.
+    pub fn push(&mut self, backend: ResolverBackend<'a>) {
+        self.backends.push(backend);
+    }
 
-    const EXAMPLE_123_ID: &str = "did:example:123";
-    const EXAMPLE_123_JSON: &str = r#"{
-        "@context": "https://www.w3.org/ns/did/v1",
-        "id": "did:example:123",
-        "authentication": [
-            {
-                "id": "did:example:123#z6MkecaLyHuYWkayBDLw5ihndj3T1m6zKTGqau3A51G7RBf3",
-                "type": "Ed25519VerificationKey2018",
-                "controller": "did:example:123",
-                "publicKeyBase58": "AKJP3f7BD6W4iWEQ9jwndVTCBq8ua2Utt8EEjJ6Vxsf"
+    fn backends_for(&self, did: &str) -> Vec<&ResolverBackend<'a>> {
+        self.backends
+            .iter()
+            .filter(|backend| backend.supports_did(did))
+            .collect()
+    }
+}
+
+fn is_method_supported(res_meta: &ResolutionMetadata) -> bool {
+    res_meta.error.as_deref() != Some(ERROR_METHOD_NOT_SUPPORTED)
+}
+
+// A successful resolution has no error, and has a DID document and DID document metadata.
+fn is_success(result: &ResolutionTuple) -> bool {
+    result.0.error.is_none() && result.1.is_some() && result.2.is_some()
+}
+
+// Check if resolution results from different resolvers agree, comparing the DID resolution
+// metadata (except for the name of the resolver), the DID document and the DID document metadata.
+fn is_same_resolution(a: &ResolutionTuple, b: &ResolutionTuple) -> bool {
+    fn res_meta_value(res_meta: &ResolutionMetadata) -> Option<Value> {
+        let mut value = serde_json::to_value(res_meta).ok()?;
+        value.as_object_mut()?.remove("resolver");
+        Some(value)
+    }
+    let (res_meta_a, res_meta_b) = match (res_meta_value(&a.0), res_meta_value(&b.0)) {
+        (Some(res_meta_a), Some(res_meta_b)) => (res_meta_a, res_meta_b),
+        _ => return false,
+    };
+    let (doc_meta_a, doc_meta_b) = match (serde_json::to_value(&a.2), serde_json::to_value(&b.2)) {
+        (Ok(doc_meta_a), Ok(doc_meta_b)) => (doc_meta_a, doc_meta_b),
+        _ => return false,
+    };
+    res_meta_a == res_meta_b && a.1 == b.1 && doc_meta_a == doc_meta_b
+}
+
+// A resolver that does not implement dereferencing is treated as not supporting the DID method.
+fn is_deref_method_supported(result: &DereferencingResult) -> bool {
+    match result {
+        Some((deref_meta, _, _)) => deref_meta.error.as_deref() != Some(ERROR_METHOD_NOT_SUPPORTED),
+        None => false,
+    }
+}
+
+fn deref_error(result: &DereferencingResult) -> Option<&str> {
+    result.as_ref()?.0.error.as_deref()
+}
+
+// Check if dereferencing results from different resolvers agree, comparing the dereferencing
+// metadata (except for the name of the resolver), the content and the content metadata.
+fn is_same_dereference(a: &DereferencingResult, b: &DereferencingResult) -> bool {
+    fn deref_meta_value(deref_meta: &DereferencingMetadata) -> Option<Value> {
+        let mut value = serde_json::to_value(deref_meta).ok()?;
+        value.as_object_mut()?.remove("resolver");
+        Some(value)
+    }
+    let (a, b) = match (a, b) {
+        (Some(a), Some(b)) => (a, b),
+        (None, None) => return true,
+        _ => return false,
+    };
+    let (deref_meta_a, deref_meta_b) = match (deref_meta_value(&a.0), deref_meta_value(&b.0)) {
+        (Some(deref_meta_a), Some(deref_meta_b)) => (deref_meta_a, deref_meta_b),
+        _ => return false,
+    };
+    let (content_meta_a, content_meta_b) =
+        match (serde_json::to_value(&a.2), serde_json::to_value(&b.2)) {
+            (Ok(content_meta_a), Ok(content_meta_b)) => (content_meta_a, content_meta_b),
+            _ => return false,
+        };
+    deref_meta_a == deref_meta_b && a.1 == b.1 && content_meta_a == content_meta_b
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<'a> DIDResolver for CompositeResolver<'a> {
+    async fn resolve(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        let backends = self.backends_for(did);
+        let method_not_supported = (
+            ResolutionMetadata::from_error(ERROR_METHOD_NOT_SUPPORTED),
+            None,
+            None,
+        );
+        match self.mode {
+            CompositeMode::Series => {
+                let mut timeout_result = None;
+                for backend in backends {
+                    let result = backend.resolve(did, input_metadata).await;
+                    match result.0.error.as_deref() {
+                        Some(ERROR_METHOD_NOT_SUPPORTED) => {}
+                        Some(ERROR_TIMEOUT) => {
+                            timeout_result.get_or_insert(result);
+                        }
+                        _ => return result,
+                    }
+                }
+                timeout_result.unwrap_or(method_not_supported)
             }
-        ],
-        "capabilityInvocation": [
-            {
-                "id": "did:example:123#z6MkhdmzFu659ZJ4XKj31vtEDmjvsi5yDZG5L7Caz63oP39k",
-                "type": "Ed25519VerificationKey2018",
-                "controller": "did:example:123",
-                "publicKeyBase58": "4BWwfeqdp1obQptLLMvPNgBw48p7og1ie6Hf9p5nTpNN"
+            CompositeMode::FirstSuccess => {
+                let mut resolutions: FuturesUnordered<_> =
+                    backends
+                        .iter()
+                        .enumerate()
+                        .map(|(i, backend)| async move {
+                            (i, backend.resolve(did, input_metadata).await)
+                        })
+                        .collect();
+                // Keep the error from the first resolver in order, in case none succeed.
+                let mut first_error: Option<(usize, ResolutionTuple)> = None;
+                while let Some((i, result)) = resolutions.next().await {
+                    if is_success(&result) {
+                        return result;
+                    }
+                    if !is_method_supported(&result.0) {
+                        continue;
+                    }
+                    match first_error {
+                        Some((j, _)) if j < i => {}
+                        _ => first_error = Some((i, result)),
+                    }
+                }
+                match first_error {
+                    Some((_, result)) => result,
+                    None => method_not_supported,
+                }
             }
-        ],
-        "capabilityDelegation": [
-            {
-                "id": "did:example:123#z6Mkw94ByR26zMSkNdCUi6FNRsWnc2DFEeDXyBGJ5KTzSWyi",
-                "type": "Ed25519VerificationKey2018",
-                "controller": "did:example:123",
-                "publicKeyBase58": "Hgo9PAmfeoxHG8Mn2XHXamxnnSwPpkyBHAMNF3VyXJCL"
+            CompositeMode::AllAgree => {
+                let results = join_all(
+                    backends
+                        .iter()
+                        .map(|backend| backend.resolve(did, input_metadata)),
+                )
+                .await;
+                let mut names = Vec::new();
+                let mut agreed: Option<ResolutionTuple> = None;
+                for (backend, result) in backends.iter().zip(results) {
+                    if !is_method_supported(&result.0) {
+                        continue;
+                    }
+                    if result.0.error.is_some() {
+                        return result;
+                    }
+                    names.push(Metadata::String(backend.name.clone()));
+                    match agreed {
+                        None => agreed = Some(result),
+                        Some(ref agreed) if is_same_resolution(agreed, &result) => {}
+                        Some(_) => {
+                            return (
+                                ResolutionMetadata::from_error(&format!(
+                                    "Resolvers disagree: {}",
+                                    backend.name
+                                )),
+                                None,
+                                None,
+                            );
+                        }
+                    }
+                }
+                match agreed {
+                    Some((mut res_meta, doc_opt, doc_meta_opt)) => {
+                        let property_set = res_meta.property_set.get_or_insert_with(HashMap::new);
+                        property_set.remove("resolver");
+                        property_set.insert("resolvers".to_string(), Metadata::List(names));
+                        (res_meta, doc_opt, doc_meta_opt)
+                    }
+                    None => method_not_supported,
+                }
             }
-        ],
+        }
+    }
+
+    async fn dereference(
+        &self,
+        primary_did_url: &PrimaryDIDURL,
+        input_metadata: &DereferencingInputMetadata,
+    ) -> Option<(DereferencingMetadata, Content, ContentMetadata)> {
+        let backends = self.backends_for(&primary_did_url.did);
+        match self.mode {
+            CompositeMode::Series => {
+                let mut timeout_result = None;
+                for backend in backends {
+                    let result = backend.dereference(primary_did_url, input_metadata).await;
+                    if !is_deref_method_supported(&result) {
+                        continue;
+                    }
+                    if deref_error(&result) == Some(ERROR_TIMEOUT) {
+                        timeout_result.get_or_insert(result);
+                        continue;
+                    }
+                    return result;
+                }
+                timeout_result.flatten()
+            }
+            CompositeMode::FirstSuccess => {
+                let mut dereferences: FuturesUnordered<_> = backends
+                    .iter()
+                    .enumerate()
+                    .map(|(i, backend)| async move {
+                        (
+                            i,
+                            backend.dereference(primary_did_url, input_metadata).await,
+                        )
+                    })
+                    .collect();
+                // Keep the error from the first resolver in order, in case none succeed.
+                let mut first_error: Option<(usize, DereferencingResult)> = None;
+                while let Some((i, result)) = dereferences.next().await {
+                    if !is_deref_method_supported(&result) {
+                        continue;
+                    }
+                    if deref_error(&result).is_none() {
+                        return result;
+                    }
+                    match first_error {
+                        Some((j, _)) if j < i => {}
+                        _ => first_error = Some((i, result)),
+                    }
+                }
+                first_error.and_then(|(_, result)| result)
+            }
+            CompositeMode::AllAgree => {
+                let results = join_all(
+                    backends
+                        .iter()
+                        .map(|backend| backend.dereference(primary_did_url, input_metadata)),
+                )
+                .await;
+                let mut names = Vec::new();
+                let mut agreed: Option<DereferencingResult> = None;
+                for (backend, result) in backends.iter().zip(results) {
+                    if !is_deref_method_supported(&result) {
+                        continue;
+                    }
+                    if deref_error(&result).is_some() {
+                        return result;
+                    }
+                    names.push(Metadata::String(backend.name.clone()));
+                    match agreed {
+                        None => agreed = Some(result),
+                        Some(ref agreed) if is_same_dereference(agreed, &result) => {}
+                        Some(_) => {
+                            return Some((
+                                DereferencingMetadata::from_error(&format!(
+                                    "Resolvers disagree: {}",
+                                    backend.name
+                                )),
+                                Content::Null,
+                                ContentMetadata::default(),
+                            ));
+                        }
+                    }
+                }
+                let (mut deref_meta, content, content_meta) = agreed.flatten()?;
+                let property_set = deref_meta.property_set.get_or_insert_with(HashMap::new);
+                property_set.remove("resolver");
+                property_set.insert("resolvers".to_string(), Metadata::List(names));
+                Some((deref_meta, content, content_meta))
+            }
+        }
+    }
+}
+
+/// Resolve DIDs as of a given time, by setting `versionTime` in the DID resolution input
+/// metadata, if it is not already set.
+///
+/// The DID is first resolved without `versionTime`. If that fails, if the DID is deactivated, or
+/// if the DID document has not been updated since the given time, that result is used, so that
+/// deactivation is not hidden by resolving a historical version, and so that DID methods without
+/// support for `versionTime` can still resolve DIDs that have not been updated.
+///
+/// DID URLs are dereferenced with `versionTime` added to the DID URL query, if neither
+/// `versionTime` nor `versionId` is already in the query, and if resolving the DID in the same way
+/// would use `versionTime`.
+pub struct VersionTimeResolver<'a> {
+    pub resolver: &'a dyn DIDResolver,
+    pub version_time: DateTime<Utc>,
+}
+
+impl<'a> VersionTimeResolver<'a> {
+    pub fn new(resolver: &'a dyn DIDResolver, version_time: DateTime<Utc>) -> Self {
+        Self {
+            resolver,
+            version_time,
+        }
+    }
+
+    // Check if a resolution without `versionTime` should be repeated with `versionTime`: the
+    // resolution succeeded, and the DID is not deactivated and may have been updated since the
+    // given time.
+    fn should_pin(
+        &self,
+        res_meta: &ResolutionMetadata,
+        doc_meta_opt: &Option<DocumentMetadata>,
+    ) -> bool {
+        if res_meta.error.is_some() {
+            return false;
+        }
+        match doc_meta_opt {
+            Some(doc_meta) if doc_meta.deactivated == Some(true) => false,
+            Some(doc_meta) => !matches!(
+                doc_meta.updated.or(doc_meta.created),
+                Some(updated) if updated <= self.version_time
+            ),
+            None => true,
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<'a> DIDResolver for VersionTimeResolver<'a> {
+    async fn resolve(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        if input_metadata.version_id.is_some() || input_metadata.version_time.is_some() {
+            return self.resolver.resolve(did, input_metadata).await;
+        }
+        let (res_meta, doc_opt, doc_meta_opt) = self.resolver.resolve(did, input_metadata).await;
+        if !self.should_pin(&res_meta, &doc_meta_opt) {
+            return (res_meta, doc_opt, doc_meta_opt);
+        }
+        let input_metadata = ResolutionInputMetadata {
+            version_time: Some(
+                self.version_time
+                    .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+            ),
+            ..input_metadata.clone()
+        };
+        self.resolver.resolve(did, &input_metadata).await
+    }
+
+    async fn dereference(
+        &self,
+        primary_did_url: &PrimaryDIDURL,
+        input_metadata: &DereferencingInputMetadata,
+    ) -> Option<(DereferencingMetadata, Content, ContentMetadata)> {
+        let has_version = primary_did_url.query.as_ref().map_or(false, |query| {
+            query
+                .split('&')
+                .any(|param| param.starts_with("versionTime=") || param.starts_with("versionId="))
+        });
+        if has_version {
+            return self
+                .resolver
+                .dereference(primary_did_url, input_metadata)
+                .await;
+        }
+        let (res_meta, _, doc_meta_opt) = self
+            .resolver
+            .resolve(&primary_did_url.did, &ResolutionInputMetadata::default())
+            .await;
+        if !self.should_pin(&res_meta, &doc_meta_opt) {
+            return self
+                .resolver
+                .dereference(primary_did_url, input_metadata)
+                .await;
+        }
+        let version_time = serde_urlencoded::to_string(&[(
+            "versionTime",
+            self.version_time
+                .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+        )])
+        .ok()?;
+        let query = match primary_did_url.query {
+            Some(ref query) if !query.is_empty() => format!("{}&{}", query, version_time),
+            _ => version_time,
+        };
+        let primary_did_url = PrimaryDIDURL {
+            query: Some(query),
+            ..primary_did_url.clone()
+        };
+        self.resolver
+            .dereference(&primary_did_url, input_metadata)
+            .await
+    }
+}
+
+type ResolutionTuple = (
+    ResolutionMetadata,
+    Option<Document>,
+    Option<DocumentMetadata>,
+);
+type RepresentationTuple = (ResolutionMetadata, Vec<u8>, Option<DocumentMetadata>);
+type DereferencingResult = Option<(DereferencingMetadata, Content, ContentMetadata)>;
+
+#[cfg(not(target_arch = "wasm32"))]
+type InFlight<'a, T> = Mutex<HashMap<String, Shared<BoxFuture<'a, T>>>>;
+
+struct CacheEntry<T> {
+    value: T,
+    time: DateTime<Utc>,
+}
+
+/// Cache a DID resolver's results.
+///
+/// Successful DID resolution (of documents or representations) and DID URL dereferencing results
+/// are cached for a given time to live, up to a given number of entries per kind of result.
+/// Resolution or dereferencing with the `noCache` input metadata property bypasses the cache.
+/// Concurrent resolutions or dereferencings for the same input share a single call to the
+/// underlying resolver, except on WASM.
+pub struct CachingResolver<'a> {
+    pub resolver: &'a dyn DIDResolver,
+    pub ttl: Duration,
+    pub max_entries: usize,
+    resolutions: Mutex<HashMap<String, CacheEntry<ResolutionTuple>>>,
+    representations: Mutex<HashMap<String, CacheEntry<RepresentationTuple>>>,
+    dereferences: Mutex<HashMap<String, CacheEntry<DereferencingResult>>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    resolutions_in_flight: InFlight<'a, ResolutionTuple>,
+    #[cfg(not(target_arch = "wasm32"))]
+    representations_in_flight: InFlight<'a, RepresentationTuple>,
+    #[cfg(not(target_arch = "wasm32"))]
+    dereferences_in_flight: InFlight<'a, DereferencingResult>,
+}
+
+// Lock a mutex, ignoring poisoning, since cache entries are only ever replaced whole.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+// Await a lookup, sharing it with concurrent lookups for the same key. When done, the entry is
+// removed only if it is still for this lookup, so that a late waiter does not remove a newer one.
+#[cfg(not(target_arch = "wasm32"))]
+async fn run_shared<'a, T: Clone>(
+    in_flight: &InFlight<'a, T>,
+    key: &str,
+    lookup: impl FnOnce() -> BoxFuture<'a, T>,
+) -> T {
+    let future = lock(in_flight)
+        .entry(key.to_string())
+        .or_insert_with(|| lookup().shared())
+        .clone();
+    let result = future.clone().await;
+    let mut in_flight = lock(in_flight);
+    if in_flight
+        .get(key)
+        .map_or(false, |current| current.ptr_eq(&future))
+    {
+        in_flight.remove(key);
+    }
+    result
+}
+
+fn cache_get<T: Clone>(
+    cache: &Mutex<HashMap<String, CacheEntry<T>>>,
+    key: &str,
+    ttl: Duration,
+) -> Option<T> {
+    let cache = lock(cache);
+    let entry = cache.get(key)?;
+    if Utc::now() - entry.time >= ttl {
+        return None;
+    }
+    Some(entry.value.clone())
+}
+
+fn cache_insert<T>(
+    cache: &Mutex<HashMap<String, CacheEntry<T>>>,
+    key: String,
+    value: T,
+    ttl: Duration,
+    max_entries: usize,
+) {
+    if max_entries == 0 {
+        return;
+    }
+    let now = Utc::now();
+    let mut cache = lock(cache);
+    if cache.len() >= max_entries && !cache.contains_key(&key) {
+        cache.retain(|_, entry| now - entry.time < ttl);
+    }
+    if cache.len() >= max_entries && !cache.contains_key(&key) {
+        let oldest_key = cache
+            .iter()
+            .min_by_key(|(_, entry)| entry.time)
+            .map(|(key, _)| key.clone());
+        if let Some(oldest_key) = oldest_key {
+            cache.remove(&oldest_key);
+        }
+    }
+    cache.insert(key, CacheEntry { value, time: now });
+}
+
+// Build a cache key from a DID or DID URL and input metadata. The input metadata is serialized
+// canonically, with sorted object keys, so that equal inputs give equal keys.
+fn cache_key<T: Serialize>(did_url: &str, input_metadata: &T) -> Option<String> {
+    let input_metadata_key = serde_jcs::to_string(input_metadata).ok()?;
+    Some(format!("{} {}", did_url, input_metadata_key))
+}
+
+// Check if a DID URL string (as used in a cache key) is for the given DID.
+fn is_did_url_for(did_url: &str, did: &str) -> bool {
+    match did_url.strip_prefix(did) {
+        Some(rest) => rest.is_empty() || rest.starts_with(&['/', '?', '#', ' '][..]),
+        None => false,
+    }
+}
+
+impl<'a> CachingResolver<'a> {
+    /// Default time to live for cached results: five minutes
+    pub const DEFAULT_TTL_SECONDS: i64 = 300;
+    /// Default maximum number of cached results
+    pub const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+    /// Cache the results of a resolver, with the default time to live and size limit.
+    pub fn new(resolver: &'a dyn DIDResolver) -> Self {
+        Self::with_limits(
+            resolver,
+            Duration::seconds(Self::DEFAULT_TTL_SECONDS),
+            Self::DEFAULT_MAX_ENTRIES,
+        )
+    }
+
+    /// Cache the results of a resolver, with the given time to live and maximum number of
+    /// entries per kind of result.
+    pub fn with_limits(resolver: &'a dyn DIDResolver, ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            resolver,
+            ttl,
+            max_entries,
+            resolutions: Mutex::new(HashMap::new()),
+            representations: Mutex::new(HashMap::new()),
+            dereferences: Mutex::new(HashMap::new()),
+            #[cfg(not(target_arch = "wasm32"))]
+            resolutions_in_flight: Mutex::new(HashMap::new()),
+            #[cfg(not(target_arch = "wasm32"))]
+            representations_in_flight: Mutex::new(HashMap::new()),
+            #[cfg(not(target_arch = "wasm32"))]
+            dereferences_in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Remove cached results for a DID, including for DID URLs with the DID.
+    pub fn invalidate(&self, did: &str) {
+        lock(&self.resolutions).retain(|key, _| !is_did_url_for(key, did));
+        lock(&self.representations).retain(|key, _| !is_did_url_for(key, did));
+        lock(&self.dereferences).retain(|key, _| !is_did_url_for(key, did));
+    }
+
+    /// Remove all cached results.
+    pub fn clear(&self) {
+        lock(&self.resolutions).clear();
+        lock(&self.representations).clear();
+        lock(&self.dereferences).clear();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn resolve_shared(
+        &self,
+        key: &str,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> ResolutionTuple {
+        run_shared(&self.resolutions_in_flight, key, || {
+            let resolver = self.resolver;
+            let did = did.to_string();
+            let input_metadata = input_metadata.clone();
+            async move { resolver.resolve(&did, &input_metadata).await }.boxed()
+        })
+        .await
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn resolve_shared(
+        &self,
+        _key: &str,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> ResolutionTuple {
+        self.resolver.resolve(did, input_metadata).await
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn resolve_representation_shared(
+        &self,
+        key: &str,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> RepresentationTuple {
+        run_shared(&self.representations_in_flight, key, || {
+            let resolver = self.resolver;
+            let did = did.to_string();
+            let input_metadata = input_metadata.clone();
+            async move { resolver.resolve_representation(&did, &input_metadata).await }.boxed()
+        })
+        .await
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn resolve_representation_shared(
+        &self,
+        _key: &str,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> RepresentationTuple {
+        self.resolver
+            .resolve_representation(did, input_metadata)
+            .await
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn dereference_shared(
+        &self,
+        key: &str,
+        primary_did_url: &PrimaryDIDURL,
+        input_metadata: &DereferencingInputMetadata,
+    ) -> DereferencingResult {
+        run_shared(&self.dereferences_in_flight, key, || {
+            let resolver = self.resolver;
+            let primary_did_url = primary_did_url.clone();
+            let input_metadata = input_metadata.clone();
+            async move {
+                resolver
+                    .dereference(&primary_did_url, &input_metadata)
+                    .await
+            }
+            .boxed()
+        })
+        .await
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn dereference_shared(
+        &self,
+        _key: &str,
+        primary_did_url: &PrimaryDIDURL,
+        input_metadata: &DereferencingInputMetadata,
+    ) -> DereferencingResult {
+        self.resolver
+            .dereference(primary_did_url, input_metadata)
+            .await
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<'a> DIDResolver for CachingResolver<'a> {
+    async fn resolve(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (
+        ResolutionMetadata,
+        Option<Document>,
+        Option<DocumentMetadata>,
+    ) {
+        let key = match cache_key(
+            did,
+            &ResolutionInputMetadata {
+                no_cache: None,
+                ..input_metadata.clone()
+            },
+        ) {
+            Some(key) => key,
+            None => return self.resolver.resolve(did, input_metadata).await,
+        };
+        if input_metadata.no_cache != Some(true) {
+            if let Some(result) = cache_get(&self.resolutions, &key, self.ttl) {
+                return result;
+            }
+        }
+        let result = self.resolve_shared(&key, did, input_metadata).await;
+        if result.0.error.is_none() {
+            cache_insert(
+                &self.resolutions,
+                key,
+                result.clone(),
+                self.ttl,
+                self.max_entries,
+            );
+        }
+        result
+    }
+
+    async fn resolve_representation(
+        &self,
+        did: &str,
+        input_metadata: &ResolutionInputMetadata,
+    ) -> (ResolutionMetadata, Vec<u8>, Option<DocumentMetadata>) {
+        let key = match cache_key(
+            did,
+            &ResolutionInputMetadata {
+                no_cache: None,
+                ..input_metadata.clone()
+            },
+        ) {
+            Some(key) => key,
+            None => {
+                return self
+                    .resolver
+                    .resolve_representation(did, input_metadata)
+                    .await
+            }
+        };
+        if input_metadata.no_cache != Some(true) {
+            if let Some(result) = cache_get(&self.representations, &key, self.ttl) {
+                return result;
+            }
+        }
+        let result = self
+            .resolve_representation_shared(&key, did, input_metadata)
+            .await;
+        if result.0.error.is_none() {
+            cache_insert(
+                &self.representations,
+                key,
+                result.clone(),
+                self.ttl,
+                self.max_entries,
+            );
+        }
+        result
+    }
+
+    async fn dereference(
+        &self,
+        primary_did_url: &PrimaryDIDURL,
+        input_metadata: &DereferencingInputMetadata,
+    ) -> Option<(DereferencingMetadata, Content, ContentMetadata)> {
+        let key = match cache_key(
+            &primary_did_url.to_string(),
+            &DereferencingInputMetadata {
+                no_cache: None,
+                ..input_metadata.clone()
+            },
+        ) {
+            Some(key) => key,
+            None => {
+                return self
+                    .resolver
+                    .dereference(primary_did_url, input_metadata)
+                    .await
+            }
+        };
+        if input_metadata.no_cache != Some(true) {
+            if let Some(result) = cache_get(&self.dereferences, &key, self.ttl) {
+                return result;
+            }
+        }
+        let result = self
+            .dereference_shared(&key, primary_did_url, input_metadata)
+            .await;
+        if let Some((ref deref_meta, _, _)) = result {
+            if deref_meta.error.is_none() {
+                cache_insert(
+                    &self.dereferences,
+                    key,
+                    result.clone(),
+                    self.ttl,
+                    self.max_entries,
+                );
+            }
+        }
+        result
+    }
+
+    fn to_did_method(&self) -> Option<&dyn DIDMethod> {
+        self.resolver.to_did_method()
+    }
+}
+
+// TODO: replace with Try trait implementation once stabilized.
+// <https://github.com/rust-lang/rust/issues/84277>
+pub(crate) async fn easy_resolve(did: &str, resolver: &dyn DIDResolver) -> Result<Document, Error> {
+    let (res_meta, doc_opt, _meta) = resolver
+        .resolve(did, &ResolutionInputMetadata::default())
+        .await;
+    if let Some(err) = res_meta.error {
+        return Err(Error::UnableToResolve(err));
+    }
+    let doc = doc_opt
+        .ok_or_else(|| Error::UnableToResolve(format!("Missing document for DID: {}", did)))?;
+    Ok(doc)
+}
+
+/// Get the resolved verification method maps for a given DID (including its controllers,
+/// recursively) and verification relationship (proof purpose).
+pub async fn get_verification_methods(
+    did: &str,
+    verification_relationship: VerificationRelationship,
+    resolver: &dyn DIDResolver,
+) -> Result<HashMap<String, VerificationMethodMap>, Error> {
+    // Resolve DIDs, recursing through DID controllers.
+    let mut did_docs: HashMap<String, Document> = HashMap::new();
+    let mut dids_to_resolve = vec![did.to_string()];
+    while let Some(did) = dids_to_resolve.pop() {
+        if !did_docs.contains_key(&did) {
+            let doc = easy_resolve(&did, resolver).await?;
+            for controller in doc.controller.iter().flatten() {
+                dids_to_resolve.push(controller.clone());
+            }
+            if did_docs.len() > MAX_CONTROLLERS {
+                return Err(Error::ControllerLimit);
+            }
+            did_docs.insert(did, doc);
+        }
+    }
+    let mut vm_ids_for_purpose = HashSet::new();
+    let mut vmms_by_id: HashMap<String, VerificationMethodMap> = HashMap::new();
+    for (_did, doc) in did_docs {
+        // Find verification method ids for this proof purpose.
+        let vm_ids = doc
+            .get_verification_method_ids(verification_relationship.clone())
+            .map_err(|e| {
+                Error::UnableToResolve(format!("Unable to get verification method ids: {:?}", e))
+            })?;
+        for id in vm_ids {
+            vm_ids_for_purpose.insert(id);
+        }
+        // Find all verification method maps defined in the resolved DID document.
+        for vm in doc
+            .verification_method
+            .into_iter()
+            .chain(doc.public_key.into_iter())
+            .chain(doc.authentication.into_iter())
+            .chain(doc.assertion_method.into_iter())
+            .chain(doc.key_agreement.into_iter())
+            .chain(doc.capability_invocation.into_iter())
+            .chain(doc.capability_delegation.into_iter())
+            .flatten()
+        {
+            if let VerificationMethod::Map(mut vmm) = vm {
+                let vm_id = vmm.get_id(&doc.id);
+                crate::did::merge_context(&mut vmm.context, &doc.context);
+                vmms_by_id.insert(vm_id, vmm);
+            }
+        }
+    }
+    let mut vmms = HashMap::new();
+    for id in vm_ids_for_purpose {
+        let vmm = if let Some(vmm) = vmms_by_id.remove(&id) {
+            vmm
+        } else {
+            crate::ldp::resolve_vm(&id, resolver).await?
+        };
+        vmms.insert(id, vmm);
+    }
+    // TODO: verify VM controller properties.
+    Ok(vmms)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "http-did")]
+    use hyper::{Body, Response, Server};
+    // use std::future::Future;
+
+    use super::*;
+
+    struct ExampleResolver {}
+
+    const EXAMPLE_123_ID: &str = "did:example:123";
+    const EXAMPLE_123_JSON: &str = r#"{
+        "@context": "https://www.w3.org/ns/did/v1",
+        "id": "did:example:123",
+        "authentication": [
+            {
+                "id": "did:example:123#z6MkecaLyHuYWkayBDLw5ihndj3T1m6zKTGqau3A51G7RBf3",
+                "type": "Ed25519VerificationKey2018",
+                "controller": "did:example:123",
+                "publicKeyBase58": "AKJP3f7BD6W4iWEQ9jwndVTCBq8ua2Utt8EEjJ6Vxsf"
+            }
+        ],
+        "capabilityInvocation": [
+            {
+                "id": "did:example:123#z6MkhdmzFu659ZJ4XKj31vtEDmjvsi5yDZG5L7Caz63oP39k",
+                "type": "Ed25519VerificationKey2018",
+                "controller": "did:example:123",
+                "publicKeyBase58": "4BWwfeqdp1obQptLLMvPNgBw48p7og1ie6Hf9p5nTpNN"
+            }
+        ],
+        "capabilityDelegation": [
+            {
+                "id": "did:example:123#z6Mkw94ByR26zMSkNdCUi6FNRsWnc2DFEeDXyBGJ5KTzSWyi",
+                "type": "Ed25519VerificationKey2018",
+                "controller": "did:example:123",
+                "publicKeyBase58": "Hgo9PAmfeoxHG8Mn2XHXamxnnSwPpkyBHAMNF3VyXJCL"
+            }
+        ],
         "assertionMethod": [
             {
                 "id": "did:example:123#z6MkiukuAuQAE8ozxvmahnQGzApvtW7KT5XXKfojjwbdEomY",
@@ -1552,7 +2609,11 @@ mod tests {
                         ..Default::default()
                     },
                     Some(doc),
-                    Some(DocumentMetadata::default()),
+                    Some(DocumentMetadata {
+                        created: Some("2021-01-01T00:00:00Z".parse().unwrap()),
+                        version_id: Some("1".to_string()),
+                        ..Default::default()
+                    }),
                 )
             }
         }
@@ -1615,5 +2676,604 @@ mod tests {
         )
         .await;
         assert_ne!(deref_meta.error, None);
+
+        // Select a service endpoint. The service parameter is not passed to the endpoint.
+        let (deref_meta, content, _content_meta) = dereference(
+            &DerefExampleResolver,
+            "did:example:123456789abcdefghi?service=agent",
+            &DereferencingInputMetadata::default(),
+        )
+        .await;
+        assert_eq!(deref_meta.error, None);
+        assert_eq!(deref_meta.content_type.as_deref(), Some(TYPE_URL));
+        assert_eq!(
+            content,
+            Content::URL("https://agent.example.com/8377464".to_string())
+        );
+
+        // Select a service endpoint with relativeRef, and append a fragment
+        let (deref_meta, content, _content_meta) = dereference(
+            &DerefExampleResolver,
+            "did:example:123456789abcdefghi?service=agent&relativeRef=%2Finbox#latest",
+            &DereferencingInputMetadata::default(),
+        )
+        .await;
+        assert_eq!(deref_meta.error, None);
+        assert_eq!(
+            content,
+            Content::URL("https://agent.example.com/8377464/inbox#latest".to_string())
+        );
+
+        // Service type must match, if given
+        let deref_input_meta = DereferencingInputMetadata {
+            service_type: Some("MessagingService".to_string()),
+            ..Default::default()
+        };
+        let (deref_meta, _content, _content_meta) = dereference(
+            &DerefExampleResolver,
+            "did:example:123456789abcdefghi?service=agent",
+            &deref_input_meta,
+        )
+        .await;
+        assert_eq!(deref_meta.error.as_deref(), Some(ERROR_NOT_FOUND));
+
+        // Unknown service
+        let (deref_meta, _content, _content_meta) = dereference(
+            &DerefExampleResolver,
+            "did:example:123456789abcdefghi?service=nope",
+            &DereferencingInputMetadata::default(),
+        )
+        .await;
+        assert_eq!(deref_meta.error.as_deref(), Some(ERROR_NOT_FOUND));
+
+        // relativeRef requires service
+        let (deref_meta, _content, _content_meta) = dereference(
+            &DerefExampleResolver,
+            "did:example:123456789abcdefghi?relativeRef=%2Finbox",
+            &DereferencingInputMetadata::default(),
+        )
+        .await;
+        assert_eq!(deref_meta.error.as_deref(), Some(ERROR_INVALID_DID_URL));
+
+        // Select a DID document version
+        for did_url in &[
+            "did:example:123456789abcdefghi?versionId=1",
+            "did:example:123456789abcdefghi?versionTime=2022-01-01T00:00:00Z",
+        ] {
+            let (deref_meta, content, _content_meta) =
+                dereference(&DerefExampleResolver, did_url, &Default::default()).await;
+            assert_eq!(deref_meta.error, None);
+            match content {
+                Content::DIDDocument(doc) => assert_eq!(doc.id, DID),
+                _ => panic!("Expected DID document"),
+            }
+        }
+        for did_url in &[
+            "did:example:123456789abcdefghi?versionId=2",
+            "did:example:123456789abcdefghi?versionTime=2020-01-01T00:00:00Z",
+        ] {
+            let (deref_meta, _content, _content_meta) =
+                dereference(&DerefExampleResolver, did_url, &Default::default()).await;
+            assert_eq!(deref_meta.error.as_deref(), Some(ERROR_NOT_FOUND));
+        }
+
+        // A DID method that does not report version ids cannot select a version by id.
+        let (deref_meta, _content, _content_meta) = dereference(
+            &crate::did::example::DIDExample,
+            "did:example:foo?versionId=1",
+            &Default::default(),
+        )
+        .await;
+        assert_eq!(
+            deref_meta.error.as_deref(),
+            Some(ERROR_METHOD_NOT_SUPPORTED)
+        );
+    }
+
+    #[async_std::test]
+    async fn caching_resolver() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        struct CountingResolver {
+            count: AtomicUsize,
+        }
+        #[async_trait]
+        impl DIDResolver for CountingResolver {
+            async fn resolve(
+                &self,
+                did: &str,
+                input_metadata: &ResolutionInputMetadata,
+            ) -> (
+                ResolutionMetadata,
+                Option<Document>,
+                Option<DocumentMetadata>,
+            ) {
+                self.count.fetch_add(1, Ordering::SeqCst);
+                // Yield, so that concurrent lookups overlap.
+                async_std::task::yield_now().await;
+                ExampleResolver {}.resolve(did, input_metadata).await
+            }
+
+            async fn dereference(
+                &self,
+                _primary_did_url: &PrimaryDIDURL,
+                _input_metadata: &DereferencingInputMetadata,
+            ) -> Option<(DereferencingMetadata, Content, ContentMetadata)> {
+                self.count.fetch_add(1, Ordering::SeqCst);
+                Some((
+                    DereferencingMetadata::default(),
+                    Content::Null,
+                    ContentMetadata::default(),
+                ))
+            }
+        }
+        let counting_resolver = CountingResolver {
+            count: AtomicUsize::new(0),
+        };
+        let count = || counting_resolver.count.load(Ordering::SeqCst);
+        let resolver = CachingResolver::new(&counting_resolver);
+        let input_metadata = ResolutionInputMetadata::default();
+
+        // Concurrent lookups are deduplicated.
+        let (res1, res2) = futures::join!(
+            resolver.resolve(EXAMPLE_123_ID, &input_metadata),
+            resolver.resolve(EXAMPLE_123_ID, &input_metadata)
+        );
+        assert_eq!(res1.0.error, None);
+        assert_eq!(res1.1, res2.1);
+        assert_eq!(count(), 1);
+
+        // Cached
+        let (res_meta, doc, _) = resolver.resolve(EXAMPLE_123_ID, &input_metadata).await;
+        assert_eq!(res_meta.error, None);
+        assert_eq!(doc.unwrap().id, EXAMPLE_123_ID);
+        assert_eq!(count(), 1);
+
+        // Errors are not cached.
+        resolver.resolve("did:example:bad", &input_metadata).await;
+        resolver.resolve("did:example:bad", &input_metadata).await;
+        assert_eq!(count(), 3);
+
+        // noCache bypasses the cache.
+        let no_cache = ResolutionInputMetadata {
+            no_cache: Some(true),
+            ..Default::default()
+        };
+        resolver.resolve(EXAMPLE_123_ID, &no_cache).await;
+        assert_eq!(count(), 4);
+
+        // Invalidation
+        resolver.invalidate("did:example:12");
+        resolver.resolve(EXAMPLE_123_ID, &input_metadata).await;
+        assert_eq!(count(), 4);
+        resolver.invalidate(EXAMPLE_123_ID);
+        resolver.resolve(EXAMPLE_123_ID, &input_metadata).await;
+        assert_eq!(count(), 5);
+
+        // Expiration
+        let resolver = CachingResolver::with_limits(&counting_resolver, Duration::zero(), 10);
+        resolver.resolve(EXAMPLE_123_ID, &input_metadata).await;
+        resolver.resolve(EXAMPLE_123_ID, &input_metadata).await;
+        assert_eq!(count(), 7);
+
+        // Size limit
+        let resolver = CachingResolver::with_limits(&counting_resolver, Duration::hours(1), 1);
+        resolver.resolve(EXAMPLE_123_ID, &input_metadata).await;
+        resolver.resolve(EXAMPLE_123_ID, &input_metadata).await;
+        assert_eq!(count(), 8);
+        let accept_json = ResolutionInputMetadata {
+            accept: Some(TYPE_DID_JSON.to_string()),
+            ..Default::default()
+        };
+        resolver.resolve(EXAMPLE_123_ID, &accept_json).await;
+        resolver.resolve(EXAMPLE_123_ID, &input_metadata).await;
+        assert_eq!(count(), 10);
+
+        // Keys do not depend on the order of input metadata properties.
+        let resolver = CachingResolver::new(&counting_resolver);
+        let names: Vec<String> = (0..16).map(|i| format!("property{}", i)).collect();
+        let with_properties = |names: &mut dyn Iterator<Item = &String>| {
+            let mut property_set = HashMap::new();
+            for name in names {
+                property_set.insert(name.clone(), Metadata::Boolean(true));
+            }
+            ResolutionInputMetadata {
+                property_set: Some(property_set),
+                ..Default::default()
+            }
+        };
+        resolver
+            .resolve(EXAMPLE_123_ID, &with_properties(&mut names.iter()))
+            .await;
+        resolver
+            .resolve(EXAMPLE_123_ID, &with_properties(&mut names.iter().rev()))
+            .await;
+        assert_eq!(count(), 11);
+
+        // Representations are cached.
+        resolver
+            .resolve_representation(EXAMPLE_123_ID, &input_metadata)
+            .await;
+        resolver
+            .resolve_representation(EXAMPLE_123_ID, &input_metadata)
+            .await;
+        assert_eq!(count(), 12);
+        resolver
+            .resolve_representation(EXAMPLE_123_ID, &no_cache)
+            .await;
+        assert_eq!(count(), 13);
+
+        // Dereferencing results are cached, unless noCache is set.
+        let primary_did_url = PrimaryDIDURL::try_from(format!("{}/path", EXAMPLE_123_ID)).unwrap();
+        let deref_input_metadata = DereferencingInputMetadata::default();
+        resolver
+            .dereference(&primary_did_url, &deref_input_metadata)
+            .await;
+        resolver
+            .dereference(&primary_did_url, &deref_input_metadata)
+            .await;
+        assert_eq!(count(), 14);
+        let deref_no_cache = DereferencingInputMetadata {
+            no_cache: Some(true),
+            ..Default::default()
+        };
+        resolver
+            .dereference(&primary_did_url, &deref_no_cache)
+            .await;
+        assert_eq!(count(), 15);
+
+        // noCache also bypasses the cache for the DID resolution done while dereferencing.
+        let did_url = format!("{}#key1", EXAMPLE_123_ID);
+        dereference(&resolver, &did_url, &deref_input_metadata).await;
+        assert_eq!(count(), 16);
+        dereference(&resolver, &did_url, &deref_input_metadata).await;
+        assert_eq!(count(), 16);
+        dereference(&resolver, &did_url, &deref_no_cache).await;
+        assert_eq!(count(), 17);
+
+        // Concurrent representation lookups are deduplicated.
+        let resolver = CachingResolver::new(&counting_resolver);
+        let (res1, res2) = futures::join!(
+            resolver.resolve_representation(EXAMPLE_123_ID, &input_metadata),
+            resolver.resolve_representation(EXAMPLE_123_ID, &input_metadata)
+        );
+        assert_eq!(res1.0.error, None);
+        assert_eq!(res1.1, res2.1);
+        assert_eq!(count(), 18);
+    }
+
+    #[async_std::test]
+    async fn composite_resolver() {
+        struct OtherResolver {
+            delay: Option<std::time::Duration>,
+        }
+        #[async_trait]
+        impl DIDResolver for OtherResolver {
+            async fn resolve(
+                &self,
+                did: &str,
+                _input_metadata: &ResolutionInputMetadata,
+            ) -> (
+                ResolutionMetadata,
+                Option<Document>,
+                Option<DocumentMetadata>,
+            ) {
+                if let Some(delay) = self.delay {
+                    async_std::task::sleep(delay).await;
+                }
+                if !did.starts_with("did:example:") {
+                    return (
+                        ResolutionMetadata::from_error(ERROR_METHOD_NOT_SUPPORTED),
+                        None,
+                        None,
+                    );
+                }
+                (
+                    ResolutionMetadata::default(),
+                    Some(Document::new(did)),
+                    Some(DocumentMetadata::default()),
+                )
+            }
+        }
+        fn resolver_name(res_meta: &ResolutionMetadata) -> Option<&str> {
+            match res_meta.property_set.as_ref()?.get("resolver")? {
+                Metadata::String(name) => Some(name),
+                _ => None,
+            }
+        }
+        let example = ExampleResolver {};
+        let other = OtherResolver { delay: None };
+        let slow = OtherResolver {
+            delay: Some(std::time::Duration::from_secs(10)),
+        };
+        let input_metadata = ResolutionInputMetadata::default();
+
+        // Routing by DID method, and timeouts
+        let mut resolver = CompositeResolver::new(CompositeMode::Series);
+        resolver.push(ResolverBackend {
+            methods: Some(vec!["key".to_string()]),
+            ..ResolverBackend::new("other", &other)
+        });
+        resolver.push(ResolverBackend {
+            timeout: Some(std::time::Duration::from_millis(10)),
+            ..ResolverBackend::new("slow", &slow)
+        });
+        resolver.push(ResolverBackend::new("example", &example));
+        let (res_meta, doc, _) = resolver.resolve(EXAMPLE_123_ID, &input_metadata).await;
+        assert_eq!(res_meta.error, None);
+        assert_eq!(resolver_name(&res_meta), Some("example"));
+        assert_eq!(doc.unwrap(), Document::from_json(EXAMPLE_123_JSON).unwrap());
+        let (res_meta, _, _) = resolver.resolve("did:example:456", &input_metadata).await;
+        assert_eq!(res_meta.error.as_deref(), Some(ERROR_NOT_FOUND));
+        assert_eq!(resolver_name(&res_meta), Some("example"));
+
+        // First success wins.
+        let mut resolver = CompositeResolver::new(CompositeMode::FirstSuccess);
+        resolver.push(ResolverBackend::new("slow", &slow));
+        resolver.push(ResolverBackend::new("example", &example));
+        let (res_meta, _, _) = resolver.resolve(EXAMPLE_123_ID, &input_metadata).await;
+        assert_eq!(res_meta.error, None);
+        assert_eq!(resolver_name(&res_meta), Some("example"));
+
+        // All must agree.
+        let mut resolver = CompositeResolver::new(CompositeMode::AllAgree);
+        resolver.push(ResolverBackend::new("example", &example));
+        resolver.push(ResolverBackend::new("example2", &example));
+        let (res_meta, doc, _) = resolver.resolve(EXAMPLE_123_ID, &input_metadata).await;
+        assert_eq!(res_meta.error, None);
+        assert!(doc.is_some());
+        match res_meta.property_set.unwrap().get("resolvers") {
+            Some(Metadata::List(names)) => assert_eq!(names.len(), 2),
+            resolvers => panic!("Unexpected resolvers metadata: {:?}", resolvers),
+        }
+        resolver.push(ResolverBackend::new("other", &other));
+        let (res_meta, doc, _) = resolver.resolve(EXAMPLE_123_ID, &input_metadata).await;
+        assert!(res_meta.error.is_some());
+        assert_eq!(doc, None);
+
+        // Resolvers must also agree on the DID document metadata.
+        struct VersionedResolver;
+        #[async_trait]
+        impl DIDResolver for VersionedResolver {
+            async fn resolve(
+                &self,
+                did: &str,
+                input_metadata: &ResolutionInputMetadata,
+            ) -> (
+                ResolutionMetadata,
+                Option<Document>,
+                Option<DocumentMetadata>,
+            ) {
+                let (res_meta, doc, doc_meta) =
+                    ExampleResolver {}.resolve(did, input_metadata).await;
+                let doc_meta = doc_meta.map(|doc_meta| DocumentMetadata {
+                    version_id: Some("2".to_string()),
+                    ..doc_meta
+                });
+                (res_meta, doc, doc_meta)
+            }
+        }
+        let mut resolver = CompositeResolver::new(CompositeMode::AllAgree);
+        resolver.push(ResolverBackend::new("example", &example));
+        resolver.push(ResolverBackend::new("versioned", &VersionedResolver));
+        let (res_meta, doc, _) = resolver.resolve(EXAMPLE_123_ID, &input_metadata).await;
+        assert!(res_meta.error.is_some());
+        assert_eq!(doc, None);
+    }
+
+    #[async_std::test]
+    async fn composite_resolver_dereference() {
+        struct PathResolver {
+            path: &'static str,
+            delay: Option<std::time::Duration>,
+        }
+        #[async_trait]
+        impl DIDResolver for PathResolver {
+            async fn resolve(
+                &self,
+                _did: &str,
+                _input_metadata: &ResolutionInputMetadata,
+            ) -> (
+                ResolutionMetadata,
+                Option<Document>,
+                Option<DocumentMetadata>,
+            ) {
+                (ResolutionMetadata::from_error(ERROR_NOT_FOUND), None, None)
+            }
+
+            async fn dereference(
+                &self,
+                primary_did_url: &PrimaryDIDURL,
+                _input_metadata: &DereferencingInputMetadata,
+            ) -> Option<(DereferencingMetadata, Content, ContentMetadata)> {
+                if let Some(delay) = self.delay {
+                    async_std::task::sleep(delay).await;
+                }
+                if primary_did_url.path.as_deref() != Some(self.path) {
+                    return Some((
+                        DereferencingMetadata::from_error(ERROR_NOT_FOUND),
+                        Content::Null,
+                        ContentMetadata::default(),
+                    ));
+                }
+                Some((
+                    DereferencingMetadata::default(),
+                    Content::URL(self.path.to_string()),
+                    ContentMetadata::default(),
+                ))
+            }
+        }
+        fn resolver_name(deref_meta: &DereferencingMetadata) -> Option<&str> {
+            match deref_meta.property_set.as_ref()?.get("resolver")? {
+                Metadata::String(name) => Some(name),
+                _ => None,
+            }
+        }
+        let example = ExampleResolver {};
+        let path = PathResolver {
+            path: "/path",
+            delay: None,
+        };
+        let other_path = PathResolver {
+            path: "/other",
+            delay: None,
+        };
+        let slow = PathResolver {
+            path: "/path",
+            delay: Some(std::time::Duration::from_secs(10)),
+        };
+        let primary_did_url = PrimaryDIDURL::try_from(format!("{}/path", EXAMPLE_123_ID)).unwrap();
+        let input_metadata = DereferencingInputMetadata::default();
+
+        // Routing by DID method, and timeouts. Resolvers that do not implement dereferencing are
+        // skipped.
+        let mut resolver = CompositeResolver::new(CompositeMode::Series);
+        resolver.push(ResolverBackend {
+            methods: Some(vec!["key".to_string()]),
+            ..ResolverBackend::new("other", &other_path)
+        });
+        resolver.push(ResolverBackend::new("example", &example));
+        resolver.push(ResolverBackend {
+            timeout: Some(std::time::Duration::from_millis(10)),
+            ..ResolverBackend::new("slow", &slow)
+        });
+        resolver.push(ResolverBackend::new("path", &path));
+        let (deref_meta, content, _) = resolver
+            .dereference(&primary_did_url, &input_metadata)
+            .await
+            .unwrap();
+        assert_eq!(deref_meta.error, None);
+        assert_eq!(resolver_name(&deref_meta), Some("path"));
+        assert_eq!(content, Content::URL("/path".to_string()));
+
+        // A timeout is returned if no other resolver supports the DID method.
+        let mut resolver = CompositeResolver::new(CompositeMode::Series);
+        resolver.push(ResolverBackend {
+            timeout: Some(std::time::Duration::from_millis(10)),
+            ..ResolverBackend::new("slow", &slow)
+        });
+        let (deref_meta, _, _) = resolver
+            .dereference(&primary_did_url, &input_metadata)
+            .await
+            .unwrap();
+        assert_eq!(deref_meta.error.as_deref(), Some(ERROR_TIMEOUT));
+
+        // First success wins.
+        let mut resolver = CompositeResolver::new(CompositeMode::FirstSuccess);
+        resolver.push(ResolverBackend::new("slow", &slow));
+        resolver.push(ResolverBackend::new("other", &other_path));
+        resolver.push(ResolverBackend::new("path", &path));
+        let (deref_meta, _, _) = resolver
+            .dereference(&primary_did_url, &input_metadata)
+            .await
+            .unwrap();
+        assert_eq!(deref_meta.error, None);
+        assert_eq!(resolver_name(&deref_meta), Some("path"));
+
+        // All must agree.
+        let mut resolver = CompositeResolver::new(CompositeMode::AllAgree);
+        resolver.push(ResolverBackend::new("path", &path));
+        resolver.push(ResolverBackend::new("path2", &path));
+        resolver.push(ResolverBackend::new("example", &example));
+        let (deref_meta, content, _) = resolver
+            .dereference(&primary_did_url, &input_metadata)
+            .await
+            .unwrap();
+        assert_eq!(deref_meta.error, None);
+        assert_eq!(content, Content::URL("/path".to_string()));
+        match deref_meta.property_set.unwrap().get("resolvers") {
+            Some(Metadata::List(names)) => assert_eq!(names.len(), 2),
+            resolvers => panic!("Unexpected resolvers metadata: {:?}", resolvers),
+        }
+        resolver.push(ResolverBackend::new("other", &other_path));
+        let (deref_meta, content, _) = resolver
+            .dereference(&primary_did_url, &input_metadata)
+            .await
+            .unwrap();
+        assert!(deref_meta.error.is_some());
+        assert_eq!(content, Content::Null);
+    }
+
+    #[async_std::test]
+    async fn version_time_resolver_dereference() {
+        // Dereference to the DID URL query.
+        struct QueryResolver {
+            updated: Option<DateTime<Utc>>,
+        }
+        #[async_trait]
+        impl DIDResolver for QueryResolver {
+            async fn resolve(
+                &self,
+                did: &str,
+                input_metadata: &ResolutionInputMetadata,
+            ) -> (
+                ResolutionMetadata,
+                Option<Document>,
+                Option<DocumentMetadata>,
+            ) {
+                let (res_meta, doc, doc_meta) =
+                    ExampleResolver {}.resolve(did, input_metadata).await;
+                let doc_meta = doc_meta.map(|doc_meta| DocumentMetadata {
+                    updated: self.updated,
+                    ..doc_meta
+                });
+                (res_meta, doc, doc_meta)
+            }
+
+            async fn dereference(
+                &self,
+                primary_did_url: &PrimaryDIDURL,
+                _input_metadata: &DereferencingInputMetadata,
+            ) -> Option<(DereferencingMetadata, Content, ContentMetadata)> {
+                let query = primary_did_url.query.clone().unwrap_or_default();
+                Some((
+                    DereferencingMetadata::default(),
+                    Content::Data(query.into_bytes()),
+                    ContentMetadata::default(),
+                ))
+            }
+        }
+        let version_time = "2021-01-01T00:00:00Z".parse().unwrap();
+        let query_resolver = QueryResolver {
+            updated: Some("2021-06-01T00:00:00Z".parse().unwrap()),
+        };
+        let resolver = VersionTimeResolver::new(&query_resolver, version_time);
+        let input_metadata = DereferencingInputMetadata::default();
+        let deref_query = |did_url: &str| {
+            let primary_did_url = PrimaryDIDURL::try_from(did_url.to_string()).unwrap();
+            let resolver = &resolver;
+            let input_metadata = &input_metadata;
+            async move {
+                match resolver.dereference(&primary_did_url, input_metadata).await {
+                    Some((_, Content::Data(query), _)) => String::from_utf8(query).unwrap(),
+                    _ => panic!("expected query"),
+                }
+            }
+        };
+        assert_eq!(
+            deref_query(EXAMPLE_123_ID).await,
+            "versionTime=2021-01-01T00%3A00%3A00Z"
+        );
+        assert_eq!(
+            deref_query(&format!("{}/path?service=foo", EXAMPLE_123_ID)).await,
+            "service=foo&versionTime=2021-01-01T00%3A00%3A00Z"
+        );
+        assert_eq!(
+            deref_query(&format!("{}?versionId=1", EXAMPLE_123_ID)).await,
+            "versionId=1"
+        );
+
+        // The DID document predates the version time, so it is dereferenced as is.
+        let query_resolver = QueryResolver {
+            updated: Some("2020-06-01T00:00:00Z".parse().unwrap()),
+        };
+        let resolver = VersionTimeResolver::new(&query_resolver, version_time);
+        let primary_did_url = PrimaryDIDURL::try_from(format!("{}/path", EXAMPLE_123_ID)).unwrap();
+        match resolver
+            .dereference(&primary_did_url, &input_metadata)
+            .await
+        {
+            Some((_, Content::Data(query), _)) => assert!(query.is_empty()),
+            _ => panic!("expected query"),
+        }
     }
 }
@@ -0,0 +1,443 @@
+//! Server side of the [DID Resolution HTTP(S) binding](https://w3c-ccg.github.io/did-resolution/#bindings-https).
+//!
+//! [`DIDResolutionServer`] exposes a [`DIDResolver`] at `/1.0/identifiers/{did}`, as a
+//! [Universal Resolver](https://github.com/decentralized-identity/universal-resolver) driver
+//! does. [`HTTPDIDResolver`](crate::did_resolve::HTTPDIDResolver) is the corresponding client.
+//!
+//! The DID or DID URL is given percent-encoded in the request path. The query string of the
+//! request contains the DID resolution or DID URL dereferencing input metadata.
+
+use std::cmp::Ordering;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use hyper::header::{self, HeaderValue};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use percent_encoding::percent_decode_str;
+
+use crate::did::Document;
+use crate::did_resolve::{
+    dereference, Content, ContentMetadata, DIDResolver, DereferencingInputMetadata,
+    DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata, ResolutionResult,
+    ERROR_INVALID_DID, ERROR_INVALID_DID_URL, ERROR_METHOD_NOT_SUPPORTED, ERROR_NOT_FOUND,
+    ERROR_REPRESENTATION_NOT_SUPPORTED, ERROR_TIMEOUT, ERROR_UNAUTHORIZED, TYPE_DID_CBOR,
+    TYPE_DID_JSON, TYPE_DID_LD_JSON, TYPE_DID_RESOLUTION, TYPE_LD_JSON, TYPE_URL,
+};
+
+/// Path under which DIDs and DID URLs are resolved
+pub const PATH_PREFIX: &str = "/1.0/identifiers/";
+
+const DID_RESOLUTION_PROFILE: &str = "https://w3id.org/did-resolution";
+const TYPE_OCTET_STREAM: &str = "application/octet-stream";
+
+/// Kind of response requested in an `Accept` header
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ResponseType {
+    /// DID Resolution Result ([`TYPE_DID_RESOLUTION`])
+    ResolutionResult,
+    /// DID document representation of the given media type
+    Representation(&'static str),
+}
+
+impl ResponseType {
+    fn media_type(&self) -> &'static str {
+        match *self {
+            Self::ResolutionResult => TYPE_DID_RESOLUTION,
+            Self::Representation(media_type) => media_type,
+        }
+    }
+}
+
+/// Select a response type from the value of an `Accept` header, by quality value and then order.
+fn negotiate(accept: Option<&str>) -> Option<ResponseType> {
+    let accept = match accept {
+        Some(accept) if !accept.trim().is_empty() => accept,
+        _ => return Some(ResponseType::ResolutionResult),
+    };
+    let mut ranges: Vec<(f32, ResponseType)> = Vec::new();
+    for range in accept.split(',') {
+        let mut parts = range.split(';').map(str::trim);
+        let media_type = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let mut quality = 1.0;
+        let mut profile = None;
+        for param in parts {
+            let mut param = param.splitn(2, '=');
+            let name = param.next().unwrap_or_default().trim();
+            let value = param.next().unwrap_or_default().trim().trim_matches('"');
+            if name.eq_ignore_ascii_case("q") {
+                quality = value.parse().unwrap_or(0.0);
+            } else if name.eq_ignore_ascii_case("profile") {
+                profile = Some(value);
+            }
+        }
+        let response_type = match (&media_type[..], profile) {
+            (TYPE_LD_JSON, Some(DID_RESOLUTION_PROFILE)) => ResponseType::ResolutionResult,
+            (TYPE_DID_LD_JSON, _) => ResponseType::Representation(TYPE_DID_LD_JSON),
+            (TYPE_DID_JSON, _) => ResponseType::Representation(TYPE_DID_JSON),
+            (TYPE_DID_CBOR, _) => ResponseType::Representation(TYPE_DID_CBOR),
+            ("*/*", _) | ("application/*", _) => ResponseType::ResolutionResult,
+            _ => continue,
+        };
+        if quality > 0.0 {
+            ranges.push((quality, response_type));
+        }
+    }
+    // Stable sort, to keep the order of ranges with equal quality.
+    ranges.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+    ranges.first().map(|(_, response_type)| *response_type)
+}
+
+/// Get the HTTP status code for a DID resolution or DID URL dereferencing error.
+///
+/// <https://w3c-ccg.github.io/did-resolution/#bindings-https>
+pub fn error_status(error: &str) -> StatusCode {
+    match error {
+        ERROR_INVALID_DID | ERROR_INVALID_DID_URL => StatusCode::BAD_REQUEST,
+        ERROR_UNAUTHORIZED => StatusCode::UNAUTHORIZED,
+        ERROR_NOT_FOUND => StatusCode::NOT_FOUND,
+        ERROR_REPRESENTATION_NOT_SUPPORTED => StatusCode::NOT_ACCEPTABLE,
+        ERROR_METHOD_NOT_SUPPORTED => StatusCode::NOT_IMPLEMENTED,
+        ERROR_TIMEOUT => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn document_status(doc_meta: Option<&DocumentMetadata>) -> StatusCode {
+    match doc_meta {
+        Some(DocumentMetadata {
+            deactivated: Some(true),
+            ..
+        }) => StatusCode::GONE,
+        _ => StatusCode::OK,
+    }
+}
+
+fn response(status: StatusCode, content_type: &str, body: Vec<u8>) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    if let Ok(content_type) = HeaderValue::from_str(content_type) {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, content_type);
+    }
+    response
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
+
+fn resolution_result_response(
+    res_meta: ResolutionMetadata,
+    doc: Option<Document>,
+    doc_meta: Option<DocumentMetadata>,
+) -> Response<Body> {
+    let status = match res_meta.error {
+        Some(ref error) => error_status(error),
+        None => document_status(doc_meta.as_ref()),
+    };
+    let result = ResolutionResult {
+        did_document: doc,
+        did_resolution_metadata: Some(res_meta),
+        did_document_metadata: doc_meta,
+        ..Default::default()
+    };
+    match serde_json::to_vec_pretty(&result) {
+        Ok(body) => response(status, TYPE_DID_RESOLUTION, body),
+        Err(_) => status_response(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+fn error_response(error: &str) -> Response<Body> {
+    resolution_result_response(ResolutionMetadata::from_error(error), None, None)
+}
+
+/// HTTP server for a [`DIDResolver`]. Cloning the server is cheap.
+#[derive(Clone)]
+pub struct DIDResolutionServer {
+    resolver: Arc<dyn DIDResolver + Send + Sync>,
+}
+
+impl DIDResolutionServer {
+    pub fn new(resolver: Arc<dyn DIDResolver + Send + Sync>) -> Self {
+        Self { resolver }
+    }
+
+    /// Handle a DID resolution or DID URL dereferencing request.
+    ///
+    /// Resolution results are returned with the HTTP status code corresponding to the
+    /// resolution error, if any, or `410 Gone` for a deactivated DID.
+    pub async fn handle(&self, req: Request<Body>) -> Response<Body> {
+        if req.method() != Method::GET {
+            return status_response(StatusCode::METHOD_NOT_ALLOWED);
+        }
+        let id = match req.uri().path().strip_prefix(PATH_PREFIX) {
+            Some(id) => id,
+            None => return status_response(StatusCode::NOT_FOUND),
+        };
+        let id = match percent_decode_str(id).decode_utf8() {
+            Ok(id) => id.into_owned(),
+            Err(_) => return error_response(ERROR_INVALID_DID),
+        };
+        let accept = req
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok());
+        let response_type = match negotiate(accept) {
+            Some(response_type) => response_type,
+            None => return error_response(ERROR_REPRESENTATION_NOT_SUPPORTED),
+        };
+        let query = req.uri().query().unwrap_or("");
+        if id.contains(&['/', '?', '#'][..]) {
+            self.dereference(&id, query, response_type).await
+        } else {
+            self.resolve(&id, query, response_type).await
+        }
+    }
+
+    async fn resolve(&self, did: &str, query: &str, response_type: ResponseType) -> Response<Body> {
+        let mut input_meta: ResolutionInputMetadata = match serde_urlencoded::from_str(query) {
+            Ok(input_meta) => input_meta,
+            Err(err) => {
+                return resolution_result_response(
+                    ResolutionMetadata::from_error(&format!(
+                        "Unable to parse resolution input metadata: {}",
+                        err
+                    )),
+                    None,
+                    None,
+                )
+            }
+        };
+        let media_type = match response_type {
+            ResponseType::ResolutionResult => {
+                let (res_meta, doc, doc_meta) = self.resolver.resolve(did, &input_meta).await;
+                return resolution_result_response(res_meta, doc, doc_meta);
+            }
+            ResponseType::Representation(media_type) => media_type,
+        };
+        input_meta.accept = Some(media_type.to_string());
+        let (res_meta, representation, doc_meta) =
+            self.resolver.resolve_representation(did, &input_meta).await;
+        if res_meta.error.is_some() {
+            return resolution_result_response(res_meta, None, doc_meta);
+        }
+        let content_type = res_meta.content_type.as_deref().unwrap_or(media_type);
+        response(
+            document_status(doc_meta.as_ref()),
+            content_type,
+            representation,
+        )
+    }
+
+    async fn dereference(
+        &self,
+        did_url: &str,
+        query: &str,
+        response_type: ResponseType,
+    ) -> Response<Body> {
+        let mut input_meta: DereferencingInputMetadata = match serde_urlencoded::from_str(query) {
+            Ok(input_meta) => input_meta,
+            Err(err) => {
+                return resolution_result_response(
+                    ResolutionMetadata::from_error(&format!(
+                        "Unable to parse dereferencing input metadata: {}",
+                        err
+                    )),
+                    None,
+                    None,
+                )
+            }
+        };
+        input_meta.accept = Some(response_type.media_type().to_string());
+        let (deref_meta, content, content_meta) =
+            dereference(self.resolver.as_ref(), did_url, &input_meta).await;
+        if deref_meta.error.is_some() {
+            return resolution_result_response(deref_meta.into(), None, None);
+        }
+        match content {
+            Content::DIDDocument(doc) => {
+                let doc_meta = match content_meta {
+                    ContentMetadata::DIDDocument(doc_meta) => Some(doc_meta),
+                    ContentMetadata::Other(_) => None,
+                };
+                let media_type = match response_type {
+                    ResponseType::ResolutionResult => {
+                        return resolution_result_response(deref_meta.into(), Some(doc), doc_meta)
+                    }
+                    ResponseType::Representation(media_type) => media_type,
+                };
+                match doc.to_representation(media_type) {
+                    Ok(body) => response(document_status(doc_meta.as_ref()), media_type, body),
+                    Err(_) => status_response(StatusCode::INTERNAL_SERVER_ERROR),
+                }
+            }
+            Content::URL(url) => {
+                // https://w3c-ccg.github.io/did-resolution/#bindings-https Step 1.10.3
+                let location = match HeaderValue::from_str(&url) {
+                    Ok(location) => location,
+                    Err(_) => return status_response(StatusCode::INTERNAL_SERVER_ERROR),
+                };
+                let mut response = response(StatusCode::SEE_OTHER, TYPE_URL, url.into_bytes());
+                response.headers_mut().insert(header::LOCATION, location);
+                response
+            }
+            Content::Object(resource) => match serde_json::to_vec_pretty(&resource) {
+                Ok(body) => response(StatusCode::OK, TYPE_LD_JSON, body),
+                Err(_) => status_response(StatusCode::INTERNAL_SERVER_ERROR),
+            },
+            Content::Data(data) => {
+                let content_type = deref_meta.content_type.as_deref();
+                response(
+                    StatusCode::OK,
+                    content_type.unwrap_or(TYPE_OCTET_STREAM),
+                    data,
+                )
+            }
+            Content::Null => error_response(ERROR_NOT_FOUND),
+        }
+    }
+
+    /// Bind the server to a socket address. Returns the local address bound, and a future that
+    /// runs the server until the given shutdown future completes.
+    pub fn bind(
+        self,
+        addr: &SocketAddr,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(SocketAddr, impl Future<Output = Result<(), hyper::Error>>), hyper::Error> {
+        let make_svc = make_service_fn(move |_| {
+            let server = self.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.handle(req).await) }
+                }))
+            }
+        });
+        let server = Server::try_bind(addr)?.serve(make_svc);
+        let local_addr = server.local_addr();
+        Ok((local_addr, server.with_graceful_shutdown(shutdown)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::did::example::DIDExample;
+    use crate::did_resolve::HTTPDIDResolver;
+    use hyper::Client;
+
+    #[test]
+    fn negotiate_accept() {
+        assert_eq!(negotiate(None), Some(ResponseType::ResolutionResult));
+        assert_eq!(
+            negotiate(Some(TYPE_DID_RESOLUTION)),
+            Some(ResponseType::ResolutionResult)
+        );
+        assert_eq!(
+            negotiate(Some("application/did+json")),
+            Some(ResponseType::Representation(TYPE_DID_JSON))
+        );
+        assert_eq!(
+            negotiate(Some("application/did+json;q=0.5, application/did+ld+json")),
+            Some(ResponseType::Representation(TYPE_DID_LD_JSON))
+        );
+        assert_eq!(negotiate(Some("text/html")), None);
+        assert_eq!(negotiate(Some("application/did+json;q=0")), None);
+    }
+
+    async fn get(url: &str, accept: Option<&str>) -> (StatusCode, String, Vec<u8>) {
+        let mut request = Request::get(url);
+        if let Some(accept) = accept {
+            request = request.header(header::ACCEPT, accept);
+        }
+        let resp = Client::new()
+            .request(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = resp.status();
+        let content_type = resp
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|content_type| content_type.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        (status, content_type, body.to_vec())
+    }
+
+    #[tokio::test]
+    async fn resolution_server() {
+        let server = DIDResolutionServer::new(Arc::new(DIDExample));
+        let (shutdown_tx, shutdown_rx) = futures::channel::oneshot::channel::<()>();
+        let (addr, serve) = server
+            .bind(&([127, 0, 0, 1], 0).into(), async {
+                shutdown_rx.await.ok();
+            })
+            .unwrap();
+        tokio::task::spawn(serve);
+        let endpoint = format!("http://{}{}", addr, PATH_PREFIX);
+
+        let resolver = HTTPDIDResolver::new(&endpoint);
+        let (res_meta, doc, doc_meta) = resolver
+            .resolve("did:example:foo", &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, None);
+        assert_eq!(doc.unwrap().id, "did:example:foo");
+        assert!(doc_meta.is_some());
+
+        let (res_meta, doc, _) = resolver
+            .resolve("did:example:missing", &ResolutionInputMetadata::default())
+            .await;
+        assert_eq!(res_meta.error, Some(ERROR_NOT_FOUND.to_string()));
+        assert!(doc.is_none());
+
+        let url = format!("{}did:example:foo", endpoint);
+        let (status, content_type, body) = get(&url, Some(TYPE_DID_LD_JSON)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, TYPE_DID_LD_JSON);
+        let doc: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(doc["id"], "did:example:foo");
+        assert!(doc.get("@context").is_some());
+
+        // The plain JSON representation has no @context.
+        let (status, content_type, body) = get(&url, Some(TYPE_DID_JSON)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, TYPE_DID_JSON);
+        let doc: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(doc["id"], "did:example:foo");
+        assert!(doc.get("@context").is_none());
+
+        let (status, content_type, _) = get(&url, Some("*/*")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, TYPE_DID_RESOLUTION);
+
+        let (status, content_type, body) = get(&url, Some(TYPE_DID_CBOR)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, TYPE_DID_CBOR);
+        let doc = Document::from_representation(&body, TYPE_DID_CBOR).unwrap();
+        assert_eq!(doc.id, "did:example:foo");
+
+        let (status, _, _) = get(&url, Some("text/html")).await;
+        assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+
+        let (status, content_type, _) =
+            get(&format!("{}did:example:missing", endpoint), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(content_type, TYPE_DID_RESOLUTION);
+
+        // Fragment must be percent-encoded
+        let url = format!("{}did:example:foo%23key2", endpoint);
+        let (status, content_type, body) = get(&url, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, TYPE_LD_JSON);
+        let vm: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(vm["id"], "did:example:foo#key2");
+
+        shutdown_tx.send(()).unwrap();
+    }
+}
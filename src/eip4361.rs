@@ -0,0 +1,498 @@
+//! [EIP-4361](https://eips.ethereum.org/EIPS/eip-4361) Sign-In with Ethereum (SIWE) messages.
+//!
+//! A SIWE message is signed by an Ethereum account using `personal_sign`
+//! ([EIP-191](https://eips.ethereum.org/EIPS/eip-191) version `0x45`). Verifying a signed
+//! message authenticates the corresponding `did:pkh:eip155` DID.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use chrono::prelude::*;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::error::Error;
+use crate::jwk::{Algorithm, JWK};
+use crate::keccak_hash::{bytes_to_lowerhex, eip55_checksum_addr, prefix_personal_message};
+use crate::vc::{Presentation, VCDateTime, URI};
+
+const PREAMBLE_SUFFIX: &str = " wants you to sign in with your Ethereum account:";
+const VERSION: &str = "1";
+const NONCE_MIN_LENGTH: usize = 8;
+/// Presentation property holding the signed message
+const MESSAGE_PROPERTY: &str = "siweMessage";
+/// Presentation property holding the message signature
+const SIGNATURE_PROPERTY: &str = "siweSignature";
+
+#[derive(Error, Debug)]
+pub enum ParseError {
+    #[error("Expected message preamble")]
+    Preamble,
+    #[error("Missing field: {0}")]
+    MissingField(&'static str),
+    #[error("Expected empty line")]
+    ExpectedEmptyLine,
+    #[error("Unexpected line: {0}")]
+    UnexpectedLine(String),
+    #[error("Invalid domain: {0}")]
+    Domain(String),
+    #[error("Invalid address: {0}")]
+    Address(String),
+    #[error("Unsupported version: {0}")]
+    Version(String),
+    #[error("Invalid chain id: {0}")]
+    ChainId(#[from] ParseIntError),
+    #[error("Invalid nonce: {0}")]
+    Nonce(String),
+    #[error("Invalid date-time: {0}")]
+    DateTime(#[from] chrono::format::ParseError),
+}
+
+#[derive(Error, Debug)]
+pub enum VerificationError {
+    #[error("Domain mismatch: expected {0}, got {1}")]
+    DomainMismatch(String, String),
+    #[error("Nonce mismatch: expected {0}, got {1}")]
+    NonceMismatch(String, String),
+    #[error("Message is expired")]
+    Expired,
+    #[error("Message is not yet valid")]
+    NotYetValid,
+    #[error("Signature key does not match address: got {0}, expected {1}")]
+    AddressMismatch(String, String),
+    #[error("Unable to verify signature: {0}")]
+    Signature(#[from] Error),
+    #[error("Missing presentation property: {0}")]
+    MissingProperty(&'static str),
+    #[error("Unable to parse message: {0}")]
+    Parse(#[from] ParseError),
+    #[error("Presentation holder does not match signing account")]
+    HolderMismatch,
+}
+
+/// Sign-In with Ethereum message
+#[derive(Debug, Clone)]
+pub struct Message {
+    /// RFC 3986 authority requesting the signing
+    pub domain: String,
+    /// EIP-55 mixed-case checksum encoded Ethereum address
+    pub address: String,
+    /// Human-readable assertion that the user will sign
+    pub statement: Option<String>,
+    /// RFC 3986 URI referring to the subject of the signing
+    pub uri: String,
+    pub version: String,
+    /// EIP-155 chain id
+    pub chain_id: u64,
+    /// Randomized token, at least 8 alphanumeric characters, to prevent replay attacks
+    pub nonce: String,
+    pub issued_at: VCDateTime,
+    pub expiration_time: Option<VCDateTime>,
+    pub not_before: Option<VCDateTime>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+fn check_address(address: &str) -> Result<(), ParseError> {
+    let hex = address
+        .strip_prefix("0x")
+        .ok_or_else(|| ParseError::Address(address.to_string()))?;
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ParseError::Address(address.to_string()));
+    }
+    match eip55_checksum_addr(&address.to_ascii_lowercase()) {
+        Ok(checksummed) if checksummed == address => Ok(()),
+        _ => Err(ParseError::Address(address.to_string())),
+    }
+}
+
+fn check_nonce(nonce: &str) -> Result<(), ParseError> {
+    if nonce.len() < NONCE_MIN_LENGTH || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(ParseError::Nonce(nonce.to_string()));
+    }
+    Ok(())
+}
+
+fn expect_empty_line<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Result<(), ParseError> {
+    match lines.next() {
+        Some("") => Ok(()),
+        _ => Err(ParseError::ExpectedEmptyLine),
+    }
+}
+
+fn take_field<'a>(
+    lines: &mut std::iter::Peekable<impl Iterator<Item = &'a str>>,
+    tag: &'static str,
+) -> Option<&'a str> {
+    let value = lines
+        .peek()
+        .copied()?
+        .strip_prefix(tag)?
+        .strip_prefix(": ")?;
+    lines.next();
+    Some(value)
+}
+
+impl FromStr for Message {
+    type Err = ParseError;
+    fn from_str(message: &str) -> Result<Self, Self::Err> {
+        let mut lines = message.split('\n').peekable();
+        let domain = lines
+            .next()
+            .and_then(|line| line.strip_suffix(PREAMBLE_SUFFIX))
+            .ok_or(ParseError::Preamble)?;
+        if domain.is_empty() || domain.contains(char::is_whitespace) {
+            return Err(ParseError::Domain(domain.to_string()));
+        }
+        let address = lines.next().ok_or(ParseError::MissingField("address"))?;
+        check_address(address)?;
+        expect_empty_line(&mut lines)?;
+        let statement = match lines.next() {
+            Some("") => None,
+            Some(statement) => {
+                expect_empty_line(&mut lines)?;
+                Some(statement.to_string())
+            }
+            None => return Err(ParseError::MissingField("URI")),
+        };
+        let uri = take_field(&mut lines, "URI").ok_or(ParseError::MissingField("URI"))?;
+        let version =
+            take_field(&mut lines, "Version").ok_or(ParseError::MissingField("Version"))?;
+        if version != VERSION {
+            return Err(ParseError::Version(version.to_string()));
+        }
+        let chain_id = take_field(&mut lines, "Chain ID")
+            .ok_or(ParseError::MissingField("Chain ID"))?
+            .parse()?;
+        let nonce = take_field(&mut lines, "Nonce").ok_or(ParseError::MissingField("Nonce"))?;
+        check_nonce(nonce)?;
+        let issued_at = take_field(&mut lines, "Issued At")
+            .ok_or(ParseError::MissingField("Issued At"))?
+            .parse()?;
+        let expiration_time = take_field(&mut lines, "Expiration Time")
+            .map(VCDateTime::from_str)
+            .transpose()?;
+        let not_before = take_field(&mut lines, "Not Before")
+            .map(VCDateTime::from_str)
+            .transpose()?;
+        let request_id = take_field(&mut lines, "Request ID").map(String::from);
+        let mut resources = Vec::new();
+        if lines.peek() == Some(&"Resources:") {
+            lines.next();
+            while let Some(resource) = lines
+                .peek()
+                .copied()
+                .and_then(|line| line.strip_prefix("- "))
+            {
+                resources.push(resource.to_string());
+                lines.next();
+            }
+        }
+        if let Some(line) = lines.next() {
+            return Err(ParseError::UnexpectedLine(line.to_string()));
+        }
+        Ok(Self {
+            domain: domain.to_string(),
+            address: address.to_string(),
+            statement,
+            uri: uri.to_string(),
+            version: version.to_string(),
+            chain_id,
+            nonce: nonce.to_string(),
+            issued_at,
+            expiration_time,
+            not_before,
+            request_id,
+            resources,
+        })
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}{}", self.domain, PREAMBLE_SUFFIX)?;
+        writeln!(f, "{}", self.address)?;
+        writeln!(f)?;
+        if let Some(ref statement) = self.statement {
+            writeln!(f, "{}", statement)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Chain ID: {}", self.chain_id)?;
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        write!(f, "Issued At: {}", String::from(self.issued_at.clone()))?;
+        if let Some(ref expiration_time) = self.expiration_time {
+            write!(
+                f,
+                "\nExpiration Time: {}",
+                String::from(expiration_time.clone())
+            )?;
+        }
+        if let Some(ref not_before) = self.not_before {
+            write!(f, "\nNot Before: {}", String::from(not_before.clone()))?;
+        }
+        if let Some(ref request_id) = self.request_id {
+            write!(f, "\nRequest ID: {}", request_id)?;
+        }
+        if !self.resources.is_empty() {
+            write!(f, "\nResources:")?;
+            for resource in &self.resources {
+                write!(f, "\n- {}", resource)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Message {
+    /// Get the `did:pkh` DID of the signing account.
+    pub fn did(&self) -> String {
+        format!("did:pkh:eip155:{}:{}", self.chain_id, self.address)
+    }
+
+    /// Sign the message with a secp256k1 key, returning a hex-encoded `personal_sign` signature.
+    pub fn sign(&self, key: &JWK) -> Result<String, Error> {
+        let data = prefix_personal_message(&self.to_string());
+        let mut sig = crate::jws::sign_bytes(Algorithm::ESKeccakKR, &data, key)?;
+        // Recovery ID starts at 27 instead of 0.
+        sig[64] += 27;
+        Ok(bytes_to_lowerhex(&sig))
+    }
+
+    /// Verify a hex-encoded `personal_sign` signature of the message by its account, returning
+    /// the recovered public key.
+    pub fn verify_signature(&self, signature: &str) -> Result<JWK, VerificationError> {
+        let sig_hex = signature.strip_prefix("0x").ok_or(Error::HexString)?;
+        let mut sig = hex::decode(sig_hex).map_err(Error::from)?;
+        if sig.len() != 65 {
+            return Err(Error::UnexpectedSignatureLength(65, sig.len()).into());
+        }
+        if sig[64] >= 27 {
+            sig[64] -= 27;
+        }
+        let data = prefix_personal_message(&self.to_string());
+        let jwk = crate::jws::recover(Algorithm::ESKeccakKR, &data, &sig)?;
+        let address = crate::keccak_hash::hash_public_key_eip55(&jwk)?;
+        if address != self.address {
+            return Err(VerificationError::AddressMismatch(
+                address,
+                self.address.clone(),
+            ));
+        }
+        Ok(jwk)
+    }
+
+    /// Verify a signed message against the relying party's domain and the nonce it issued, and
+    /// check that the message is within its validity period at `timestamp` (by default, the
+    /// current time).
+    pub fn verify(
+        &self,
+        signature: &str,
+        domain: &str,
+        nonce: &str,
+        timestamp: Option<DateTime<Utc>>,
+    ) -> Result<VerifiedMessage, VerificationError> {
+        if domain != self.domain {
+            return Err(VerificationError::DomainMismatch(
+                domain.to_string(),
+                self.domain.clone(),
+            ));
+        }
+        if nonce != self.nonce {
+            return Err(VerificationError::NonceMismatch(
+                nonce.to_string(),
+                self.nonce.clone(),
+            ));
+        }
+        let now = timestamp.unwrap_or_else(Utc::now);
+        if let Some(ref expiration_time) = self.expiration_time {
+            if now >= DateTime::<FixedOffset>::from(expiration_time.clone()).with_timezone(&Utc) {
+                return Err(VerificationError::Expired);
+            }
+        }
+        if let Some(ref not_before) = self.not_before {
+            if now < DateTime::<FixedOffset>::from(not_before.clone()).with_timezone(&Utc) {
+                return Err(VerificationError::NotYetValid);
+            }
+        }
+        let jwk = self.verify_signature(signature)?;
+        Ok(VerifiedMessage {
+            message: self.clone(),
+            signature: signature.to_string(),
+            jwk,
+        })
+    }
+}
+
+/// A [`Message`] whose signature has been verified, as returned by [`Message::verify`].
+#[derive(Debug, Clone)]
+pub struct VerifiedMessage {
+    message: Message,
+    signature: String,
+    jwk: JWK,
+}
+
+impl VerifiedMessage {
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+
+    /// Get the public key recovered from the signature.
+    pub fn jwk(&self) -> &JWK {
+        &self.jwk
+    }
+
+    /// Create a presentation with the signing account's `did:pkh` DID as holder.
+    ///
+    /// The message and its signature are embedded in the presentation (as the `siweMessage` and
+    /// `siweSignature` properties), so that it can be verified with [`verify_presentation`].
+    pub fn to_presentation(&self) -> Presentation {
+        let mut property_set = HashMap::new();
+        property_set.insert(
+            MESSAGE_PROPERTY.to_string(),
+            Value::String(self.message.to_string()),
+        );
+        property_set.insert(
+            SIGNATURE_PROPERTY.to_string(),
+            Value::String(self.signature.clone()),
+        );
+        Presentation {
+            holder: Some(URI::String(self.message.did())),
+            property_set: Some(property_set),
+            ..Default::default()
+        }
+    }
+}
+
+/// Verify the message and signature embedded in a presentation created by
+/// [`VerifiedMessage::to_presentation`], and check that the presentation holder is the signing
+/// account's `did:pkh` DID. The message is verified as by [`Message::verify`].
+pub fn verify_presentation(
+    presentation: &Presentation,
+    domain: &str,
+    nonce: &str,
+    timestamp: Option<DateTime<Utc>>,
+) -> Result<VerifiedMessage, VerificationError> {
+    let property = |name| {
+        presentation
+            .property_set
+            .as_ref()
+            .and_then(|properties| properties.get(name))
+            .and_then(Value::as_str)
+            .ok_or(VerificationError::MissingProperty(name))
+    };
+    let message = Message::from_str(property(MESSAGE_PROPERTY)?)?;
+    let signature = property(SIGNATURE_PROPERTY)?;
+    match presentation.holder {
+        Some(URI::String(ref holder)) if holder == &message.did() => {}
+        _ => return Err(VerificationError::HolderMismatch),
+    }
+    message.verify(signature, domain, nonce, timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // https://eips.ethereum.org/EIPS/eip-4361#example-message
+    const EXAMPLE_MESSAGE: &str = "service.invalid wants you to sign in with your Ethereum account:
+0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2
+
+I accept the ServiceOrg Terms of Service: https://service.invalid/tos
+
+URI: https://service.invalid/login
+Version: 1
+Chain ID: 1
+Nonce: 32891756
+Issued At: 2021-09-30T16:25:24Z
+Resources:
+- ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/
+- https://example.com/my-web2-claim.json";
+
+    #[test]
+    fn parse_format() {
+        let message = Message::from_str(EXAMPLE_MESSAGE).unwrap();
+        assert_eq!(message.domain, "service.invalid");
+        assert_eq!(message.chain_id, 1);
+        assert_eq!(message.resources.len(), 2);
+        assert_eq!(message.to_string(), EXAMPLE_MESSAGE);
+
+        let mut message = message;
+        message.statement = None;
+        message.expiration_time = Some(VCDateTime::from_str("2021-10-01T16:25:24Z").unwrap());
+        message.resources = vec![];
+        let reparsed = Message::from_str(&message.to_string()).unwrap();
+        assert_eq!(reparsed.to_string(), message.to_string());
+
+        let bad_address = EXAMPLE_MESSAGE.replace("0xC02aaA", "0xc02aaA");
+        assert!(Message::from_str(&bad_address).is_err());
+        let bad_nonce = EXAMPLE_MESSAGE.replace("32891756", "1234");
+        assert!(Message::from_str(&bad_nonce).is_err());
+        let bad_version = EXAMPLE_MESSAGE.replace("Version: 1", "Version: 2");
+        assert!(Message::from_str(&bad_version).is_err());
+    }
+
+    #[test]
+    fn sign_verify() {
+        let key = JWK::generate_secp256k1().unwrap();
+        let address = crate::keccak_hash::hash_public_key_eip55(&key).unwrap();
+        let mut message = Message::from_str(EXAMPLE_MESSAGE).unwrap();
+        message.address = address.clone();
+        message.expiration_time = Some(VCDateTime::from_str("2021-10-01T16:25:24Z").unwrap());
+        let sig = message.sign(&key).unwrap();
+        let domain = "service.invalid";
+        let nonce = "32891756";
+        let time = Some("2021-09-30T17:00:00Z".parse().unwrap());
+        let verified = message.verify(&sig, domain, nonce, time).unwrap();
+        assert!(verified.jwk().equals_public(&key));
+
+        assert!(matches!(
+            message.verify(&sig, "example.org", nonce, time),
+            Err(VerificationError::DomainMismatch(_, _))
+        ));
+        assert!(matches!(
+            message.verify(&sig, domain, "87654321", time),
+            Err(VerificationError::NonceMismatch(_, _))
+        ));
+        let expired = Some("2021-10-02T00:00:00Z".parse().unwrap());
+        assert!(message.verify(&sig, domain, nonce, expired).is_err());
+
+        let mut tampered = message.clone();
+        tampered.uri = "https://example.org/login".to_string();
+        assert!(tampered.verify(&sig, domain, nonce, time).is_err());
+
+        let vp = verified.to_presentation();
+        assert_eq!(
+            vp.holder,
+            Some(URI::String(format!("did:pkh:eip155:1:{}", address)))
+        );
+        let vp: Presentation = serde_json::from_value(serde_json::to_value(&vp).unwrap()).unwrap();
+        let verified = verify_presentation(&vp, domain, nonce, time).unwrap();
+        assert_eq!(verified.message().to_string(), message.to_string());
+        assert!(verify_presentation(&vp, domain, "87654321", time).is_err());
+
+        let mut other_holder = vp.clone();
+        other_holder.holder = Some(URI::String("did:example:foo".to_string()));
+        assert!(matches!(
+            verify_presentation(&other_holder, domain, nonce, time),
+            Err(VerificationError::HolderMismatch)
+        ));
+        let mut tampered_vp = vp.clone();
+        tampered_vp.property_set.as_mut().unwrap().insert(
+            MESSAGE_PROPERTY.to_string(),
+            Value::String(tampered.to_string()),
+        );
+        assert!(verify_presentation(&tampered_vp, domain, nonce, time).is_err());
+        assert!(matches!(
+            verify_presentation(&Presentation::default(), domain, nonce, time),
+            Err(VerificationError::MissingProperty(_))
+        ));
+    }
+}
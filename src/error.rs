@@ -8,6 +8,8 @@ use crate::eip712::TypedDataConstructionJSONError;
 use crate::eip712::TypedDataHashError;
 use crate::tzkey::{DecodeTezosSignatureError, EncodeTezosSignedMessageError};
 use base64::DecodeError as Base64Error;
+use ciborium::de::Error as CBORDeserializeError;
+use ciborium::ser::Error as CBORSerializeError;
 #[cfg(feature = "ed25519-dalek")]
 use ed25519_dalek::ed25519::Error as ED25519Error;
 use iref::Error as IRIError;
@@ -108,6 +110,8 @@ pub enum Error {
     MissingContext,
     MissingDocumentId,
     MissingProofSignature,
+    MissingProofId,
+    MissingPreviousProof(String),
     ExpiredProof,
     FutureProof,
     InvalidProofPurpose,
@@ -129,6 +133,10 @@ pub enum Error {
     UnencodableOptionClaim(String),
     ExpectedUnencodedHeader,
     ResourceNotFound(String),
+    DIDDeactivated(String),
+    InvalidDID(String),
+    DuplicateId(String),
+    InvalidServiceEndpoint(String),
     InvalidProofTypeType,
     InvalidKeyLength,
     InconsistentDIDKey,
@@ -179,11 +187,15 @@ pub enum Error {
     Secp256k1(Secp256k1Error),
     #[cfg(feature = "p256")]
     Secp256r1(Secp256r1Error),
+    #[cfg(feature = "p384")]
+    Secp384r1(p384::ecdsa::Error),
     ASN1Encode(ASN1EncodeError),
     Base64(Base64Error),
     Multibase(MultibaseError),
     JSON(JSONError),
     SerdeJSON(SerdeJSONError),
+    CBORSerialize(CBORSerializeError<std::io::Error>),
+    CBORDeserialize(CBORDeserializeError<std::io::Error>),
     SerdeUrlEncoded(SerdeUrlEncodedError),
     JSONLD(JSONLDErrorCode),
     IRI(IRIError),
@@ -200,6 +212,8 @@ pub enum Error {
     TypedDataHash(TypedDataHashError),
     FromHex(hex::FromHexError),
     Base58(bs58::decode::Error),
+    #[cfg(feature = "bech32")]
+    Bech32(bech32::Error),
     HexString,
     ExpectedLowercase,
     SignaturePrefix,
@@ -212,6 +226,8 @@ pub enum Error {
     K256EC(k256::elliptic_curve::Error),
     #[cfg(feature = "p256")]
     P256EC(p256::elliptic_curve::Error),
+    #[cfg(feature = "p384")]
+    P384EC(p384::elliptic_curve::Error),
     MissingFeatures(&'static str),
     NumericDateOutOfMicrosecondPrecisionRange,
 }
@@ -289,6 +305,8 @@ impl fmt::Display for Error {
             Error::MissingContext => write!(f, "Missing context"),
             Error::MissingDocumentId => write!(f, "Missing document ID"),
             Error::MissingProofSignature => write!(f, "Missing JWS in proof"),
+            Error::MissingProofId => write!(f, "Missing proof id"),
+            Error::MissingPreviousProof(id) => write!(f, "Previous proof not found: {}", id),
             Error::ExpiredProof => write!(f, "Expired proof"),
             Error::FutureProof => write!(f, "Proof creation time is in the future"),
             Error::InvalidSignature => write!(f, "Invalid Signature"),
@@ -310,6 +328,10 @@ impl fmt::Display for Error {
             Error::UnencodableOptionClaim(name) => write!(f, "Linked data proof option unencodable as JWT claim: {}", name),
             Error::ExpectedUnencodedHeader => write!(f, "Expected unencoded JWT header"),
             Error::ResourceNotFound(id) => write!(f, "Resource not found: {}", id),
+            Error::DIDDeactivated(did) => write!(f, "DID is deactivated: {}", did),
+            Error::InvalidDID(did) => write!(f, "Invalid DID: {}", did),
+            Error::DuplicateId(id) => write!(f, "Duplicate id in DID document: {}", id),
+            Error::InvalidServiceEndpoint(id) => write!(f, "Invalid service endpoint for service: {}", id),
             Error::InvalidProofTypeType => write!(f, "Invalid ProofType type"),
             Error::InvalidKeyLength => write!(f, "Invalid key length"),
             Error::InconsistentDIDKey => write!(f, "Inconsistent DID Key"),
@@ -369,11 +391,15 @@ impl fmt::Display for Error {
             Error::Secp256k1(e) => e.fmt(f),
             #[cfg(feature = "p256")]
             Error::Secp256r1(e) => e.fmt(f),
+            #[cfg(feature = "p384")]
+            Error::Secp384r1(e) => e.fmt(f),
             Error::Base64(e) => e.fmt(f),
             Error::Multibase(e) => e.fmt(f),
             Error::ASN1Encode(e) => e.fmt(f),
             Error::JSON(e) => e.fmt(f),
             Error::SerdeJSON(e) => e.fmt(f),
+            Error::CBORSerialize(e) => e.fmt(f),
+            Error::CBORDeserialize(e) => e.fmt(f),
             Error::SerdeUrlEncoded(e) => e.fmt(f),
             Error::JSONLD(e) => e.fmt(f),
             Error::IRI(e) => e.fmt(f),
@@ -389,10 +415,14 @@ impl fmt::Display for Error {
             Error::TypedDataHash(e) => e.fmt(f),
             Error::FromHex(e) => e.fmt(f),
             Error::Base58(e) => e.fmt(f),
+            #[cfg(feature = "bech32")]
+            Error::Bech32(e) => e.fmt(f),
             #[cfg(feature = "k256")]
             Error::K256EC(e) => e.fmt(f),
             #[cfg(feature = "p256")]
             Error::P256EC(e) => e.fmt(f),
+            #[cfg(feature = "p384")]
+            Error::P384EC(e) => e.fmt(f),
             Error::P256KeyLength(len) => write!(f, "Expected 64 byte uncompressed key or 33 bytes compressed key but found length: {}", len),
             Error::ECEncodingError => write!(f, "Unable to encode EC key"),
             Error::ECDecompress => write!(f, "Unable to decompress elliptic curve"),
@@ -432,6 +462,18 @@ impl From<SerdeJSONError> for Error {
     }
 }
 
+impl From<CBORSerializeError<std::io::Error>> for Error {
+    fn from(err: CBORSerializeError<std::io::Error>) -> Error {
+        Error::CBORSerialize(err)
+    }
+}
+
+impl From<CBORDeserializeError<std::io::Error>> for Error {
+    fn from(err: CBORDeserializeError<std::io::Error>) -> Error {
+        Error::CBORDeserialize(err)
+    }
+}
+
 impl From<SerdeUrlEncodedError> for Error {
     fn from(err: SerdeUrlEncodedError) -> Error {
         Error::SerdeUrlEncoded(err)
@@ -546,6 +588,13 @@ impl From<bs58::decode::Error> for Error {
     }
 }
 
+#[cfg(feature = "bech32")]
+impl From<bech32::Error> for Error {
+    fn from(err: bech32::Error) -> Error {
+        Error::Bech32(err)
+    }
+}
+
 #[cfg(feature = "k256")]
 impl From<Secp256k1Error> for Error {
     fn from(err: Secp256k1Error) -> Error {
@@ -561,6 +610,13 @@ impl From<Secp256r1Error> for Error {
     }
 }
 
+#[cfg(feature = "p384")]
+impl From<p384::ecdsa::Error> for Error {
+    fn from(err: p384::ecdsa::Error) -> Error {
+        Error::Secp384r1(err)
+    }
+}
+
 #[cfg(feature = "k256")]
 impl From<k256::elliptic_curve::Error> for Error {
     fn from(err: k256::elliptic_curve::Error) -> Error {
@@ -588,6 +644,13 @@ impl From<p256::elliptic_curve::Error> for Error {
     }
 }
 
+#[cfg(feature = "p384")]
+impl From<p384::elliptic_curve::Error> for Error {
+    fn from(err: p384::elliptic_curve::Error) -> Error {
+        Error::P384EC(err)
+    }
+}
+
 #[cfg(all(
     feature = "ed25519-dalek",
     not(feature = "k256"),
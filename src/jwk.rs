@@ -226,6 +226,7 @@ pub enum Algorithm {
     EdDSA,
     EdBlake2b,
     ES256,
+    ES384,
     ES256K,
     /// https://github.com/decentralized-identity/EcdsaSecp256k1RecoverySignature2020#es256k-r
     #[serde(rename = "ES256K-R")]
@@ -299,6 +300,18 @@ impl JWK {
         Ok(JWK::from(Params::EC(ec_params)))
     }
 
+    #[cfg(feature = "p384")]
+    pub fn generate_p384() -> Result<JWK, Error> {
+        let mut rng = rand::rngs::OsRng {};
+        let secret_key = p384::SecretKey::random(&mut rng);
+        // SecretKey zeroizes on drop
+        let sk_bytes = secret_key.to_bytes().to_vec();
+        let public_key: p384::PublicKey = secret_key.public_key();
+        let mut ec_params = ECParams::try_from(&public_key)?;
+        ec_params.ecc_private_key = Some(Base64urlUInt(sk_bytes));
+        Ok(JWK::from(Params::EC(ec_params)))
+    }
+
     pub fn get_algorithm(&self) -> Option<Algorithm> {
         if let Some(algorithm) = self.algorithm {
             return Some(algorithm);
@@ -322,6 +335,9 @@ impl JWK {
                     "P-256" => {
                         return Some(Algorithm::ES256);
                     }
+                    "P-384" => {
+                        return Some(Algorithm::ES384);
+                    }
                     _ => {}
                 }
             }
@@ -894,6 +910,23 @@ impl TryFrom<&ECParams> for p256::SecretKey {
     }
 }
 
+#[cfg(feature = "p384")]
+impl TryFrom<&ECParams> for p384::SecretKey {
+    type Error = Error;
+    fn try_from(params: &ECParams) -> Result<Self, Self::Error> {
+        let curve = params.curve.as_ref().ok_or(Error::MissingCurve)?;
+        if curve != "P-384" {
+            return Err(Error::CurveNotImplemented(curve.to_string()));
+        }
+        let private_key = params
+            .ecc_private_key
+            .as_ref()
+            .ok_or(Error::MissingPrivateKey)?;
+        let secret_key = p384::SecretKey::from_slice(&private_key.0)?;
+        Ok(secret_key)
+    }
+}
+
 #[cfg(feature = "k256")]
 impl TryFrom<&ECParams> for k256::PublicKey {
     type Error = Error;
@@ -928,6 +961,23 @@ impl TryFrom<&ECParams> for p256::PublicKey {
     }
 }
 
+#[cfg(feature = "p384")]
+impl TryFrom<&ECParams> for p384::PublicKey {
+    type Error = Error;
+    fn try_from(params: &ECParams) -> Result<Self, Self::Error> {
+        let curve = params.curve.as_ref().ok_or(Error::MissingCurve)?;
+        if curve != "P-384" {
+            return Err(Error::CurveNotImplemented(curve.to_string()));
+        }
+        const EC_UNCOMPRESSED_POINT_TAG: &[u8] = &[0x04];
+        let x = &params.x_coordinate.as_ref().ok_or(Error::MissingPoint)?.0;
+        let y = &params.y_coordinate.as_ref().ok_or(Error::MissingPoint)?.0;
+        let pk_data = [EC_UNCOMPRESSED_POINT_TAG, x.as_slice(), y.as_slice()].concat();
+        let public_key = p384::PublicKey::from_sec1_bytes(&pk_data)?;
+        Ok(public_key)
+    }
+}
+
 #[cfg(feature = "k256")]
 impl TryFrom<&k256::PublicKey> for ECParams {
     type Error = Error;
@@ -963,6 +1013,23 @@ impl TryFrom<&p256::PublicKey> for ECParams {
     }
 }
 
+#[cfg(feature = "p384")]
+impl TryFrom<&p384::PublicKey> for ECParams {
+    type Error = Error;
+    fn try_from(pk: &p384::PublicKey) -> Result<Self, Self::Error> {
+        use p384::elliptic_curve::sec1::ToEncodedPoint;
+        let encoded_point = pk.to_encoded_point(false);
+        let x = encoded_point.x().ok_or(Error::MissingPoint)?;
+        let y = encoded_point.y().ok_or(Error::MissingPoint)?;
+        Ok(ECParams {
+            curve: Some("P-384".to_string()),
+            x_coordinate: Some(Base64urlUInt(x.to_vec())),
+            y_coordinate: Some(Base64urlUInt(y.to_vec())),
+            ecc_private_key: None,
+        })
+    }
+}
+
 impl TryFrom<String> for Base64urlUInt {
     type Error = Error;
     fn try_from(data: String) -> Result<Self, Self::Error> {
@@ -1031,6 +1098,13 @@ mod tests {
         let _jwk = JWK::generate_p256().unwrap();
     }
 
+    #[test]
+    #[cfg(feature = "p384")]
+    fn p384_generate() {
+        let jwk = JWK::generate_p384().unwrap();
+        assert_eq!(jwk.get_algorithm(), Some(Algorithm::ES384));
+    }
+
     #[test]
     fn jwk_thumbprint() {
         // https://tools.ietf.org/html/rfc7638#section-3.1
@@ -151,6 +151,18 @@ pub fn sign_bytes(algorithm: Algorithm, data: &[u8], key: &JWK) -> Result<Vec<u8
                 let sig: p256::ecdsa::Signature = signing_key.try_sign(data)?;
                 sig.as_bytes().to_vec()
             }
+            #[cfg(feature = "p384")]
+            Algorithm::ES384 => {
+                use p384::ecdsa::signature::Signer;
+                let curve = ec.curve.as_ref().ok_or(Error::MissingCurve)?;
+                if curve != "P-384" {
+                    return Err(Error::CurveNotImplemented(curve.to_string()));
+                }
+                let secret_key = p384::SecretKey::try_from(ec)?;
+                let signing_key = p384::ecdsa::SigningKey::from(secret_key);
+                let sig: p384::ecdsa::Signature = signing_key.try_sign(data)?;
+                sig.to_bytes().to_vec()
+            }
             #[cfg(feature = "k256")]
             Algorithm::ES256K => {
                 use k256::ecdsa::signature::{Signature, Signer};
@@ -343,6 +355,18 @@ pub fn verify_bytes_warnable(
                     .map_err(|e| Error::Secp256r1Parse("Error parsing signature".to_string()))??;
                 verifying_key.verify(data, &sig)?;
             }
+            #[cfg(feature = "p384")]
+            Algorithm::ES384 => {
+                use p384::ecdsa::signature::Verifier;
+                let curve = ec.curve.as_ref().ok_or(Error::MissingCurve)?;
+                if curve != "P-384" {
+                    return Err(Error::CurveNotImplemented(curve.to_string()));
+                }
+                let public_key = p384::PublicKey::try_from(ec)?;
+                let verifying_key = p384::ecdsa::VerifyingKey::from(public_key);
+                let sig = p384::ecdsa::Signature::from_slice(signature)?;
+                verifying_key.verify(data, &sig)?;
+            }
             #[cfg(feature = "k256")]
             Algorithm::ES256K => {
                 use k256::ecdsa::signature::Verifier;
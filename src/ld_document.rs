@@ -0,0 +1,397 @@
+use std::collections::HashMap as Map;
+
+use crate::did_resolve::{DIDResolver, VersionTimeResolver};
+use crate::error::Error;
+use crate::jsonld::{json_to_dataset, StaticLoader};
+use crate::jwk::JWK;
+use crate::ldp::{LinkedDataDocument, LinkedDataProofs, ProofPreparation};
+use crate::one_or_many::OneOrMany;
+use crate::rdf::DataSet;
+use crate::vc::{
+    filter_proofs_at_created, find_proofs, get_verification_methods_for_purpose, verify_proof_set,
+    Check, LinkedDataProofOptions, Proof, ProofPurpose, ProofSetPolicy, VerificationResult,
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// An arbitrary JSON-LD document that can carry embedded Linked Data Proofs.
+///
+/// This allows signing and verifying objects that are not Verifiable Credentials, such as
+/// receipts, events, or supply-chain objects. The document's `@context` must define the terms
+/// used by the document and by its proofs, e.g. by including the [security
+/// context](crate::jsonld::SECURITY_V2_CONTEXT) before any application-specific contexts.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JsonLdDocument {
+    #[serde(rename = "@context")]
+    pub context: Value,
+    // This field is populated only when using
+    // embedded proofs such as LD-PROOF
+    //   https://w3c-ccg.github.io/ld-proofs/
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof: Option<OneOrMany<Proof>>,
+    #[serde(flatten)]
+    pub property_set: Map<String, Value>,
+}
+
+impl JsonLdDocument {
+    pub fn new(context: Value, property_set: Map<String, Value>) -> Self {
+        Self {
+            context,
+            proof: None,
+            property_set,
+        }
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, Error> {
+        let doc: Self = serde_json::from_str(s)?;
+        doc.validate()?;
+        Ok(doc)
+    }
+
+    pub fn from_value(value: Value) -> Result<Self, Error> {
+        let doc: Self = serde_json::from_value(value)?;
+        doc.validate()?;
+        Ok(doc)
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        match self.context {
+            Value::Null => return Err(Error::MissingContext),
+            Value::String(_) | Value::Object(_) => {}
+            Value::Array(ref contexts) if !contexts.is_empty() => {}
+            _ => return Err(Error::InvalidContext),
+        }
+        Ok(())
+    }
+
+    /// Get the `id` of the document's `issuer` property, if any. The issuer may be given either
+    /// as a string or as an object with an `id`.
+    pub fn get_issuer_id(&self) -> Option<&str> {
+        match self.property_set.get("issuer")? {
+            Value::String(issuer) => Some(issuer),
+            Value::Object(issuer) => issuer.get("id").and_then(Value::as_str),
+            _ => None,
+        }
+    }
+
+    async fn filter_proofs(
+        &self,
+        options: Option<LinkedDataProofOptions>,
+        resolver: &dyn DIDResolver,
+    ) -> Result<Vec<&Proof>, String> {
+        // Allow any of issuer's verification methods by default
+        let mut options = options.unwrap_or_default();
+        let proof_purpose = options
+            .proof_purpose
+            .clone()
+            .unwrap_or(ProofPurpose::AssertionMethod);
+        let allowed_vms = match options.verification_method.take() {
+            Some(vm) => vec![vm.to_string()],
+            None => {
+                if let Some(issuer) = self.get_issuer_id() {
+                    if options.resolve_at_created == Some(true) {
+                        return filter_proofs_at_created(
+                            &self.proof,
+                            &options,
+                            issuer,
+                            proof_purpose,
+                            resolver,
+                        )
+                        .await;
+                    }
+                    get_verification_methods_for_purpose(issuer, resolver, proof_purpose).await?
+                } else {
+                    return Ok(self
+                        .filter_proofs_by_controller(&options, proof_purpose, resolver)
+                        .await);
+                }
+            }
+        };
+        Ok(self
+            .proof
+            .iter()
+            .flatten()
+            .filter(|proof| proof.matches(&options, &allowed_vms))
+            .collect())
+    }
+
+    // Without an issuer, allow each proof's verification method if the DID in it lists it for the
+    // proof purpose. Proofs whose DID cannot be resolved are skipped.
+    async fn filter_proofs_by_controller(
+        &self,
+        options: &LinkedDataProofOptions,
+        proof_purpose: ProofPurpose,
+        resolver: &dyn DIDResolver,
+    ) -> Vec<&Proof> {
+        let mut matched_proofs = Vec::new();
+        for proof in self.proof.iter().flatten() {
+            let did = match proof.verification_method {
+                Some(ref vm) => vm.split('#').next().unwrap_or_default(),
+                None => continue,
+            };
+            let allowed_vms = if options.resolve_at_created == Some(true) {
+                let created = match proof.created {
+                    Some(created) => created,
+                    None => continue,
+                };
+                let resolver = VersionTimeResolver::new(resolver, created);
+                get_verification_methods_for_purpose(did, &resolver, proof_purpose.clone()).await
+            } else {
+                get_verification_methods_for_purpose(did, resolver, proof_purpose.clone()).await
+            };
+            match allowed_vms {
+                Ok(allowed_vms) if proof.matches(options, &allowed_vms) => {
+                    matched_proofs.push(proof);
+                }
+                _ => {}
+            }
+        }
+        matched_proofs
+    }
+
+    /// Verify the document's proofs. Proofs must use the verification method given in the
+    /// options, or else one of the verification methods of the document's `issuer` for the proof
+    /// purpose. If the document has no `issuer`, a proof's verification method must instead be
+    /// one of the verification methods, for the proof purpose, of the DID it belongs to.
+    pub async fn verify(
+        &self,
+        options: Option<LinkedDataProofOptions>,
+        resolver: &dyn DIDResolver,
+    ) -> VerificationResult {
+        let proof_set = options
+            .as_ref()
+            .and_then(|opts| opts.proof_set.clone())
+            .unwrap_or_default();
+        let resolve_at_created = options
+            .as_ref()
+            .and_then(|opts| opts.resolve_at_created)
+            .unwrap_or(false);
+        let set_options = options.clone().unwrap_or_default();
+        let proofs = match self.filter_proofs(options, resolver).await {
+            Ok(proofs) => proofs,
+            Err(err) => {
+                return VerificationResult::error(&format!("Unable to filter proofs: {}", err));
+            }
+        };
+        if proofs.is_empty() {
+            return VerificationResult::error("No applicable proof");
+        }
+        let all_proofs: Vec<&Proof> = self.proof.iter().flatten().collect();
+        if proof_set != ProofSetPolicy::Any {
+            return verify_proof_set(
+                self,
+                &all_proofs,
+                &proofs,
+                &set_options,
+                &proof_set,
+                resolver,
+                resolve_at_created,
+            )
+            .await;
+        }
+        let mut results = VerificationResult::new();
+        // Try verifying each proof until one succeeds
+        for proof in proofs {
+            let mut result = proof
+                .verify_in_set_at_created(self, &all_proofs, resolver, resolve_at_created)
+                .await;
+            if result.errors.is_empty() {
+                result.checks.push(Check::Proof);
+                return result;
+            };
+            results.append(&mut result);
+        }
+        results
+    }
+
+    // https://w3c-ccg.github.io/ld-proofs/
+    pub async fn generate_proof(
+        &self,
+        jwk: &JWK,
+        options: &LinkedDataProofOptions,
+        resolver: &dyn DIDResolver,
+    ) -> Result<Proof, Error> {
+        LinkedDataProofs::sign(self, options, resolver, jwk, None).await
+    }
+
+    /// Prepare to generate a linked data proof. Returns the signing input for the caller to sign
+    /// and then pass to [`ProofPreparation::complete`] to complete the proof.
+    pub async fn prepare_proof(
+        &self,
+        public_key: &JWK,
+        options: &LinkedDataProofOptions,
+        resolver: &dyn DIDResolver,
+    ) -> Result<ProofPreparation, Error> {
+        LinkedDataProofs::prepare(self, options, resolver, public_key, None).await
+    }
+
+    /// Generate a linked data proof with the given proof id, as part of a proof chain. See
+    /// [`Credential::generate_chained_proof`](crate::vc::Credential::generate_chained_proof).
+    pub async fn generate_chained_proof(
+        &self,
+        jwk: &JWK,
+        options: &LinkedDataProofOptions,
+        resolver: &dyn DIDResolver,
+        proof_id: &str,
+        previous_proof_ids: &[&str],
+    ) -> Result<Proof, Error> {
+        let previous_proofs = find_proofs(&self.proof, previous_proof_ids)?;
+        let mut ps = Map::<String, Value>::new();
+        ps.insert("id".to_string(), Value::String(proof_id.to_string()));
+        LinkedDataProofs::sign_chained(self, &previous_proofs, options, resolver, jwk, Some(ps))
+            .await
+    }
+
+    pub fn add_proof(&mut self, proof: Proof) {
+        self.proof = match self.proof.take() {
+            None => Some(OneOrMany::One(proof)),
+            Some(OneOrMany::One(existing_proof)) => {
+                Some(OneOrMany::Many(vec![existing_proof, proof]))
+            }
+            Some(OneOrMany::Many(mut proofs)) => {
+                proofs.push(proof);
+                Some(OneOrMany::Many(proofs))
+            }
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl LinkedDataDocument for JsonLdDocument {
+    fn get_contexts(&self) -> Result<Option<String>, Error> {
+        Ok(Some(serde_json::to_string(&self.context)?))
+    }
+
+    async fn to_dataset_for_signing(
+        &self,
+        parent: Option<&(dyn LinkedDataDocument + Sync)>,
+    ) -> Result<DataSet, Error> {
+        let mut copy = self.clone();
+        copy.proof = None;
+        let json = serde_json::to_string(&copy)?;
+        let more_contexts = match parent {
+            Some(parent) => parent.get_contexts()?,
+            None => None,
+        };
+        let mut loader = StaticLoader;
+        json_to_dataset(&json, more_contexts.as_ref(), false, None, &mut loader).await
+    }
+
+    fn to_value(&self) -> Result<Value, Error> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    fn get_issuer(&self) -> Option<&str> {
+        self.get_issuer_id()
+    }
+
+    fn get_default_proof_purpose(&self) -> Option<ProofPurpose> {
+        Some(ProofPurpose::AssertionMethod)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::did::example::DIDExample;
+    use crate::vc::URI;
+
+    const RECEIPT_JSON: &str = r#"{
+        "@context": [
+            "https://w3id.org/security/v2",
+            { "@vocab": "https://example.org/receipts#" }
+        ],
+        "id": "urn:uuid:b9b0b1c0-9a0e-4bd5-9a4f-2b1c3a8e0f11",
+        "type": "Receipt",
+        "issuer": "did:example:foo",
+        "amount": "12.00",
+        "currency": "EUR"
+    }"#;
+
+    #[test]
+    fn document_from_json() {
+        let doc = JsonLdDocument::from_json(RECEIPT_JSON).unwrap();
+        assert_eq!(doc.get_issuer_id(), Some("did:example:foo"));
+        assert!(doc.proof.is_none());
+        assert!(JsonLdDocument::from_json(r#"{"id": "urn:example:no-context"}"#).is_err());
+    }
+
+    #[async_std::test]
+    async fn sign_and_verify() {
+        let key: JWK = JWK {
+            key_id: Some("did:example:foo#key2".to_string()),
+            ..serde_json::from_str(include_str!("../tests/ed25519-2020-10-18.json")).unwrap()
+        };
+        let mut doc = JsonLdDocument::from_json(RECEIPT_JSON).unwrap();
+        let options = LinkedDataProofOptions {
+            verification_method: Some(URI::String("did:example:foo#key2".to_string())),
+            ..Default::default()
+        };
+        let proof = doc
+            .generate_proof(&key, &options, &DIDExample)
+            .await
+            .unwrap();
+        assert_eq!(proof.type_, "Ed25519Signature2018");
+        doc.add_proof(proof);
+
+        let result = doc.verify(None, &DIDExample).await;
+        println!("{:#?}", result);
+        assert!(result.errors.is_empty());
+        assert!(result.checks.contains(&Check::Proof));
+
+        // Round trip through JSON
+        let doc = JsonLdDocument::from_value(doc.to_value().unwrap()).unwrap();
+        assert!(doc.verify(None, &DIDExample).await.errors.is_empty());
+
+        // Tamper with the document
+        let mut bad_doc = doc.clone();
+        bad_doc
+            .property_set
+            .insert("amount".to_string(), Value::String("1200.00".to_string()));
+        assert!(!bad_doc.verify(None, &DIDExample).await.errors.is_empty());
+    }
+
+    #[async_std::test]
+    async fn verify_without_issuer() {
+        let key: JWK = JWK {
+            key_id: Some("did:example:foo#key2".to_string()),
+            ..serde_json::from_str(include_str!("../tests/ed25519-2020-10-18.json")).unwrap()
+        };
+        let mut doc = JsonLdDocument::from_json(RECEIPT_JSON).unwrap();
+        doc.property_set.remove("issuer");
+        let options = LinkedDataProofOptions {
+            verification_method: Some(URI::String("did:example:foo#key2".to_string())),
+            ..Default::default()
+        };
+        let proof = doc
+            .generate_proof(&key, &options, &DIDExample)
+            .await
+            .unwrap();
+        doc.add_proof(proof);
+
+        // The proof's verification method is checked against its DID.
+        let result = doc.verify(None, &DIDExample).await;
+        println!("{:#?}", result);
+        assert!(result.errors.is_empty());
+        let options = LinkedDataProofOptions {
+            resolve_at_created: Some(true),
+            ..Default::default()
+        };
+        assert!(doc
+            .verify(Some(options), &DIDExample)
+            .await
+            .errors
+            .is_empty());
+
+        // A verification method not listed by its DID is not allowed.
+        let mut bad_doc = doc.clone();
+        if let Some(OneOrMany::One(ref mut proof)) = bad_doc.proof {
+            proof.verification_method = Some("did:example:foo#unknown".to_string());
+        }
+        let result = bad_doc.verify(None, &DIDExample).await;
+        assert_eq!(result.errors, vec!["No applicable proof".to_string()]);
+    }
+}
@@ -12,14 +12,19 @@ const P2SIG_PREFIX: [u8; 4] = [54, 240, 44, 52];
 // use crate::did::{VerificationMethod, VerificationMethodMap};
 use crate::caip10::BlockchainAccountId;
 use crate::did::{Resource, VerificationMethodMap};
-use crate::did_resolve::{dereference, Content, DIDResolver, DereferencingInputMetadata};
+use crate::did_resolve::{
+    dereference, Content, ContentMetadata, DIDResolver, DereferencingInputMetadata,
+    DocumentMetadata,
+};
 #[cfg(feature = "keccak-hash")]
 use crate::eip712::TypedData;
 use crate::error::Error;
 use crate::hash::sha256;
+use crate::jsonld::{json_to_dataset, StaticLoader};
 use crate::jwk::Base64urlUInt;
 use crate::jwk::{Algorithm, Params as JWKParams, JWK};
 use crate::jws::Header;
+use crate::one_or_many::OneOrMany;
 use crate::rdf::DataSet;
 use crate::urdna2015;
 use crate::vc::{LinkedDataProofOptions, Proof, ProofPurpose, URI};
@@ -53,6 +58,25 @@ lazy_static! {
         let context_str = ssi_contexts::SOLVM;
         serde_json::from_str(context_str).unwrap()
     };
+    pub static ref BTCMSG_CONTEXT: Value = {
+        let context_str = ssi_contexts::BTCMSG_V0_1;
+        serde_json::from_str(context_str).unwrap()
+    };
+    /// Term definition for the `publicKeyJwk` proof property, used by proof types that carry
+    /// the signing key.
+    pub static ref PUBLIC_KEY_JWK_CONTEXT: Value = serde_json::json!({
+        "publicKeyJwk": {
+            "@id": "https://w3id.org/security#publicKeyJwk",
+            "@type": "@json"
+        }
+    });
+    /// Term definition for the `previousProof` proof property, used in proof chains.
+    pub static ref PREVIOUS_PROOF_CONTEXT: Value = serde_json::json!({
+        "previousProof": {
+            "@id": "https://w3id.org/security#previousProof",
+            "@type": "@id"
+        }
+    });
 }
 
 pub fn get_proof_suite(proof_type: &str) -> Result<&(dyn ProofSuite + Sync), Error> {
@@ -86,6 +110,12 @@ pub fn get_proof_suite(proof_type: &str) -> Result<&(dyn ProofSuite + Sync), Err
             #[cfg(feature = "keccak-hash")]
             &EthereumEip712Signature2021
         }
+        "BitcoinMessageSignature2021" => {
+            #[cfg(not(feature = "ripemd160"))]
+            return Err(Error::MissingFeatures("ripemd160"));
+            #[cfg(feature = "ripemd160")]
+            &BitcoinMessageSignature2021
+        }
         "TezosSignature2021" => &TezosSignature2021,
         "TezosJcsSignature2021" => &TezosJcsSignature2021,
         "SolanaSignature2021" => &SolanaSignature2021,
@@ -102,7 +132,7 @@ fn pick_proof_suite<'a, 'b>(
     let algorithm = jwk.get_algorithm().ok_or(Error::MissingAlgorithm)?;
     Ok(match algorithm {
         Algorithm::RS256 => &RsaSignature2018,
-        Algorithm::PS256 => &JsonWebSignature2020,
+        Algorithm::PS256 | Algorithm::ES384 => &JsonWebSignature2020,
         Algorithm::EdDSA | Algorithm::EdBlake2b => match verification_method {
             Some(URI::String(ref vm))
                 if (vm.starts_with("did:sol:") || vm.starts_with("did:pkh:sol:"))
@@ -119,6 +149,7 @@ fn pick_proof_suite<'a, 'b>(
                     &Ed25519BLAKE2BDigestSize20Base58CheckEncodedSignature2021
                 }
             }
+            Some(URI::String(ref vm)) if vm.starts_with("did:jwk:") => &JsonWebSignature2020,
             _ => &Ed25519Signature2018,
         },
         Algorithm::ES256 | Algorithm::ESBlake2b => match verification_method {
@@ -143,6 +174,7 @@ fn pick_proof_suite<'a, 'b>(
                     &EcdsaSecp256k1RecoverySignature2020
                 }
             }
+            Some(URI::String(ref vm)) if vm.starts_with("did:jwk:") => &JsonWebSignature2020,
             _ => &EcdsaSecp256k1Signature2019,
         },
         Algorithm::ES256KR =>
@@ -261,6 +293,10 @@ pub enum SigningInput {
     Micheline {
         micheline: String,
     },
+    #[serde(rename_all = "camelCase")]
+    BitcoinMessage {
+        bitcoin_message: String,
+    },
 }
 
 impl ProofPreparation {
@@ -408,6 +444,182 @@ impl LinkedDataProofs {
         let suite = get_proof_suite(proof.type_.as_str())?;
         suite.verify(proof, document, resolver).await
     }
+
+    /// Create a linked data proof that is part of a proof chain. The new proof references the
+    /// given previous proofs by id in its `previousProof` property, and signs over the document
+    /// together with those proofs. With no previous proofs, this is the same as
+    /// [`LinkedDataProofs::sign`].
+    ///
+    /// Proof chains are supported for proof types that use RDF dataset canonicalization.
+    pub async fn sign_chained(
+        document: &(dyn LinkedDataDocument + Sync),
+        previous_proofs: &[&Proof],
+        options: &LinkedDataProofOptions,
+        resolver: &dyn DIDResolver,
+        key: &JWK,
+        extra_proof_properties: Option<Map<String, Value>>,
+    ) -> Result<Proof, Error> {
+        if previous_proofs.is_empty() {
+            return Self::sign(document, options, resolver, key, extra_proof_properties).await;
+        }
+        let extra_proof_properties =
+            with_previous_proof_ids(previous_proofs, extra_proof_properties)?;
+        let chained_document = ChainedDocument::new(document, previous_proofs.to_vec());
+        Self::sign(
+            &chained_document,
+            options,
+            resolver,
+            key,
+            Some(extra_proof_properties),
+        )
+        .await
+    }
+
+    /// Prepare to create a linked data proof that is part of a proof chain. See
+    /// [`LinkedDataProofs::sign_chained`].
+    pub async fn prepare_chained(
+        document: &(dyn LinkedDataDocument + Sync),
+        previous_proofs: &[&Proof],
+        options: &LinkedDataProofOptions,
+        resolver: &dyn DIDResolver,
+        public_key: &JWK,
+        extra_proof_properties: Option<Map<String, Value>>,
+    ) -> Result<ProofPreparation, Error> {
+        if previous_proofs.is_empty() {
+            return Self::prepare(
+                document,
+                options,
+                resolver,
+                public_key,
+                extra_proof_properties,
+            )
+            .await;
+        }
+        let extra_proof_properties =
+            with_previous_proof_ids(previous_proofs, extra_proof_properties)?;
+        let chained_document = ChainedDocument::new(document, previous_proofs.to_vec());
+        Self::prepare(
+            &chained_document,
+            options,
+            resolver,
+            public_key,
+            Some(extra_proof_properties),
+        )
+        .await
+    }
+
+    /// Verify a linked data proof that may be part of a proof chain. If the proof has a
+    /// `previousProof` property, the referenced proofs are looked up by id in `proofs` (the
+    /// document's proof set) and included in the verification. The referenced proofs are not
+    /// themselves verified.
+    pub async fn verify_chained(
+        proof: &Proof,
+        document: &(dyn LinkedDataDocument + Sync),
+        proofs: &[&Proof],
+        resolver: &dyn DIDResolver,
+    ) -> Result<VerificationWarnings, Error> {
+        let previous_proof_ids = proof.get_previous_proof_ids()?;
+        if previous_proof_ids.is_empty() {
+            return Self::verify(proof, document, resolver).await;
+        }
+        let previous_proofs = previous_proof_ids
+            .into_iter()
+            .map(|id| {
+                proofs
+                    .iter()
+                    .find(|proof| proof.get_id() == Some(id))
+                    .copied()
+                    .ok_or_else(|| Error::MissingPreviousProof(id.to_string()))
+            })
+            .collect::<Result<Vec<&Proof>, Error>>()?;
+        let chained_document = ChainedDocument::new(document, previous_proofs);
+        Self::verify(proof, &chained_document, resolver).await
+    }
+}
+
+// Add a previousProof property referencing the given proofs to a set of proof properties.
+fn with_previous_proof_ids(
+    previous_proofs: &[&Proof],
+    extra_proof_properties: Option<Map<String, Value>>,
+) -> Result<Map<String, Value>, Error> {
+    let previous_proof_ids = previous_proofs
+        .iter()
+        .map(|proof| proof.get_id().ok_or(Error::MissingProofId))
+        .collect::<Result<Vec<&str>, Error>>()?;
+    let previous_proof = match previous_proof_ids.as_slice() {
+        [id] => Value::String(id.to_string()),
+        ids => serde_json::to_value(ids)?,
+    };
+    let mut properties = extra_proof_properties.unwrap_or_default();
+    properties.insert("previousProof".to_string(), previous_proof);
+    Ok(properties)
+}
+
+/// A linked data document together with the previous proofs that a chained proof signs over.
+///
+/// The previous proofs, including their signatures, are embedded in the document's `proof`
+/// property for canonicalization, so that the chained proof commits to them.
+struct ChainedDocument<'a> {
+    document: &'a (dyn LinkedDataDocument + Sync),
+    previous_proofs: Vec<&'a Proof>,
+}
+
+impl<'a> ChainedDocument<'a> {
+    fn new(document: &'a (dyn LinkedDataDocument + Sync), previous_proofs: Vec<&'a Proof>) -> Self {
+        Self {
+            document,
+            previous_proofs,
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl LinkedDataDocument for ChainedDocument<'_> {
+    fn get_contexts(&self) -> Result<Option<String>, Error> {
+        // Append the previousProof term definition to the document's contexts, so that it is
+        // available to the chained proof as well as to the embedded previous proofs.
+        let mut contexts = match self.document.get_contexts()? {
+            Some(contexts_string) => match serde_json::from_str(&contexts_string)? {
+                Value::Array(contexts) => contexts,
+                context => vec![context],
+            },
+            None => Vec::new(),
+        };
+        contexts.push(PREVIOUS_PROOF_CONTEXT.clone());
+        Ok(Some(serde_json::to_string(&contexts)?))
+    }
+
+    async fn to_dataset_for_signing(
+        &self,
+        parent: Option<&(dyn LinkedDataDocument + Sync)>,
+    ) -> Result<DataSet, Error> {
+        let mut value = self.document.to_value()?;
+        let object = value.as_object_mut().ok_or(Error::ExpectedObject)?;
+        object.insert(
+            "proof".to_string(),
+            serde_json::to_value(&self.previous_proofs)?,
+        );
+        let json = serde_json::to_string(&value)?;
+        let more_contexts = match parent {
+            Some(parent) => parent.get_contexts()?,
+            None => Some(serde_json::to_string(&*PREVIOUS_PROOF_CONTEXT)?),
+        };
+        let mut loader = StaticLoader;
+        json_to_dataset(&json, more_contexts.as_ref(), false, None, &mut loader).await
+    }
+
+    fn to_value(&self) -> Result<Value, Error> {
+        self.document.to_value()
+    }
+
+    fn get_default_proof_purpose(&self) -> Option<ProofPurpose> {
+        self.document.get_default_proof_purpose()
+    }
+
+    fn get_issuer(&self) -> Option<&str> {
+        self.document.get_issuer()
+    }
 }
 
 /// Resolve a verificationMethod to a key
@@ -420,12 +632,12 @@ pub async fn resolve_key(
     Ok(jwk)
 }
 
-/// Resolve a verificationMethod
+/// Resolve a verificationMethod. Fails if the DID of the verification method is deactivated.
 pub async fn resolve_vm(
     verification_method: &str,
     resolver: &dyn DIDResolver,
 ) -> Result<VerificationMethodMap, Error> {
-    let (res_meta, object, _meta) = dereference(
+    let (res_meta, object, meta) = dereference(
         resolver,
         verification_method,
         &DereferencingInputMetadata::default(),
@@ -434,6 +646,14 @@ pub async fn resolve_vm(
     if let Some(error) = res_meta.error {
         return Err(Error::DIDURLDereference(error));
     }
+    if let ContentMetadata::DIDDocument(DocumentMetadata {
+        deactivated: Some(true),
+        ..
+    }) = meta
+    {
+        let did = verification_method.split('#').next().unwrap_or_default();
+        return Err(Error::DIDDeactivated(did.to_string()));
+    }
     let vm = match object {
         Content::Object(Resource::VerificationMethod(vm)) => vm,
         Content::Null => return Err(Error::ResourceNotFound(verification_method.to_string())),
@@ -693,6 +913,40 @@ impl ProofSuite for RsaSignature2018 {
     }
 }
 
+/// Proof for a verification method with only a blockchain account id, such as a `did:pkh`
+/// Polkadot account: the proof contains the public key, which the verifier checks against the
+/// account id.
+async fn account_key_proof(
+    options: &LinkedDataProofOptions,
+    resolver: &dyn DIDResolver,
+    public_key: &JWK,
+    type_: &str,
+    extra_proof_properties: Option<Map<String, Value>>,
+) -> Result<Option<Proof>, Error> {
+    let verification_method = match options.verification_method {
+        Some(URI::String(ref vm)) => vm,
+        None => return Ok(None),
+    };
+    match resolve_vm(verification_method, resolver).await {
+        Ok(vm) if vm.blockchain_account_id.is_some() => match vm.get_jwk() {
+            Err(Error::MissingKey) => {}
+            _ => return Ok(None),
+        },
+        _ => return Ok(None),
+    }
+    let jwk_value = serde_json::to_value(public_key.to_public())?;
+    let mut props = extra_proof_properties;
+    props
+        .get_or_insert(Map::new())
+        .insert("publicKeyJwk".to_string(), jwk_value);
+    Ok(Some(Proof {
+        context: serde_json::json!([PUBLIC_KEY_JWK_CONTEXT.clone()]),
+        ..Proof::new(type_)
+            .with_options(options)
+            .with_properties(props)
+    }))
+}
+
 pub struct Ed25519Signature2018;
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -705,6 +959,17 @@ impl ProofSuite for Ed25519Signature2018 {
         key: &JWK,
         extra_proof_properties: Option<Map<String, Value>>,
     ) -> Result<Proof, Error> {
+        if let Some(proof) = account_key_proof(
+            options,
+            resolver,
+            key,
+            "Ed25519Signature2018",
+            extra_proof_properties.clone(),
+        )
+        .await?
+        {
+            return sign_proof(document, proof, key, Algorithm::EdDSA).await;
+        }
         sign(
             document,
             options,
@@ -724,6 +989,17 @@ impl ProofSuite for Ed25519Signature2018 {
         public_key: &JWK,
         extra_proof_properties: Option<Map<String, Value>>,
     ) -> Result<ProofPreparation, Error> {
+        if let Some(proof) = account_key_proof(
+            options,
+            resolver,
+            public_key,
+            "Ed25519Signature2018",
+            extra_proof_properties.clone(),
+        )
+        .await?
+        {
+            return prepare_proof(document, proof, Algorithm::EdDSA).await;
+        }
         prepare(
             document,
             options,
@@ -741,7 +1017,30 @@ impl ProofSuite for Ed25519Signature2018 {
         document: &(dyn LinkedDataDocument + Sync),
         resolver: &dyn DIDResolver,
     ) -> Result<VerificationWarnings, Error> {
-        verify(proof, document, resolver).await
+        let jws = proof.jws.as_ref().ok_or(Error::MissingProofSignature)?;
+        let verification_method = proof
+            .verification_method
+            .as_ref()
+            .ok_or(Error::MissingVerificationMethod)?;
+        let vm = resolve_vm(verification_method, resolver).await?;
+        let key = match vm.get_jwk() {
+            Ok(key) => key,
+            // VM has only a blockchain account id: the proof must have the public key.
+            Err(Error::MissingKey) if vm.blockchain_account_id.is_some() => {
+                let jwk_value = proof
+                    .property_set
+                    .as_ref()
+                    .and_then(|props| props.get("publicKeyJwk"))
+                    .ok_or(Error::MissingKey)?;
+                let jwk: JWK = serde_json::from_value(jwk_value.clone())?;
+                vm.match_jwk(&jwk)?;
+                jwk
+            }
+            Err(err) => return Err(err),
+        };
+        let message = to_jws_payload(document, proof).await?;
+        crate::jws::detached_verify(jws, &message, &key)?;
+        Ok(Default::default())
     }
     async fn complete(
         &self,
@@ -1533,6 +1832,124 @@ impl ProofSuite for EthereumPersonalSignature2021 {
     }
 }
 
+#[cfg(feature = "ripemd160")]
+pub struct BitcoinMessageSignature2021;
+#[cfg(feature = "ripemd160")]
+impl BitcoinMessageSignature2021 {
+    /// Get the message magic prefix for the chain of a verification method's blockchain account
+    /// id: Dogecoin for Dogecoin mainnet accounts, and Bitcoin otherwise.
+    fn message_magic(vm: &VerificationMethodMap) -> Result<&'static str, Error> {
+        use std::str::FromStr;
+        let account_id = match vm.blockchain_account_id {
+            Some(ref account_id) => BlockchainAccountId::from_str(account_id)?,
+            None => return Ok(crate::bip137::BITCOIN_MESSAGE_MAGIC),
+        };
+        Ok(
+            match (
+                account_id.chain_id.namespace.as_str(),
+                account_id.chain_id.reference.as_str(),
+            ) {
+                ("bip122", "1a91e3dace36e2be3bf030a65679fe82") => {
+                    crate::bip137::DOGECOIN_MESSAGE_MAGIC
+                }
+                _ => crate::bip137::BITCOIN_MESSAGE_MAGIC,
+            },
+        )
+    }
+}
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg(feature = "ripemd160")]
+impl ProofSuite for BitcoinMessageSignature2021 {
+    async fn sign(
+        &self,
+        document: &(dyn LinkedDataDocument + Sync),
+        options: &LinkedDataProofOptions,
+        resolver: &dyn DIDResolver,
+        key: &JWK,
+        extra_proof_properties: Option<Map<String, Value>>,
+    ) -> Result<Proof, Error> {
+        let verification_method = options
+            .verification_method
+            .as_ref()
+            .ok_or(Error::MissingVerificationMethod)?;
+        let vm = resolve_vm(&verification_method.to_string(), resolver).await?;
+        let magic = Self::message_magic(&vm)?;
+        let mut proof = Proof {
+            context: serde_json::json!([BTCMSG_CONTEXT.clone()]),
+            ..Proof::new("BitcoinMessageSignature2021")
+                .with_options(options)
+                .with_properties(extra_proof_properties)
+        };
+        let signing_string = string_from_document_and_options(document, &proof).await?;
+        let sig = crate::bip137::sign_message(magic, &signing_string, key)?;
+        proof.proof_value = Some(base64::encode(sig));
+        Ok(proof)
+    }
+
+    async fn prepare(
+        &self,
+        document: &(dyn LinkedDataDocument + Sync),
+        options: &LinkedDataProofOptions,
+        _resolver: &dyn DIDResolver,
+        _public_key: &JWK,
+        extra_proof_properties: Option<Map<String, Value>>,
+    ) -> Result<ProofPreparation, Error> {
+        let proof = Proof {
+            context: serde_json::json!([BTCMSG_CONTEXT.clone()]),
+            ..Proof::new("BitcoinMessageSignature2021")
+                .with_options(options)
+                .with_properties(extra_proof_properties)
+        };
+        let signing_string = string_from_document_and_options(document, &proof).await?;
+        Ok(ProofPreparation {
+            proof,
+            jws_header: None,
+            signing_input: SigningInput::BitcoinMessage {
+                bitcoin_message: signing_string,
+            },
+        })
+    }
+
+    async fn complete(
+        &self,
+        preparation: ProofPreparation,
+        signature: &str,
+    ) -> Result<Proof, Error> {
+        let mut proof = preparation.proof;
+        proof.proof_value = Some(signature.to_string());
+        Ok(proof)
+    }
+
+    async fn verify(
+        &self,
+        proof: &Proof,
+        document: &(dyn LinkedDataDocument + Sync),
+        resolver: &dyn DIDResolver,
+    ) -> Result<VerificationWarnings, Error> {
+        let sig_b64 = proof
+            .proof_value
+            .as_ref()
+            .ok_or(Error::MissingProofSignature)?;
+        let verification_method = proof
+            .verification_method
+            .as_ref()
+            .ok_or(Error::MissingVerificationMethod)?;
+        let vm = resolve_vm(verification_method, resolver).await?;
+        match &vm.type_[..] {
+            "EcdsaSecp256k1VerificationKey2019" => (),
+            "EcdsaSecp256k1RecoveryMethod2020" => (),
+            _ => return Err(Error::VerificationMethodMismatch),
+        };
+        let magic = Self::message_magic(&vm)?;
+        let sig = base64::decode(sig_b64)?;
+        let signing_string = string_from_document_and_options(document, proof).await?;
+        let jwk = crate::bip137::recover_message(magic, &signing_string, &sig)?;
+        vm.match_jwk(&jwk)?;
+        Ok(Default::default())
+    }
+}
+
 async fn micheline_from_document_and_options(
     document: &(dyn LinkedDataDocument + Sync),
     proof: &Proof,
@@ -1563,7 +1980,7 @@ async fn micheline_from_document_and_options_jcs(
     Ok(data)
 }
 
-#[cfg(feature = "keccak-hash")]
+#[cfg(any(feature = "keccak-hash", feature = "ripemd160"))]
 async fn string_from_document_and_options(
     document: &(dyn LinkedDataDocument + Sync),
     proof: &Proof,
@@ -2023,8 +2440,12 @@ fn document_has_context(
     context_uri: &str,
 ) -> Result<bool, Error> {
     let contexts_string = document.get_contexts()?.ok_or(Error::MissingContext)?;
-    let contexts: crate::vc::Contexts = serde_json::from_str(&contexts_string)?;
-    Ok(contexts.contains_uri(context_uri))
+    // Parse the contexts generically, since the document is not necessarily a credential or
+    // presentation.
+    let contexts: OneOrMany<Value> = serde_json::from_str(&contexts_string)?;
+    Ok(contexts
+        .into_iter()
+        .any(|context| context.as_str() == Some(context_uri)))
 }
 
 /// <https://w3c-ccg.github.io/lds-jws2020/>
@@ -2118,7 +2539,7 @@ impl JsonWebSignature2020 {
             Algorithm::EdDSA => (),
             Algorithm::ES256K => (),
             Algorithm::ES256 => (),
-            // Algorithm::ES384 => (), TODO
+            Algorithm::ES384 => (),
             Algorithm::PS256 => (),
             _ => return Err(Error::UnsupportedAlgorithm),
         }
@@ -2155,7 +2576,10 @@ impl JsonWebSignature2020 {
                     },
                     "P-256" => match algorithm {
                         Algorithm::ES256 => (),
-                        // Algorithm::ES384 => (), TODO
+                        _ => return Err(Error::UnsupportedAlgorithm),
+                    },
+                    "P-384" => match algorithm {
+                        Algorithm::ES384 => (),
                         _ => return Err(Error::UnsupportedAlgorithm),
                     },
                     _ => {
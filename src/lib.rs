@@ -1,12 +1,19 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
+pub mod algorand;
 pub mod bbs;
+#[cfg(feature = "k256")]
+pub mod bip137;
 pub mod blakesig;
 pub mod caip10;
 pub mod caip2;
 pub mod der;
 pub mod did;
 pub mod did_resolve;
+#[cfg(feature = "http-did")]
+pub mod did_resolve_server;
+#[cfg(feature = "keccak-hash")]
+pub mod eip4361;
 #[cfg(feature = "keccak-hash")]
 pub mod eip712;
 pub mod error;
@@ -17,6 +24,7 @@ pub mod jws;
 pub mod jwt;
 #[cfg(feature = "keccak-hash")]
 pub mod keccak_hash;
+pub mod ld_document;
 pub mod ldp;
 pub mod one_or_many;
 pub mod rdf;
@@ -24,7 +32,9 @@ pub mod revocation;
 #[cfg(feature = "ripemd160")]
 pub mod ripemd;
 pub mod soltx;
+pub mod ss58;
 pub mod ssh;
+pub mod trust;
 pub mod tzkey;
 pub mod urdna2015;
 pub mod vc;
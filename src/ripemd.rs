@@ -7,7 +7,9 @@ use crate::jwk::{Params, JWK};
 use k256::elliptic_curve::sec1::ToEncodedPoint;
 use ripemd160::{Digest, Ripemd160};
 
-pub fn hash_public_key(jwk: &JWK, version: u8) -> Result<String, Error> {
+/// Hash a secp256k1 public key with SHA-256 and RIPEMD-160 (HASH160), as used for Bitcoin and
+/// Cosmos addresses.
+fn hash160(jwk: &JWK) -> Result<Vec<u8>, Error> {
     let ec_params = match jwk.params {
         Params::EC(ref params) => params,
         _ => return Err(Error::UnsupportedKeyType),
@@ -18,7 +20,11 @@ pub fn hash_public_key(jwk: &JWK, version: u8) -> Result<String, Error> {
         return Err(Error::UnsupportedKeyType);
     }
     let pk_sha256 = sha256(pk_bytes.as_bytes())?;
-    let pk_ripemd160 = Ripemd160::digest(&pk_sha256);
+    Ok(Ripemd160::digest(&pk_sha256).to_vec())
+}
+
+pub fn hash_public_key(jwk: &JWK, version: u8) -> Result<String, Error> {
+    let pk_ripemd160 = hash160(jwk)?;
     let mut extended_ripemd160 = Vec::with_capacity(21);
     extended_ripemd160.extend_from_slice(&[version]);
     extended_ripemd160.extend_from_slice(&pk_ripemd160);
@@ -26,6 +32,17 @@ pub fn hash_public_key(jwk: &JWK, version: u8) -> Result<String, Error> {
     Ok(addr)
 }
 
+/// Get the [Bech32](https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki) address of a
+/// secp256k1 public key, with the given human-readable part, as used for Cosmos accounts
+/// (e.g. `cosmos1...`).
+#[cfg(feature = "bech32")]
+pub fn hash_public_key_bech32(jwk: &JWK, hrp: &str) -> Result<String, Error> {
+    use bech32::ToBase32;
+    let pk_ripemd160 = hash160(jwk)?;
+    let addr = bech32::encode(hrp, pk_ripemd160.to_base32(), bech32::Variant::Bech32)?;
+    Ok(addr)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,4 +68,20 @@ mod tests {
         let hash = hash_public_key(&jwk, 0).unwrap();
         assert_eq!(hash, "1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAs");
     }
+
+    #[test]
+    #[cfg(feature = "bech32")]
+    fn hash_bech32() {
+        let jwk: JWK = serde_json::from_value(serde_json::json!({
+            "kty": "EC",
+            "crv": "secp256k1",
+            "x": "yclqMZ0MtyVkKm1eBh2AyaUtsqT0l5RJM3g4SzRT96A",
+            "y": "yQzUwKnftWCJPGs-faGaHiYi1sxA6fGJVw2Px_LCNe8",
+        }))
+        .unwrap();
+        let addr = hash_public_key_bech32(&jwk, "cosmos").unwrap();
+        assert_eq!(addr, "cosmos1q8nmdyu99f50u7uv8309vuua4z3ew6xk0tgjpz");
+        let addr = hash_public_key_bech32(&jwk, "osmo").unwrap();
+        assert_eq!(addr, "osmo1q8nmdyu99f50u7uv8309vuua4z3ew6xk8smzhs");
+    }
 }
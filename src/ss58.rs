@@ -0,0 +1,116 @@
+//! [SS58](https://docs.substrate.io/reference/address-formats/) addresses, as used by Polkadot,
+//! Kusama and other Substrate-based chains.
+//!
+//! Only single-byte address types (0-63) and 32-byte account ids are supported.
+
+use crate::jwk::{Params, JWK};
+use thiserror::Error;
+
+/// Address type of Polkadot accounts
+pub const POLKADOT: u8 = 0;
+/// Address type of Kusama accounts
+pub const KUSAMA: u8 = 2;
+/// Generic Substrate address type
+pub const SUBSTRATE: u8 = 42;
+
+const CHECKSUM_PREFIX: &[u8] = b"SS58PRE";
+const CHECKSUM_LENGTH: usize = 2;
+const ACCOUNT_ID_LENGTH: usize = 32;
+
+#[derive(Error, Debug)]
+pub enum SS58Error {
+    #[error("Unsupported address type: {0}")]
+    AddressType(u8),
+    #[error("Unexpected address length: {0}")]
+    Length(usize),
+    #[error("Invalid address checksum")]
+    Checksum,
+    #[error("Expected Ed25519 key")]
+    KeyType,
+    #[error(transparent)]
+    Base58(#[from] bs58::decode::Error),
+}
+
+fn checksum(data: &[u8]) -> [u8; CHECKSUM_LENGTH] {
+    let mut state = blake2b_simd::State::new();
+    state.update(CHECKSUM_PREFIX);
+    state.update(data);
+    let hash = state.finalize();
+    let mut checksum = [0; CHECKSUM_LENGTH];
+    checksum.copy_from_slice(&hash.as_bytes()[..CHECKSUM_LENGTH]);
+    checksum
+}
+
+/// Encode an account id (public key) as an SS58 address.
+pub fn encode(address_type: u8, account_id: &[u8]) -> Result<String, SS58Error> {
+    if address_type >= 64 {
+        return Err(SS58Error::AddressType(address_type));
+    }
+    if account_id.len() != ACCOUNT_ID_LENGTH {
+        return Err(SS58Error::Length(account_id.len()));
+    }
+    let mut data = Vec::with_capacity(1 + ACCOUNT_ID_LENGTH + CHECKSUM_LENGTH);
+    data.push(address_type);
+    data.extend_from_slice(account_id);
+    let checksum = checksum(&data);
+    data.extend_from_slice(&checksum);
+    Ok(bs58::encode(data).into_string())
+}
+
+/// Decode an SS58 address into its address type and account id (public key).
+pub fn decode(address: &str) -> Result<(u8, Vec<u8>), SS58Error> {
+    let data = bs58::decode(address).into_vec()?;
+    if data.len() != 1 + ACCOUNT_ID_LENGTH + CHECKSUM_LENGTH {
+        return Err(SS58Error::Length(data.len()));
+    }
+    let address_type = data[0];
+    if address_type >= 64 {
+        return Err(SS58Error::AddressType(address_type));
+    }
+    let (payload, checksum_bytes) = data.split_at(1 + ACCOUNT_ID_LENGTH);
+    if checksum(payload) != checksum_bytes {
+        return Err(SS58Error::Checksum);
+    }
+    Ok((address_type, payload[1..].to_vec()))
+}
+
+/// Get the SS58 address of an Ed25519 public key.
+pub fn encode_public_key(jwk: &JWK, address_type: u8) -> Result<String, SS58Error> {
+    match jwk.params {
+        Params::OKP(ref params) if params.curve == "Ed25519" => {
+            encode(address_type, &params.public_key.0)
+        }
+        _ => Err(SS58Error::KeyType),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode() {
+        // https://docs.substrate.io/reference/command-line-tools/subkey/
+        let account_id =
+            hex::decode("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d")
+                .unwrap();
+        for (address_type, address) in &[
+            (POLKADOT, "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5"),
+            (KUSAMA, "HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F"),
+            (
+                SUBSTRATE,
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            ),
+        ] {
+            assert_eq!(&encode(*address_type, &account_id).unwrap(), address);
+            assert_eq!(
+                decode(address).unwrap(),
+                (*address_type, account_id.clone())
+            );
+        }
+        assert!(matches!(
+            decode("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp6"),
+            Err(SS58Error::Checksum)
+        ));
+    }
+}
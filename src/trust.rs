@@ -0,0 +1,510 @@
+//! Issuer trust policies, for deciding whether a credential's issuer is trusted to issue it.
+//!
+//! A policy is evaluated during verification when [`Check::IssuerTrust`] is requested, using
+//! [`Credential::verify_with_trust_policy`] or [`Credential::decode_verify_jwt_with_trust_policy`].
+use std::collections::HashMap;
+
+use crate::did_resolve::DIDResolver;
+use crate::vc::{Check, Credential, LinkedDataProofOptions, VerificationResult, URI};
+
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A policy deciding whether a credential's issuer is trusted for that credential.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait IssuerTrustPolicy: Sync {
+    /// Evaluate the policy for a credential. The credential's proofs are assumed to be verified
+    /// already. An untrusted issuer is reported as an error in the result.
+    async fn check(
+        &self,
+        credential: &Credential,
+        resolver: &dyn DIDResolver,
+    ) -> VerificationResult;
+}
+
+fn issuer_id(credential: &Credential) -> Result<&str, VerificationResult> {
+    match credential.issuer {
+        Some(ref issuer) => Ok(issuer.get_id_ref()),
+        None => Err(VerificationResult::error("Credential is missing issuer")),
+    }
+}
+
+/// Get the domain name that an issuer identifier is bound to, if any: the host of an HTTP(S)
+/// URL, or the domain of a `did:web` DID.
+pub fn issuer_domain(issuer: &str) -> Option<String> {
+    if let Some(method_specific_id) = issuer.strip_prefix("did:web:") {
+        let domain = method_specific_id
+            .split([':', '#', '?', '/'])
+            .next()?;
+        // Remove percent-encoded port.
+        let domain = domain.split("%3A").next()?;
+        return Some(domain.to_lowercase());
+    }
+    if issuer.starts_with("https:") || issuer.starts_with("http:") {
+        let url = url::Url::parse(issuer).ok()?;
+        return url.host_str().map(|host| host.to_lowercase());
+    }
+    None
+}
+
+/// Trust issuers by DID (or other issuer URI), DID method, or domain.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct IssuerAllowList {
+    /// Trusted issuer identifiers.
+    #[serde(default)]
+    pub issuers: Vec<String>,
+    /// Trusted DID method names, e.g. `"web"`.
+    #[serde(default)]
+    pub methods: Vec<String>,
+    /// Trusted domains, matched against `did:web` DIDs and HTTP(S) issuer URLs. Subdomains of a
+    /// listed domain are also trusted.
+    #[serde(default)]
+    pub domains: Vec<String>,
+}
+
+impl IssuerAllowList {
+    /// Check if the given issuer is allowed.
+    pub fn allows(&self, issuer: &str) -> bool {
+        if self.issuers.iter().any(|allowed| allowed == issuer) {
+            return true;
+        }
+        if let Some(method) = issuer
+            .strip_prefix("did:")
+            .and_then(|rest| rest.split(':').next())
+        {
+            if self.methods.iter().any(|allowed| allowed == method) {
+                return true;
+            }
+        }
+        if let Some(domain) = issuer_domain(issuer) {
+            return self.domains.iter().any(|allowed| {
+                let allowed = allowed.to_lowercase();
+                domain == allowed || domain.ends_with(&format!(".{}", allowed))
+            });
+        }
+        false
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IssuerTrustPolicy for IssuerAllowList {
+    async fn check(
+        &self,
+        credential: &Credential,
+        _resolver: &dyn DIDResolver,
+    ) -> VerificationResult {
+        let issuer = match issuer_id(credential) {
+            Ok(issuer) => issuer,
+            Err(result) => return result,
+        };
+        if !self.allows(issuer) {
+            return VerificationResult::error(&format!("Untrusted issuer: {}", issuer));
+        }
+        VerificationResult::new()
+    }
+}
+
+/// Trust issuers per credential type.
+///
+/// The issuer is trusted if, for at least one of the credential's types, it is allowed by the
+/// allow list for that type. Types without an allow list do not make any issuer trusted.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialTypeIssuers {
+    pub issuers_by_type: HashMap<String, IssuerAllowList>,
+}
+
+impl CredentialTypeIssuers {
+    /// Allow an issuer for a credential type.
+    pub fn allow(&mut self, type_: &str, issuer: &str) {
+        self.issuers_by_type
+            .entry(type_.to_string())
+            .or_default()
+            .issuers
+            .push(issuer.to_string());
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IssuerTrustPolicy for CredentialTypeIssuers {
+    async fn check(
+        &self,
+        credential: &Credential,
+        _resolver: &dyn DIDResolver,
+    ) -> VerificationResult {
+        let issuer = match issuer_id(credential) {
+            Ok(issuer) => issuer,
+            Err(result) => return result,
+        };
+        let trusted = credential.type_.any(|type_| {
+            self.issuers_by_type
+                .get(type_)
+                .map_or(false, |allow_list| allow_list.allows(issuer))
+        });
+        if !trusted {
+            return VerificationResult::error(&format!(
+                "Issuer {} is not trusted for credential type",
+                issuer
+            ));
+        }
+        VerificationResult::new()
+    }
+}
+
+/// Trust issuers accredited by a chain of accreditation credentials leading to a root of trust.
+///
+/// An accreditation credential is a credential of type `accreditation_type` whose
+/// `credentialSubject.id` is the accredited issuer. An issuer is trusted if it is a root, or if
+/// a verified, currently valid accreditation credential for it was issued by a trusted issuer, up
+/// to `max_depth` accreditations away from a root.
+pub struct AccreditationChain {
+    pub roots: IssuerAllowList,
+    pub accreditation_type: String,
+    pub accreditations: Vec<Credential>,
+    pub max_depth: usize,
+}
+
+impl AccreditationChain {
+    pub fn new(roots: IssuerAllowList, accreditation_type: &str) -> Self {
+        Self {
+            roots,
+            accreditation_type: accreditation_type.to_string(),
+            accreditations: Vec::new(),
+            max_depth: 3,
+        }
+    }
+
+    // Get the accreditation credentials with the given subject that are valid at the given time.
+    fn accreditations_for<'a>(
+        &'a self,
+        subject: &'a str,
+        now: DateTime<Utc>,
+    ) -> impl Iterator<Item = &'a Credential> {
+        self.accreditations.iter().filter(move |accreditation| {
+            accreditation
+                .type_
+                .contains(&self.accreditation_type)
+                && is_valid_at(accreditation, now)
+                && accreditation.credential_subject.any(|credential_subject| {
+                    matches!(credential_subject.id, Some(URI::String(ref id)) if id == subject)
+                })
+        })
+    }
+
+    /// Find the chain of issuers from a root to the given issuer, if the issuer is trusted.
+    pub async fn find_chain(
+        &self,
+        issuer: &str,
+        resolver: &dyn DIDResolver,
+    ) -> Option<Vec<String>> {
+        // Breadth-first search from the issuer toward a root. Each entry is a chain of issuers,
+        // ending with the issuer to be accredited.
+        let mut chains = vec![vec![issuer.to_string()]];
+        let now = Utc::now();
+        for _ in 0..=self.max_depth {
+            let mut next_chains = Vec::new();
+            for chain in chains {
+                let subject = &chain[0];
+                if self.roots.allows(subject) {
+                    return Some(chain);
+                }
+                for accreditation in self.accreditations_for(subject, now) {
+                    let accreditor = match accreditation.issuer {
+                        Some(ref issuer) => issuer.get_id(),
+                        None => continue,
+                    };
+                    if chain.contains(&accreditor) {
+                        continue;
+                    }
+                    let result = accreditation
+                        .verify(Some(LinkedDataProofOptions::default()), resolver)
+                        .await;
+                    if !result.errors.is_empty() {
+                        continue;
+                    }
+                    let mut next_chain = vec![accreditor];
+                    next_chain.extend(chain.iter().cloned());
+                    next_chains.push(next_chain);
+                }
+            }
+            chains = next_chains;
+        }
+        None
+    }
+}
+
+// Check that a credential has been issued and has not expired at the given time.
+fn is_valid_at(credential: &Credential, time: DateTime<Utc>) -> bool {
+    if let Some(ref issuance_date) = credential.issuance_date {
+        if DateTime::<FixedOffset>::from(issuance_date.clone()) > time {
+            return false;
+        }
+    }
+    if let Some(ref expiration_date) = credential.expiration_date {
+        if DateTime::<FixedOffset>::from(expiration_date.clone()) <= time {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IssuerTrustPolicy for AccreditationChain {
+    async fn check(
+        &self,
+        credential: &Credential,
+        resolver: &dyn DIDResolver,
+    ) -> VerificationResult {
+        let issuer = match issuer_id(credential) {
+            Ok(issuer) => issuer,
+            Err(result) => return result,
+        };
+        if self.find_chain(issuer, resolver).await.is_none() {
+            return VerificationResult::error(&format!(
+                "No accreditation chain found for issuer: {}",
+                issuer
+            ));
+        }
+        VerificationResult::new()
+    }
+}
+
+/// Require all of a set of policies to trust the issuer.
+pub struct AllOf(pub Vec<Box<dyn IssuerTrustPolicy>>);
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IssuerTrustPolicy for AllOf {
+    async fn check(
+        &self,
+        credential: &Credential,
+        resolver: &dyn DIDResolver,
+    ) -> VerificationResult {
+        let mut results = VerificationResult::new();
+        for policy in &self.0 {
+            results.append(&mut policy.check(credential, resolver).await);
+        }
+        results
+    }
+}
+
+/// Require at least one of a set of policies to trust the issuer.
+pub struct AnyOf(pub Vec<Box<dyn IssuerTrustPolicy>>);
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IssuerTrustPolicy for AnyOf {
+    async fn check(
+        &self,
+        credential: &Credential,
+        resolver: &dyn DIDResolver,
+    ) -> VerificationResult {
+        let mut results = VerificationResult::new();
+        for policy in &self.0 {
+            let mut result = policy.check(credential, resolver).await;
+            if result.errors.is_empty() {
+                return result;
+            }
+            results.append(&mut result);
+        }
+        if results.errors.is_empty() {
+            results.errors.push("No trust policy".to_string());
+        }
+        results
+    }
+}
+
+/// Evaluate a trust policy as the [`Check::IssuerTrust`] check.
+pub(crate) async fn check_issuer_trust(
+    credential: &Credential,
+    trust_policy: &dyn IssuerTrustPolicy,
+    resolver: &dyn DIDResolver,
+) -> VerificationResult {
+    let mut result = trust_policy.check(credential, resolver).await;
+    if result.errors.is_empty() {
+        result.checks.push(Check::IssuerTrust);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::did::example::DIDExample;
+    use crate::jwk::JWK;
+
+    fn credential(issuer: &str, subject: &str, type_: &str) -> Credential {
+        let vc_json = serde_json::json!({
+            "@context": [
+                "https://www.w3.org/2018/credentials/v1",
+                { "@vocab": "https://example.org/examples#" }
+            ],
+            "type": ["VerifiableCredential", type_],
+            "issuer": issuer,
+            "issuanceDate": "2021-01-01T00:00:00Z",
+            "credentialSubject": {
+                "id": subject
+            }
+        });
+        serde_json::from_value(vc_json).unwrap()
+    }
+
+    #[test]
+    fn allow_list() {
+        let allow_list = IssuerAllowList {
+            issuers: vec!["did:example:foo".to_string()],
+            methods: vec!["key".to_string()],
+            domains: vec!["example.org".to_string()],
+        };
+        assert!(allow_list.allows("did:example:foo"));
+        assert!(!allow_list.allows("did:example:bar"));
+        assert!(allow_list.allows("did:key:z6MkpTHR8VNsBxYAAWHut2Geadd9jSwuBV8xRoAnwWsdvktH"));
+        assert!(allow_list.allows("did:web:example.org"));
+        assert!(allow_list.allows("did:web:issuer.example.org%3A8443:users:alice"));
+        assert!(allow_list.allows("https://example.org/issuers/14"));
+        assert!(!allow_list.allows("did:web:example.org.evil.com"));
+        assert!(!allow_list.allows("https://notexample.org/"));
+    }
+
+    #[async_std::test]
+    async fn credential_type_issuers() {
+        let mut policy = CredentialTypeIssuers::default();
+        policy.allow("UniversityDegreeCredential", "did:example:foo");
+        let vc = credential(
+            "did:example:foo",
+            "did:example:bar",
+            "UniversityDegreeCredential",
+        );
+        assert!(policy.check(&vc, &DIDExample).await.errors.is_empty());
+        let vc = credential("did:example:foo", "did:example:bar", "AlumniCredential");
+        assert!(!policy.check(&vc, &DIDExample).await.errors.is_empty());
+        let vc = credential(
+            "did:example:bar",
+            "did:example:foo",
+            "UniversityDegreeCredential",
+        );
+        assert!(!policy.check(&vc, &DIDExample).await.errors.is_empty());
+    }
+
+    #[async_std::test]
+    async fn jwt_issuer_trust() {
+        let key: JWK =
+            serde_json::from_str(include_str!("../tests/ed25519-2020-10-18.json")).unwrap();
+        let vc = credential(
+            "did:example:foo",
+            "did:example:bar",
+            "UniversityDegreeCredential",
+        );
+        let options = LinkedDataProofOptions {
+            verification_method: Some(URI::String("did:example:foo#key2".to_string())),
+            checks: None,
+            created: None,
+            ..Default::default()
+        };
+        let jwt = vc
+            .generate_jwt(Some(&key), &options, &DIDExample)
+            .await
+            .unwrap();
+
+        let verify_options = LinkedDataProofOptions {
+            checks: Some(vec![Check::IssuerTrust]),
+            ..Default::default()
+        };
+        let mut policy = CredentialTypeIssuers::default();
+        policy.allow("AlumniCredential", "did:example:foo");
+        let (_, result) = Credential::decode_verify_jwt_with_trust_policy(
+            &jwt,
+            Some(verify_options.clone()),
+            &DIDExample,
+            &policy,
+        )
+        .await;
+        assert!(!result.errors.is_empty());
+
+        policy.allow("UniversityDegreeCredential", "did:example:foo");
+        let (_, result) = Credential::decode_verify_jwt_with_trust_policy(
+            &jwt,
+            Some(verify_options.clone()),
+            &DIDExample,
+            &policy,
+        )
+        .await;
+        println!("{:#?}", result);
+        assert!(result.errors.is_empty());
+        assert!(result.checks.contains(&Check::IssuerTrust));
+
+        let (_, result) =
+            Credential::decode_verify_jwt(&jwt, Some(verify_options), &DIDExample).await;
+        assert!(!result.errors.is_empty());
+    }
+
+    #[async_std::test]
+    async fn accreditation_chain() {
+        let key: JWK =
+            serde_json::from_str(include_str!("../tests/ed25519-2020-10-18.json")).unwrap();
+        let mut accreditation = credential(
+            "did:example:foo",
+            "did:example:bar",
+            "AccreditationCredential",
+        );
+        let options = LinkedDataProofOptions {
+            verification_method: Some(URI::String("did:example:foo#key2".to_string())),
+            ..Default::default()
+        };
+        let proof = accreditation
+            .generate_proof(&key, &options, &DIDExample)
+            .await
+            .unwrap();
+        accreditation.add_proof(proof);
+
+        let roots = IssuerAllowList {
+            issuers: vec!["did:example:foo".to_string()],
+            ..Default::default()
+        };
+        let mut policy = AccreditationChain::new(roots, "AccreditationCredential");
+        let vc = credential("did:example:bar", "did:example:baz", "ExampleCredential");
+        assert!(!policy.check(&vc, &DIDExample).await.errors.is_empty());
+
+        policy.accreditations.push(accreditation.clone());
+        let result = check_issuer_trust(&vc, &policy, &DIDExample).await;
+        println!("{:#?}", result);
+        assert!(result.errors.is_empty());
+        assert!(result.checks.contains(&Check::IssuerTrust));
+        assert_eq!(
+            policy.find_chain("did:example:bar", &DIDExample).await,
+            Some(vec![
+                "did:example:foo".to_string(),
+                "did:example:bar".to_string()
+            ])
+        );
+
+        // Accreditation with an invalid proof is ignored.
+        let mut bad_accreditation = accreditation.clone();
+        bad_accreditation.issuance_date = Some("2021-02-01T00:00:00Z".parse().unwrap());
+        policy.accreditations = vec![bad_accreditation];
+        assert!(!policy.check(&vc, &DIDExample).await.errors.is_empty());
+
+        // Expired accreditation is ignored.
+        let mut expired_accreditation = accreditation;
+        expired_accreditation.proof = None;
+        expired_accreditation.expiration_date = Some("2021-06-01T00:00:00Z".parse().unwrap());
+        let proof = expired_accreditation
+            .generate_proof(&key, &options, &DIDExample)
+            .await
+            .unwrap();
+        expired_accreditation.add_proof(proof);
+        assert!(expired_accreditation
+            .verify(None, &DIDExample)
+            .await
+            .errors
+            .is_empty());
+        policy.accreditations = vec![expired_accreditation];
+        assert!(!policy.check(&vc, &DIDExample).await.errors.is_empty());
+    }
+}
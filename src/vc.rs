@@ -1,8 +1,9 @@
 use std::collections::HashMap as Map;
 use std::convert::{TryFrom, TryInto};
+use std::num::NonZeroUsize;
 use std::str::FromStr;
 
-use crate::did_resolve::DIDResolver;
+use crate::did_resolve::{DIDResolver, VersionTimeResolver};
 use crate::error::Error;
 use crate::jsonld::{json_to_dataset, StaticLoader};
 use crate::jwk::{JWTKeys, JWK};
@@ -12,6 +13,7 @@ use crate::ldp::{
 };
 use crate::one_or_many::OneOrMany;
 use crate::rdf::DataSet;
+use crate::trust::{check_issuer_trust, IssuerTrustPolicy};
 
 use async_trait::async_trait;
 use chrono::{prelude::*, Duration, LocalResult};
@@ -484,6 +486,16 @@ pub struct LinkedDataProofOptions {
     pub eip712_domain: Option<crate::eip712::ProofInfo>,
     #[cfg(not(feature = "keccak-hash"))]
     pub eip712_domain: Option<()>,
+    /// Which proofs must verify, when verifying a document with multiple proofs (not standard in
+    /// vc-http-api). Default is [`ProofSetPolicy::Any`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof_set: Option<ProofSetPolicy>,
+    /// When verifying, resolve DIDs as of each proof's `created` time (not standard in
+    /// vc-http-api), so that proofs made with keys that have since been rotated out can be
+    /// verified, for DID methods that support `versionTime`. See
+    /// [`VersionTimeResolver`](crate::did_resolve::VersionTimeResolver).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolve_at_created: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -495,6 +507,42 @@ pub enum Check {
     #[serde(rename = "JWS")]
     JWS,
     CredentialStatus,
+    IssuerTrust,
+}
+
+/// Policy for verifying a document with multiple proofs (a proof set or proof chain).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum ProofSetPolicy {
+    /// At least one applicable proof must verify.
+    Any,
+    /// Every proof on the document must verify.
+    All,
+    /// The proofs with the given ids must be present and must verify.
+    Ids(Vec<String>),
+    /// Proofs from at least `threshold` distinct signers must verify. If `signers` is given,
+    /// only proofs whose verification method or its controller is listed are counted, and each
+    /// verification method must be authorized by its controller for the proof's purpose.
+    /// Otherwise, only applicable proofs (e.g. by the credential's issuer) are counted; since
+    /// those all share one controller, [`DistinctSigners::Controller`] requires `signers`.
+    #[serde(rename_all = "camelCase")]
+    Threshold {
+        threshold: NonZeroUsize,
+        distinct: DistinctSigners,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        signers: Option<Vec<String>>,
+    },
+}
+
+/// How signers are told apart when counting proofs toward a [`ProofSetPolicy::Threshold`].
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum DistinctSigners {
+    /// Each verification method counts as a signer.
+    VerificationMethod,
+    /// Each verification method controller counts as a signer.
+    Controller,
 }
 
 // https://w3c-ccg.github.io/vc-http-api/#/Verifier/verifyCredential
@@ -509,6 +557,10 @@ pub struct VerificationResult {
     pub warnings: Vec<String>,
     /// Errors
     pub errors: Vec<String>,
+    /// Signers whose proofs satisfied a [`ProofSetPolicy::Threshold`] (not standard in
+    /// vc-http-api)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signers: Vec<String>,
 }
 
 impl Default for ProofPurpose {
@@ -517,6 +569,12 @@ impl Default for ProofPurpose {
     }
 }
 
+impl Default for ProofSetPolicy {
+    fn default() -> Self {
+        Self::Any
+    }
+}
+
 impl Default for LinkedDataProofOptions {
     fn default() -> Self {
         Self {
@@ -528,6 +586,8 @@ impl Default for LinkedDataProofOptions {
             checks: Some(vec![Check::Proof]),
             eip712_domain: None,
             type_: None,
+            proof_set: None,
+            resolve_at_created: None,
         }
     }
 }
@@ -542,6 +602,7 @@ impl VerificationResult {
             checks: vec![],
             warnings: vec![],
             errors: vec![err.to_string()],
+            signers: vec![],
         }
     }
 
@@ -549,6 +610,7 @@ impl VerificationResult {
         self.checks.append(&mut other.checks);
         self.warnings.append(&mut other.warnings);
         self.errors.append(&mut other.errors);
+        self.signers.append(&mut other.signers);
     }
 
     pub fn with_error(mut self, error: String) -> Self {
@@ -564,11 +626,13 @@ impl From<Result<VerificationWarnings, Error>> for VerificationResult {
                 checks: vec![],
                 warnings,
                 errors: vec![],
+                signers: vec![],
             },
             Err(error) => Self {
                 checks: vec![],
                 warnings: vec![],
                 errors: vec![error.to_string()],
+                signers: vec![],
             },
         }
     }
@@ -717,6 +781,12 @@ impl From<VCDateTime> for String {
     }
 }
 
+impl From<VCDateTime> for DateTime<FixedOffset> {
+    fn from(vc_date_time: VCDateTime) -> DateTime<FixedOffset> {
+        vc_date_time.date_time
+    }
+}
+
 impl<Tz: chrono::TimeZone> From<DateTime<Tz>> for VCDateTime
 where
     chrono::DateTime<chrono::FixedOffset>: From<chrono::DateTime<Tz>>,
@@ -790,6 +860,199 @@ pub(crate) async fn pick_default_vm(
     Err(err)
 }
 
+// Verify the proofs required by a proof set policy other than ProofSetPolicy::Any. `proofs` are
+// all of the document's proofs, and `matched_proofs` are those applicable for the verification
+// options; at least one of the required proofs must be applicable. The required proofs, which may
+// be made by other signers than the issuer, must all match the other verification options
+// (`options`, e.g. proof purpose, challenge and domain).
+pub(crate) async fn verify_proof_set(
+    document: &(dyn LinkedDataDocument + Sync),
+    proofs: &[&Proof],
+    matched_proofs: &[&Proof],
+    options: &LinkedDataProofOptions,
+    policy: &ProofSetPolicy,
+    resolver: &dyn DIDResolver,
+    resolve_at_created: bool,
+) -> VerificationResult {
+    let set_options = LinkedDataProofOptions {
+        verification_method: None,
+        ..options.clone()
+    };
+    let required_proofs: Vec<&Proof> = match policy {
+        ProofSetPolicy::Threshold {
+            threshold,
+            distinct,
+            signers,
+        } => {
+            return verify_proof_threshold(
+                document,
+                proofs,
+                matched_proofs,
+                &set_options,
+                threshold.get(),
+                distinct,
+                signers.as_deref(),
+                resolver,
+                resolve_at_created,
+            )
+            .await;
+        }
+        ProofSetPolicy::Any | ProofSetPolicy::All => proofs.to_vec(),
+        ProofSetPolicy::Ids(ids) => {
+            let mut required_proofs = Vec::new();
+            for id in ids {
+                match proofs
+                    .iter()
+                    .find(|proof| proof.get_id() == Some(id.as_str()))
+                {
+                    Some(proof) => required_proofs.push(*proof),
+                    None => return VerificationResult::error(&format!("Missing proof: {}", id)),
+                }
+            }
+            required_proofs
+        }
+    };
+    if !required_proofs.iter().any(|proof| {
+        matched_proofs
+            .iter()
+            .any(|matched| std::ptr::eq(*matched, *proof))
+    }) {
+        return VerificationResult::error("No applicable proof");
+    }
+    for proof in &required_proofs {
+        if !proof.matches_options(&set_options) {
+            return VerificationResult::error(&format!(
+                "Proof does not match verification options: {}",
+                proof
+                    .get_id()
+                    .or(proof.verification_method.as_deref())
+                    .unwrap_or_default()
+            ));
+        }
+    }
+    let mut results = VerificationResult::new();
+    for proof in required_proofs {
+        let mut result = proof
+            .verify_in_set_at_created(document, proofs, resolver, resolve_at_created)
+            .await;
+        results.append(&mut result);
+    }
+    if results.errors.is_empty() {
+        results.checks.push(Check::Proof);
+    }
+    results
+}
+
+// Verify proofs from distinct signers, until a threshold is reached. Only proofs matching the
+// verification options (`options`, without a verification method) are counted. Proofs that fail
+// to verify are reported as warnings, since other signers may still satisfy the threshold.
+#[allow(clippy::too_many_arguments)]
+async fn verify_proof_threshold(
+    document: &(dyn LinkedDataDocument + Sync),
+    proofs: &[&Proof],
+    matched_proofs: &[&Proof],
+    options: &LinkedDataProofOptions,
+    threshold: usize,
+    distinct: &DistinctSigners,
+    signers: Option<&[String]>,
+    resolver: &dyn DIDResolver,
+    resolve_at_created: bool,
+) -> VerificationResult {
+    let candidate_proofs = match (signers, distinct) {
+        (Some(_), _) => proofs,
+        (None, DistinctSigners::VerificationMethod) => matched_proofs,
+        (None, DistinctSigners::Controller) => {
+            return VerificationResult::error(
+                "Counting distinct controllers requires a list of signers",
+            );
+        }
+    };
+    let mut results = VerificationResult::new();
+    let mut verified_signers: Vec<String> = Vec::new();
+    for proof in candidate_proofs {
+        if verified_signers.len() >= threshold {
+            break;
+        }
+        if !proof.matches_options(options) {
+            continue;
+        }
+        let vm = match proof.verification_method {
+            Some(ref vm) => vm,
+            None => continue,
+        };
+        let version_time_resolver;
+        let resolver: &dyn DIDResolver = match proof.created {
+            Some(created) if resolve_at_created => {
+                version_time_resolver = VersionTimeResolver::new(resolver, created);
+                &version_time_resolver
+            }
+            _ => resolver,
+        };
+        let vmm = match crate::ldp::resolve_vm(vm, resolver).await {
+            Ok(vmm) => vmm,
+            Err(err) => {
+                results.warnings.push(format!(
+                    "Unable to resolve verification method {}: {}",
+                    vm, err
+                ));
+                continue;
+            }
+        };
+        let signer = match distinct {
+            DistinctSigners::VerificationMethod => vm.to_string(),
+            DistinctSigners::Controller => vmm.controller.clone(),
+        };
+        if verified_signers.contains(&signer) {
+            continue;
+        }
+        if let Some(signers) = signers {
+            if !signers.contains(vm) && !signers.contains(&vmm.controller) {
+                continue;
+            }
+            let proof_purpose = proof.proof_purpose.clone().unwrap_or_default();
+            match crate::did_resolve::get_verification_methods(
+                &vmm.controller,
+                proof_purpose,
+                resolver,
+            )
+            .await
+            {
+                Ok(vmms) if vmms.contains_key(vm) => {}
+                _ => {
+                    results.warnings.push(format!(
+                        "Verification method {} not authorized by controller {}",
+                        vm, vmm.controller
+                    ));
+                    continue;
+                }
+            }
+        }
+        let result = proof.verify_in_set(document, proofs, resolver).await;
+        if result.errors.is_empty() {
+            results.warnings.extend(result.warnings);
+            verified_signers.push(signer);
+        } else {
+            results.warnings.extend(
+                result
+                    .errors
+                    .into_iter()
+                    .map(|err| format!("Proof by {} failed: {}", vm, err)),
+            );
+        }
+    }
+    if verified_signers.len() >= threshold {
+        results.checks.push(Check::Proof);
+    } else {
+        results.errors.push(format!(
+            "Proofs from {} of {} required signers verified",
+            verified_signers.len(),
+            threshold
+        ));
+    }
+    results.signers = verified_signers;
+    results
+}
+
 impl Credential {
     pub fn from_json(s: &str) -> Result<Self, Error> {
         let vp: Self = serde_json::from_str(s)?;
@@ -966,10 +1229,20 @@ impl Credential {
             checks,
             eip712_domain,
             type_,
+            proof_set,
+            resolve_at_created,
         } = options;
         if checks.is_some() {
             return Err(Error::UnencodableOptionClaim("checks".to_string()));
         }
+        if proof_set.is_some() {
+            return Err(Error::UnencodableOptionClaim("proofSet".to_string()));
+        }
+        if resolve_at_created.is_some() {
+            return Err(Error::UnencodableOptionClaim(
+                "resolveAtCreated".to_string(),
+            ));
+        }
         if created.is_some() {
             return Err(Error::UnencodableOptionClaim("created".to_string()));
         }
@@ -1038,6 +1311,40 @@ impl Credential {
         jwt: &str,
         options_opt: Option<LinkedDataProofOptions>,
         resolver: &dyn DIDResolver,
+    ) -> (Option<Self>, VerificationResult) {
+        Box::pin(Self::decode_verify_jwt_inner(
+            jwt,
+            options_opt,
+            resolver,
+            None,
+        ))
+        .await
+    }
+
+    /// Decode and verify a JWT credential, evaluating the given trust policy for the credential's
+    /// issuer if the [`Check::IssuerTrust`] check is requested.
+    pub async fn decode_verify_jwt_with_trust_policy(
+        jwt: &str,
+        options_opt: Option<LinkedDataProofOptions>,
+        resolver: &dyn DIDResolver,
+        trust_policy: &dyn IssuerTrustPolicy,
+    ) -> (Option<Self>, VerificationResult) {
+        Box::pin(Self::decode_verify_jwt_inner(
+            jwt,
+            options_opt,
+            resolver,
+            Some(trust_policy),
+        ))
+        .await
+    }
+
+    // The public verification functions box their inner future rather than embedding it, to
+    // keep the futures that callers hold (and debug builds copy) on the stack from growing.
+    async fn decode_verify_jwt_inner(
+        jwt: &str,
+        options_opt: Option<LinkedDataProofOptions>,
+        resolver: &dyn DIDResolver,
+        trust_policy: Option<&dyn IssuerTrustPolicy>,
     ) -> (Option<Self>, VerificationResult) {
         let checks = options_opt
             .as_ref()
@@ -1138,6 +1445,9 @@ impl Credential {
                     .errors
                     .push(format!("Unable to filter proofs: {}", err)),
             }
+            if checks.contains(&Check::IssuerTrust) {
+                results.append(&mut vc.check_trust(trust_policy, resolver).await);
+            }
             return (Some(vc), results);
         }
         // No JWS verified: try to verify a proof.
@@ -1159,6 +1469,9 @@ impl Credential {
         if checks.contains(&Check::CredentialStatus) {
             results.append(&mut vc.check_status(resolver).await);
         }
+        if checks.contains(&Check::IssuerTrust) {
+            results.append(&mut vc.check_trust(trust_policy, resolver).await);
+        }
         (Some(vc), results)
     }
 
@@ -1217,31 +1530,59 @@ impl Credential {
     ) -> Result<(Vec<&Proof>, bool), String> {
         // Allow any of issuer's verification methods by default
         let mut options = options.unwrap_or_default();
+        // https://w3c.github.io/did-core/#assertion
+        // assertionMethod is the verification relationship usually used for issuing
+        // VCs.
+        let proof_purpose = options
+            .proof_purpose
+            .clone()
+            .unwrap_or(ProofPurpose::AssertionMethod);
+        let mut resolve_at_created_did = None;
         let allowed_vms = match options.verification_method.take() {
             Some(vm) => vec![vm.to_string()],
             None => {
                 if let Some(ref issuer) = self.issuer {
                     let issuer_did = issuer.get_id();
-                    // https://w3c.github.io/did-core/#assertion
-                    // assertionMethod is the verification relationship usually used for issuing
-                    // VCs.
-                    let proof_purpose = options
-                        .proof_purpose
-                        .clone()
-                        .unwrap_or(ProofPurpose::AssertionMethod);
-                    get_verification_methods_for_purpose(&issuer_did, resolver, proof_purpose)
-                        .await?
+                    // With resolve_at_created, proofs' verification methods are resolved at
+                    // their created times instead; only a JWT needs the current ones.
+                    let allowed_vms =
+                        if options.resolve_at_created != Some(true) || jwt_params.is_some() {
+                            get_verification_methods_for_purpose(
+                                &issuer_did,
+                                resolver,
+                                proof_purpose.clone(),
+                            )
+                            .await?
+                        } else {
+                            Vec::new()
+                        };
+                    if options.resolve_at_created == Some(true) {
+                        resolve_at_created_did = Some(issuer_did);
+                    }
+                    allowed_vms
                 } else {
                     Vec::new()
                 }
             }
         };
-        let matched_proofs = self
-            .proof
-            .iter()
-            .flatten()
-            .filter(|proof| proof.matches(&options, &allowed_vms))
-            .collect();
+        let matched_proofs = match resolve_at_created_did {
+            Some(issuer_did) => {
+                filter_proofs_at_created(
+                    &self.proof,
+                    &options,
+                    &issuer_did,
+                    proof_purpose,
+                    resolver,
+                )
+                .await?
+            }
+            None => self
+                .proof
+                .iter()
+                .flatten()
+                .filter(|proof| proof.matches(&options, &allowed_vms))
+                .collect(),
+        };
         let matched_jwt = match jwt_params {
             Some((header, claims)) => jwt_matches(
                 header,
@@ -1260,11 +1601,40 @@ impl Credential {
         &self,
         options: Option<LinkedDataProofOptions>,
         resolver: &dyn DIDResolver,
+    ) -> VerificationResult {
+        Box::pin(self.verify_inner(options, resolver, None)).await
+    }
+
+    /// Verify the credential, evaluating the given trust policy for the credential's issuer if
+    /// the [`Check::IssuerTrust`] check is requested.
+    pub async fn verify_with_trust_policy(
+        &self,
+        options: Option<LinkedDataProofOptions>,
+        resolver: &dyn DIDResolver,
+        trust_policy: &dyn IssuerTrustPolicy,
+    ) -> VerificationResult {
+        Box::pin(self.verify_inner(options, resolver, Some(trust_policy))).await
+    }
+
+    async fn verify_inner(
+        &self,
+        options: Option<LinkedDataProofOptions>,
+        resolver: &dyn DIDResolver,
+        trust_policy: Option<&dyn IssuerTrustPolicy>,
     ) -> VerificationResult {
         let checks = options
             .as_ref()
             .and_then(|opts| opts.checks.clone())
             .unwrap_or_default();
+        let proof_set = options
+            .as_ref()
+            .and_then(|opts| opts.proof_set.clone())
+            .unwrap_or_default();
+        let resolve_at_created = options
+            .as_ref()
+            .and_then(|opts| opts.resolve_at_created)
+            .unwrap_or(false);
+        let set_options = options.clone().unwrap_or_default();
         let (proofs, _) = match self.filter_proofs(options, None, resolver).await {
             Ok(proofs) => proofs,
             Err(err) => {
@@ -1275,22 +1645,52 @@ impl Credential {
             return VerificationResult::error("No applicable proof");
             // TODO: say why, e.g. expired
         }
+        let all_proofs: Vec<&Proof> = self.proof.iter().flatten().collect();
         let mut results = VerificationResult::new();
-        // Try verifying each proof until one succeeds
-        for proof in proofs {
-            let mut result = proof.verify(self, resolver).await;
-            results.append(&mut result);
-            if result.errors.is_empty() {
-                results.checks.push(Check::Proof);
-                break;
-            };
+        if proof_set == ProofSetPolicy::Any {
+            // Try verifying each proof until one succeeds
+            for proof in proofs {
+                let mut result = proof
+                    .verify_in_set_at_created(self, &all_proofs, resolver, resolve_at_created)
+                    .await;
+                results.append(&mut result);
+                if result.errors.is_empty() {
+                    results.checks.push(Check::Proof);
+                    break;
+                };
+            }
+        } else {
+            results = verify_proof_set(
+                self,
+                &all_proofs,
+                &proofs,
+                &set_options,
+                &proof_set,
+                resolver,
+                resolve_at_created,
+            )
+            .await;
         }
         if checks.contains(&Check::CredentialStatus) {
             results.append(&mut self.check_status(resolver).await);
         }
+        if checks.contains(&Check::IssuerTrust) {
+            results.append(&mut self.check_trust(trust_policy, resolver).await);
+        }
         results
     }
 
+    async fn check_trust(
+        &self,
+        trust_policy: Option<&dyn IssuerTrustPolicy>,
+        resolver: &dyn DIDResolver,
+    ) -> VerificationResult {
+        match trust_policy {
+            Some(trust_policy) => check_issuer_trust(self, trust_policy, resolver).await,
+            None => VerificationResult::error("Issuer trust check requires a trust policy"),
+        }
+    }
+
     // https://w3c-ccg.github.io/ld-proofs/
     // https://w3c-ccg.github.io/lds-rsa2018/
     // https://w3c-ccg.github.io/vc-http-api/#/Issuer/issueCredential
@@ -1314,6 +1714,24 @@ impl Credential {
         LinkedDataProofs::prepare(self, options, resolver, public_key, None).await
     }
 
+    /// Generate a linked data proof with the given proof id, as part of a proof chain. The proof
+    /// references the credential's existing proofs with ids `previous_proof_ids` in its
+    /// `previousProof` property, and signs over them along with the credential.
+    pub async fn generate_chained_proof(
+        &self,
+        jwk: &JWK,
+        options: &LinkedDataProofOptions,
+        resolver: &dyn DIDResolver,
+        proof_id: &str,
+        previous_proof_ids: &[&str],
+    ) -> Result<Proof, Error> {
+        let previous_proofs = find_proofs(&self.proof, previous_proof_ids)?;
+        let mut ps = Map::<String, Value>::new();
+        ps.insert("id".to_string(), Value::String(proof_id.to_string()));
+        LinkedDataProofs::sign_chained(self, &previous_proofs, options, resolver, jwk, Some(ps))
+            .await
+    }
+
     pub fn add_proof(&mut self, proof: Proof) {
         self.proof = match self.proof.take() {
             None => Some(OneOrMany::One(proof)),
@@ -1485,10 +1903,20 @@ impl Presentation {
             checks,
             eip712_domain,
             type_,
+            proof_set,
+            resolve_at_created,
         } = options;
         if checks.is_some() {
             return Err(Error::UnencodableOptionClaim("checks".to_string()));
         }
+        if proof_set.is_some() {
+            return Err(Error::UnencodableOptionClaim("proofSet".to_string()));
+        }
+        if resolve_at_created.is_some() {
+            return Err(Error::UnencodableOptionClaim(
+                "resolveAtCreated".to_string(),
+            ));
+        }
         if created.is_some() {
             return Err(Error::UnencodableOptionClaim("created".to_string()));
         }
@@ -1727,6 +2155,23 @@ impl Presentation {
         LinkedDataProofs::sign(self, options, resolver, jwk, None).await
     }
 
+    /// Generate a linked data proof with the given proof id, as part of a proof chain. See
+    /// [`Credential::generate_chained_proof`].
+    pub async fn generate_chained_proof(
+        &self,
+        jwk: &JWK,
+        options: &LinkedDataProofOptions,
+        resolver: &dyn DIDResolver,
+        proof_id: &str,
+        previous_proof_ids: &[&str],
+    ) -> Result<Proof, Error> {
+        let previous_proofs = find_proofs(&self.proof, previous_proof_ids)?;
+        let mut ps = Map::<String, Value>::new();
+        ps.insert("id".to_string(), Value::String(proof_id.to_string()));
+        LinkedDataProofs::sign_chained(self, &previous_proofs, options, resolver, jwk, Some(ps))
+            .await
+    }
+
     pub fn add_proof(&mut self, proof: Proof) {
         self.proof = match self.proof.take() {
             None => Some(OneOrMany::One(proof)),
@@ -1751,26 +2196,47 @@ impl Presentation {
             proof_purpose: Some(ProofPurpose::Authentication),
             ..Default::default()
         });
+        let proof_purpose = options
+            .proof_purpose
+            .clone()
+            .unwrap_or(ProofPurpose::Authentication);
+        let mut resolve_at_created_did = None;
         let allowed_vms = match options.verification_method.take() {
             Some(vm) => vec![vm.to_string()],
             None => {
                 if let Some(URI::String(ref holder)) = self.holder {
-                    let proof_purpose = options
-                        .proof_purpose
-                        .clone()
-                        .unwrap_or(ProofPurpose::Authentication);
-                    get_verification_methods_for_purpose(holder, resolver, proof_purpose).await?
+                    if options.resolve_at_created == Some(true) {
+                        resolve_at_created_did = Some(holder);
+                    }
+                    // With resolve_at_created, proofs' verification methods are resolved at
+                    // their created times instead; only a JWT needs the current ones.
+                    if options.resolve_at_created != Some(true) || jwt_params.is_some() {
+                        get_verification_methods_for_purpose(
+                            holder,
+                            resolver,
+                            proof_purpose.clone(),
+                        )
+                        .await?
+                    } else {
+                        Vec::new()
+                    }
                 } else {
                     Vec::new()
                 }
             }
         };
-        let matched_proofs = self
-            .proof
-            .iter()
-            .flatten()
-            .filter(|proof| proof.matches(&options, &allowed_vms))
-            .collect();
+        let matched_proofs = match resolve_at_created_did {
+            Some(holder) => {
+                filter_proofs_at_created(&self.proof, &options, holder, proof_purpose, resolver)
+                    .await?
+            }
+            None => self
+                .proof
+                .iter()
+                .flatten()
+                .filter(|proof| proof.matches(&options, &allowed_vms))
+                .collect(),
+        };
         let matched_jwt = match jwt_params {
             Some((header, claims)) => jwt_matches(
                 header,
@@ -1800,6 +2266,18 @@ impl Presentation {
                 "credentialStatus check not valid for VerifiablePresentation",
             );
         }
+        let proof_set = options
+            .as_ref()
+            .and_then(|opts| opts.proof_set.clone())
+            .unwrap_or_default();
+        let resolve_at_created = options
+            .as_ref()
+            .and_then(|opts| opts.resolve_at_created)
+            .unwrap_or(false);
+        let set_options = options.clone().unwrap_or_else(|| LinkedDataProofOptions {
+            proof_purpose: Some(ProofPurpose::Authentication),
+            ..Default::default()
+        });
         let (proofs, _) = match self.filter_proofs(options, None, resolver).await {
             Ok(proofs) => proofs,
             Err(err) => {
@@ -1810,10 +2288,25 @@ impl Presentation {
             return VerificationResult::error("No applicable proof");
             // TODO: say why, e.g. expired
         }
+        let all_proofs: Vec<&Proof> = self.proof.iter().flatten().collect();
+        if proof_set != ProofSetPolicy::Any {
+            return verify_proof_set(
+                self,
+                &all_proofs,
+                &proofs,
+                &set_options,
+                &proof_set,
+                resolver,
+                resolve_at_created,
+            )
+            .await;
+        }
         let mut results = VerificationResult::new();
         // Try verifying each proof until one succeeds
         for proof in proofs {
-            let mut result = proof.verify(self, resolver).await;
+            let mut result = proof
+                .verify_in_set_at_created(self, &all_proofs, resolver, resolve_at_created)
+                .await;
             if result.errors.is_empty() {
                 result.checks.push(Check::Proof);
                 return result;
@@ -1885,6 +2378,31 @@ pub async fn get_verification_methods_for_purpose(
     Ok(vmms.into_keys().collect())
 }
 
+/// Filter proofs by the given options, allowing the verification methods that the given DID
+/// authorized for the proof purpose as of each proof's `created` time.
+pub(crate) async fn filter_proofs_at_created<'a>(
+    proofs: &'a Option<OneOrMany<Proof>>,
+    options: &LinkedDataProofOptions,
+    did: &str,
+    proof_purpose: ProofPurpose,
+    resolver: &dyn DIDResolver,
+) -> Result<Vec<&'a Proof>, String> {
+    let mut matched_proofs = Vec::new();
+    for proof in proofs.iter().flatten() {
+        let created = match proof.created {
+            Some(created) => created,
+            None => continue,
+        };
+        let resolver = VersionTimeResolver::new(resolver, created);
+        let allowed_vms =
+            get_verification_methods_for_purpose(did, &resolver, proof_purpose.clone()).await?;
+        if proof.matches(options, &allowed_vms) {
+            matched_proofs.push(proof);
+        }
+    }
+    Ok(matched_proofs)
+}
+
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl LinkedDataDocument for Presentation {
@@ -1962,14 +2480,20 @@ impl Proof {
 
     #[allow(clippy::ptr_arg)]
     pub fn matches(&self, options: &LinkedDataProofOptions, allowed_vms: &Vec<String>) -> bool {
+        if let Some(vm) = self.verification_method.as_ref() {
+            assert_local!(allowed_vms.contains(vm));
+        }
+        self.matches_options(options)
+    }
+
+    /// Check if the proof matches the given options, without restricting its verification method
+    /// to a set of allowed verification methods.
+    pub fn matches_options(&self, options: &LinkedDataProofOptions) -> bool {
         if let Some(ref verification_method) = options.verification_method {
             assert_local!(
                 self.verification_method.as_ref() == Some(&verification_method.to_string())
             );
         }
-        if let Some(vm) = self.verification_method.as_ref() {
-            assert_local!(allowed_vms.contains(vm));
-        }
         if let Some(created) = self.created {
             assert_local!(options.created.unwrap_or_else(now_ms) >= created);
         } else {
@@ -1999,6 +2523,78 @@ impl Proof {
             .await
             .into()
     }
+
+    /// Verify the proof as a member of the given proof set, resolving any `previousProof`
+    /// references against it.
+    pub async fn verify_in_set(
+        &self,
+        document: &(dyn LinkedDataDocument + Sync),
+        proofs: &[&Proof],
+        resolver: &dyn DIDResolver,
+    ) -> VerificationResult {
+        LinkedDataProofs::verify_chained(self, document, proofs, resolver)
+            .await
+            .into()
+    }
+
+    /// Verify the proof as a member of the given proof set. If `resolve_at_created` is true, DIDs
+    /// are resolved as of the proof's `created` time.
+    pub async fn verify_in_set_at_created(
+        &self,
+        document: &(dyn LinkedDataDocument + Sync),
+        proofs: &[&Proof],
+        resolver: &dyn DIDResolver,
+        resolve_at_created: bool,
+    ) -> VerificationResult {
+        match self.created {
+            Some(created) if resolve_at_created => {
+                let resolver = VersionTimeResolver::new(resolver, created);
+                self.verify_in_set(document, proofs, &resolver).await
+            }
+            _ => self.verify_in_set(document, proofs, resolver).await,
+        }
+    }
+
+    /// Get the proof's `id` property, if any.
+    pub fn get_id(&self) -> Option<&str> {
+        self.property_set.as_ref()?.get("id")?.as_str()
+    }
+
+    /// Get the ids of the proofs referenced by the proof's `previousProof` property.
+    pub fn get_previous_proof_ids(&self) -> Result<Vec<&str>, Error> {
+        let previous_proof = match self
+            .property_set
+            .as_ref()
+            .and_then(|ps| ps.get("previousProof"))
+        {
+            Some(previous_proof) => previous_proof,
+            None => return Ok(Vec::new()),
+        };
+        match previous_proof {
+            Value::String(id) => Ok(vec![id.as_str()]),
+            Value::Array(ids) => ids
+                .iter()
+                .map(|id| id.as_str().ok_or(Error::ExpectedString))
+                .collect(),
+            _ => Err(Error::ExpectedString),
+        }
+    }
+}
+
+/// Find proofs by id in a proof set.
+pub(crate) fn find_proofs<'a>(
+    proofs: &'a Option<OneOrMany<Proof>>,
+    ids: &[&str],
+) -> Result<Vec<&'a Proof>, Error> {
+    ids.iter()
+        .map(|id| {
+            proofs
+                .iter()
+                .flatten()
+                .find(|proof| proof.get_id() == Some(*id))
+                .ok_or_else(|| Error::MissingPreviousProof(id.to_string()))
+        })
+        .collect()
 }
 
 /// Evaluate if a JWT (header and claims) matches some linked data proof options.
@@ -2135,6 +2731,7 @@ fn verify_proof_consistency(proof: &Proof, dataset: &DataSet) -> Result<(), Erro
         ("TezosSignature2021", "https://w3id.org/security#TezosSignature2021") => (),
         ("TezosJcsSignature2021", "https://w3id.org/security#TezosJcsSignature2021") => (),
         ("SolanaSignature2021", "https://w3id.org/security#SolanaSignature2021") => (),
+        ("BitcoinMessageSignature2021", "https://demo.spruceid.com/ld/btcmsg/BitcoinMessageSignature2021") => (),
         _ => return Err(Error::UnexpectedTriple(type_triple.clone())),
     };
     let proof_id = &type_triple.subject;
@@ -2197,6 +2794,19 @@ fn verify_proof_consistency(proof: &Proof, dataset: &DataSet) -> Result<(), Erro
             .as_ref()
             .and_then(|cc| cc.get("capabilityChain")),
     )?;
+    for previous_proof_id in proof.get_previous_proof_ids()? {
+        graph_ref
+            .take(
+                Some(proof_id),
+                Some(&rdf::Predicate::IRIRef(rdf::IRIRef(
+                    "https://w3id.org/security#previousProof".to_string(),
+                ))),
+                Some(&rdf::Object::IRIRef(rdf::IRIRef(
+                    previous_proof_id.to_string(),
+                ))),
+            )
+            .ok_or(Error::MissingStatement)?;
+    }
 
     // Disallow additional unexpected statements
     if let Some(triple) = graph_ref.triples.into_iter().next() {
@@ -2265,6 +2875,7 @@ impl FromStr for Check {
             "proof" => Ok(Self::Proof),
             "JWS" => Ok(Self::JWS),
             "credentialStatus" => Ok(Self::CredentialStatus),
+            "issuerTrust" => Ok(Self::IssuerTrust),
             _ => Err(Error::UnsupportedCheck),
         }
     }
@@ -2283,6 +2894,7 @@ impl From<Check> for String {
             Check::Proof => "proof".to_string(),
             Check::JWS => "JWS".to_string(),
             Check::CredentialStatus => "credentialStatus".to_string(),
+            Check::IssuerTrust => "issuerTrust".to_string(),
         }
     }
 }
@@ -2617,6 +3229,450 @@ pub(crate) mod tests {
         assert!(verification_result.errors.len() >= 1);
     }
 
+    #[async_std::test]
+    async fn credential_proof_chain() {
+        let vc_str = r###"{
+            "@context": "https://www.w3.org/2018/credentials/v1",
+            "id": "http://example.org/credentials/3731",
+            "type": ["VerifiableCredential"],
+            "issuer": "did:example:foo",
+            "issuanceDate": "2020-08-19T21:41:50Z",
+            "credentialSubject": {
+                "id": "did:example:d23dd687a7dc6787646f2eb98d0"
+            }
+        }"###;
+        let mut vc: Credential = Credential::from_json_unsigned(vc_str).unwrap();
+        let issuer_key: JWK = serde_json::from_str(JWK_JSON).unwrap();
+        let endorser_key: JWK =
+            serde_json::from_str(include_str!("../tests/ed25519-2020-10-18.json")).unwrap();
+
+        let issue_options = LinkedDataProofOptions {
+            verification_method: Some(URI::String("did:example:foo#key1".to_string())),
+            ..Default::default()
+        };
+        let proof = vc
+            .generate_chained_proof(
+                &issuer_key,
+                &issue_options,
+                &DIDExample,
+                "urn:example:proof-1",
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(proof.get_id(), Some("urn:example:proof-1"));
+        vc.add_proof(proof);
+
+        let endorse_options = LinkedDataProofOptions {
+            verification_method: Some(URI::String("did:example:foo#key2".to_string())),
+            ..Default::default()
+        };
+        let proof = vc
+            .generate_chained_proof(
+                &endorser_key,
+                &endorse_options,
+                &DIDExample,
+                "urn:example:proof-2",
+                &["urn:example:proof-1"],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            proof.get_previous_proof_ids().unwrap(),
+            vec!["urn:example:proof-1"]
+        );
+        vc.add_proof(proof);
+        println!("{}", serde_json::to_string_pretty(&vc).unwrap());
+
+        let verify_all = LinkedDataProofOptions {
+            proof_set: Some(ProofSetPolicy::All),
+            ..Default::default()
+        };
+        let verification_result = vc.verify(Some(verify_all.clone()), &DIDExample).await;
+        println!("{:#?}", verification_result);
+        assert!(verification_result.errors.is_empty());
+        assert!(verification_result.checks.contains(&Check::Proof));
+
+        let verify_endorsement = LinkedDataProofOptions {
+            proof_set: Some(ProofSetPolicy::Ids(vec!["urn:example:proof-2".to_string()])),
+            ..Default::default()
+        };
+        let verification_result = vc
+            .verify(Some(verify_endorsement.clone()), &DIDExample)
+            .await;
+        println!("{:#?}", verification_result);
+        assert!(verification_result.errors.is_empty());
+
+        let verify_missing = LinkedDataProofOptions {
+            proof_set: Some(ProofSetPolicy::Ids(vec!["urn:example:proof-3".to_string()])),
+            ..Default::default()
+        };
+        let verification_result = vc.verify(Some(verify_missing), &DIDExample).await;
+        assert!(!verification_result.errors.is_empty());
+
+        // Every required proof must match the verification options, not just an applicable one.
+        let verify_all_rsa = LinkedDataProofOptions {
+            type_: Some("RsaSignature2018".to_string()),
+            ..verify_all.clone()
+        };
+        let verification_result = vc.verify(Some(verify_all_rsa), &DIDExample).await;
+        println!("{:#?}", verification_result);
+        assert!(!verification_result.errors.is_empty());
+
+        // Changing the first proof invalidates both it and the proof chained from it.
+        let mut bad_vc = vc.clone();
+        match bad_vc.proof {
+            Some(OneOrMany::Many(ref mut proofs)) => {
+                proofs[0].created = Some(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+            }
+            _ => unreachable!(),
+        }
+        let verification_result = bad_vc.verify(Some(verify_all), &DIDExample).await;
+        println!("{:#?}", verification_result);
+        assert!(verification_result.errors.len() >= 2);
+        let verification_result = bad_vc.verify(Some(verify_endorsement), &DIDExample).await;
+        assert!(!verification_result.errors.is_empty());
+    }
+
+    #[async_std::test]
+    async fn presentation_proof_chain() {
+        let mut vp = Presentation {
+            holder: Some(URI::String("did:example:foo".to_string())),
+            ..Default::default()
+        };
+        let holder_key: JWK = serde_json::from_str(JWK_JSON).unwrap();
+        let cosigner_key: JWK =
+            serde_json::from_str(include_str!("../tests/ed25519-2020-10-18.json")).unwrap();
+
+        let holder_options = LinkedDataProofOptions {
+            verification_method: Some(URI::String("did:example:foo#key1".to_string())),
+            proof_purpose: Some(ProofPurpose::Authentication),
+            challenge: Some("abc".to_string()),
+            ..Default::default()
+        };
+        let proof = vp
+            .generate_chained_proof(
+                &holder_key,
+                &holder_options,
+                &DIDExample,
+                "urn:example:proof-1",
+                &[],
+            )
+            .await
+            .unwrap();
+        vp.add_proof(proof);
+
+        let cosigner_options = LinkedDataProofOptions {
+            verification_method: Some(URI::String("did:example:foo#key2".to_string())),
+            challenge: Some("def".to_string()),
+            ..holder_options.clone()
+        };
+        let proof = vp
+            .generate_chained_proof(
+                &cosigner_key,
+                &cosigner_options,
+                &DIDExample,
+                "urn:example:proof-2",
+                &["urn:example:proof-1"],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            proof.get_previous_proof_ids().unwrap(),
+            vec!["urn:example:proof-1"]
+        );
+        vp.add_proof(proof);
+        println!("{}", serde_json::to_string_pretty(&vp).unwrap());
+
+        let verify_first = LinkedDataProofOptions {
+            proof_purpose: Some(ProofPurpose::Authentication),
+            challenge: Some("abc".to_string()),
+            proof_set: Some(ProofSetPolicy::Ids(vec!["urn:example:proof-1".to_string()])),
+            ..Default::default()
+        };
+        let verification_result = vp.verify(Some(verify_first), &DIDExample).await;
+        println!("{:#?}", verification_result);
+        assert!(verification_result.errors.is_empty());
+
+        // The chained proof was made for a different challenge.
+        let verify_all = LinkedDataProofOptions {
+            proof_purpose: Some(ProofPurpose::Authentication),
+            challenge: Some("abc".to_string()),
+            proof_set: Some(ProofSetPolicy::All),
+            ..Default::default()
+        };
+        let verification_result = vp.verify(Some(verify_all), &DIDExample).await;
+        println!("{:#?}", verification_result);
+        assert!(!verification_result.errors.is_empty());
+    }
+
+    #[async_std::test]
+    async fn credential_verify_threshold() {
+        let vc_str = r###"{
+            "@context": "https://www.w3.org/2018/credentials/v1",
+            "id": "http://example.org/credentials/3731",
+            "type": ["VerifiableCredential"],
+            "issuer": "did:example:foo",
+            "issuanceDate": "2020-08-19T21:41:50Z",
+            "credentialSubject": {
+                "id": "did:example:d23dd687a7dc6787646f2eb98d0"
+            }
+        }"###;
+        let mut vc: Credential = Credential::from_json_unsigned(vc_str).unwrap();
+        let rsa_key: JWK = serde_json::from_str(JWK_JSON).unwrap();
+        let ed25519_key: JWK =
+            serde_json::from_str(include_str!("../tests/ed25519-2020-10-18.json")).unwrap();
+        for (key, vm) in &[
+            (&rsa_key, "did:example:foo#key1"),
+            (&ed25519_key, "did:example:foo#key2"),
+        ] {
+            let issue_options = LinkedDataProofOptions {
+                verification_method: Some(URI::String(vm.to_string())),
+                ..Default::default()
+            };
+            let proof = vc
+                .generate_proof(key, &issue_options, &DIDExample)
+                .await
+                .unwrap();
+            vc.add_proof(proof);
+        }
+
+        let two_keys = LinkedDataProofOptions {
+            proof_set: Some(ProofSetPolicy::Threshold {
+                threshold: NonZeroUsize::new(2).unwrap(),
+                distinct: DistinctSigners::VerificationMethod,
+                signers: None,
+            }),
+            ..Default::default()
+        };
+        let verification_result = vc.verify(Some(two_keys), &DIDExample).await;
+        println!("{:#?}", verification_result);
+        assert!(verification_result.errors.is_empty());
+        assert_eq!(verification_result.signers.len(), 2);
+        assert!(verification_result
+            .signers
+            .contains(&"did:example:foo#key1".to_string()));
+
+        // Both proofs are from the same controller.
+        let two_controllers = LinkedDataProofOptions {
+            proof_set: Some(ProofSetPolicy::Threshold {
+                threshold: NonZeroUsize::new(2).unwrap(),
+                distinct: DistinctSigners::Controller,
+                signers: Some(vec!["did:example:foo".to_string()]),
+            }),
+            ..Default::default()
+        };
+        let verification_result = vc.verify(Some(two_controllers), &DIDExample).await;
+        println!("{:#?}", verification_result);
+        assert!(!verification_result.errors.is_empty());
+        assert_eq!(verification_result.signers, vec!["did:example:foo"]);
+
+        let no_signers = LinkedDataProofOptions {
+            proof_set: Some(ProofSetPolicy::Threshold {
+                threshold: NonZeroUsize::new(1).unwrap(),
+                distinct: DistinctSigners::Controller,
+                signers: None,
+            }),
+            ..Default::default()
+        };
+        let verification_result = vc.verify(Some(no_signers), &DIDExample).await;
+        assert!(!verification_result.errors.is_empty());
+
+        let zero_threshold = serde_json::from_value::<ProofSetPolicy>(serde_json::json!({
+            "threshold": {"threshold": 0, "distinct": "verificationMethod"}
+        }));
+        assert!(zero_threshold.is_err());
+
+        let listed_signer = LinkedDataProofOptions {
+            proof_set: Some(ProofSetPolicy::Threshold {
+                threshold: NonZeroUsize::new(1).unwrap(),
+                distinct: DistinctSigners::Controller,
+                signers: Some(vec!["did:example:foo".to_string()]),
+            }),
+            ..Default::default()
+        };
+        let verification_result = vc.verify(Some(listed_signer), &DIDExample).await;
+        println!("{:#?}", verification_result);
+        assert!(verification_result.errors.is_empty());
+
+        let unlisted_signer = LinkedDataProofOptions {
+            proof_set: Some(ProofSetPolicy::Threshold {
+                threshold: NonZeroUsize::new(1).unwrap(),
+                distinct: DistinctSigners::Controller,
+                signers: Some(vec!["did:example:bar".to_string()]),
+            }),
+            ..Default::default()
+        };
+        let verification_result = vc.verify(Some(unlisted_signer), &DIDExample).await;
+        assert!(!verification_result.errors.is_empty());
+        assert!(verification_result.signers.is_empty());
+    }
+
+    #[async_std::test]
+    async fn credential_verify_threshold_options() {
+        let vc_str = r###"{
+            "@context": "https://www.w3.org/2018/credentials/v1",
+            "id": "http://example.org/credentials/3731",
+            "type": ["VerifiableCredential"],
+            "issuer": "did:example:foo",
+            "issuanceDate": "2020-08-19T21:41:50Z",
+            "credentialSubject": {
+                "id": "did:example:d23dd687a7dc6787646f2eb98d0"
+            }
+        }"###;
+        let mut vc: Credential = Credential::from_json_unsigned(vc_str).unwrap();
+        let rsa_key: JWK = serde_json::from_str(JWK_JSON).unwrap();
+        let ed25519_key: JWK =
+            serde_json::from_str(include_str!("../tests/ed25519-2020-10-18.json")).unwrap();
+        // The second proof was made for a different challenge.
+        for (key, vm, challenge) in &[
+            (&rsa_key, "did:example:foo#key1", "abc"),
+            (&ed25519_key, "did:example:foo#key2", "xyz"),
+        ] {
+            let issue_options = LinkedDataProofOptions {
+                verification_method: Some(URI::String(vm.to_string())),
+                challenge: Some(challenge.to_string()),
+                ..Default::default()
+            };
+            let proof = vc
+                .generate_proof(key, &issue_options, &DIDExample)
+                .await
+                .unwrap();
+            vc.add_proof(proof);
+        }
+
+        let threshold = |threshold| LinkedDataProofOptions {
+            challenge: Some("abc".to_string()),
+            proof_set: Some(ProofSetPolicy::Threshold {
+                threshold: NonZeroUsize::new(threshold).unwrap(),
+                distinct: DistinctSigners::VerificationMethod,
+                signers: Some(vec!["did:example:foo".to_string()]),
+            }),
+            ..Default::default()
+        };
+        let verification_result = vc.verify(Some(threshold(2)), &DIDExample).await;
+        println!("{:#?}", verification_result);
+        assert!(!verification_result.errors.is_empty());
+        assert_eq!(verification_result.signers, vec!["did:example:foo#key1"]);
+
+        let verification_result = vc.verify(Some(threshold(1)), &DIDExample).await;
+        println!("{:#?}", verification_result);
+        assert!(verification_result.errors.is_empty());
+        assert_eq!(verification_result.signers, vec!["did:example:foo#key1"]);
+    }
+
+    #[async_std::test]
+    async fn credential_verify_rotated_and_deactivated() {
+        use crate::did::{Document, VerificationMethod};
+        use crate::did_resolve::{DocumentMetadata, ResolutionInputMetadata, ResolutionMetadata};
+
+        // did:example:foo, with key2 rotated out at the start of 2021, and optionally deactivated.
+        struct RotatingResolver {
+            deactivated: bool,
+        }
+        #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+        #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+        impl DIDResolver for RotatingResolver {
+            async fn resolve(
+                &self,
+                did: &str,
+                input_metadata: &ResolutionInputMetadata,
+            ) -> (
+                ResolutionMetadata,
+                Option<Document>,
+                Option<DocumentMetadata>,
+            ) {
+                let rotation_time = "2021-01-01T00:00:00Z";
+                let (res_meta, doc_opt, _) = DIDExample.resolve(did, input_metadata).await;
+                let mut doc = match doc_opt {
+                    Some(doc) => doc,
+                    None => return (res_meta, None, None),
+                };
+                let doc_meta = match input_metadata.version_time.as_deref() {
+                    Some(time) if time < rotation_time => DocumentMetadata::default(),
+                    _ => {
+                        let is_key2 = |vm: &VerificationMethod| vm.get_id(did).ends_with("#key2");
+                        for vms in doc
+                            .verification_method
+                            .iter_mut()
+                            .chain(doc.assertion_method.iter_mut())
+                        {
+                            vms.retain(|vm| !is_key2(vm));
+                        }
+                        DocumentMetadata {
+                            updated: Some(rotation_time.parse().unwrap()),
+                            deactivated: Some(self.deactivated),
+                            ..Default::default()
+                        }
+                    }
+                };
+                (res_meta, Some(doc), Some(doc_meta))
+            }
+        }
+
+        let vc_str = r###"{
+            "@context": "https://www.w3.org/2018/credentials/v1",
+            "id": "http://example.org/credentials/3731",
+            "type": ["VerifiableCredential"],
+            "issuer": "did:example:foo",
+            "issuanceDate": "2020-08-19T21:41:50Z",
+            "credentialSubject": {
+                "id": "did:example:d23dd687a7dc6787646f2eb98d0"
+            }
+        }"###;
+        let mut vc: Credential = Credential::from_json_unsigned(vc_str).unwrap();
+        let key: JWK =
+            serde_json::from_str(include_str!("../tests/ed25519-2020-10-18.json")).unwrap();
+        let issue_options = LinkedDataProofOptions {
+            verification_method: Some(URI::String("did:example:foo#key2".to_string())),
+            created: Some("2020-08-19T21:41:50Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let proof = vc
+            .generate_proof(&key, &issue_options, &DIDExample)
+            .await
+            .unwrap();
+        vc.add_proof(proof);
+
+        // The key has since been rotated out.
+        let rotated = RotatingResolver { deactivated: false };
+        let verification_result = vc.verify(None, &rotated).await;
+        println!("{:#?}", verification_result);
+        assert!(!verification_result.errors.is_empty());
+
+        let at_created = LinkedDataProofOptions {
+            resolve_at_created: Some(true),
+            ..Default::default()
+        };
+        let verification_result = vc.verify(Some(at_created.clone()), &rotated).await;
+        println!("{:#?}", verification_result);
+        assert!(verification_result.errors.is_empty());
+
+        // Deactivation is not hidden by resolving the DID at the proof's created time.
+        let deactivated = RotatingResolver { deactivated: true };
+        let verification_result = vc.verify(Some(at_created), &deactivated).await;
+        println!("{:#?}", verification_result);
+        assert!(!verification_result.errors.is_empty());
+
+        let key1_options = LinkedDataProofOptions {
+            verification_method: Some(URI::String("did:example:foo#key1".to_string())),
+            ..Default::default()
+        };
+        let rsa_key: JWK = serde_json::from_str(JWK_JSON).unwrap();
+        let proof = vc
+            .generate_proof(&rsa_key, &key1_options, &DIDExample)
+            .await
+            .unwrap();
+        vc.add_proof(proof);
+        assert!(vc.verify(None, &rotated).await.errors.is_empty());
+        let verification_result = vc.verify(None, &deactivated).await;
+        println!("{:#?}", verification_result);
+        assert!(verification_result
+            .errors
+            .iter()
+            .any(|err| err.contains("DID is deactivated")));
+    }
+
     #[async_std::test]
     async fn credential_issue_verify_bs58() {
         let vc_str = r###"{